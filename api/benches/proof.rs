@@ -0,0 +1,50 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_api::BasicError;
+use aptos_api_test_context::new_test_context;
+use aptos_config::config::NodeConfig;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+/// Back-to-back account proofs within the same epoch, which share the validator set of the
+/// latest epoch, along with what fetching that validator set costs with and without the cache.
+fn proof(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let _guard = runtime.enter();
+    let context = new_test_context("proof_bench".to_string(), NodeConfig::default(), false);
+    let ledger_info = context.get_latest_ledger_info();
+    let epoch = ledger_info.epoch.0;
+
+    let mut group = c.benchmark_group("latest_epoch_state");
+    group.bench_function("db", |b| {
+        b.iter(|| black_box(context.context.db.get_latest_epoch_state().unwrap()))
+    });
+    group.bench_function("cached", |b| {
+        b.iter(|| {
+            black_box(
+                context
+                    .context
+                    .latest_epoch_state::<BasicError>(epoch, &ledger_info)
+                    .unwrap(),
+            )
+        })
+    });
+    group.finish();
+
+    c.bench_function("account_proof", |b| {
+        b.iter(|| {
+            let resp = runtime.block_on(
+                context.reply(
+                    warp::test::request()
+                        .header("Accept", "application/x-bcs")
+                        .path("/v1/accounts/0x1/proof"),
+                ),
+            );
+            assert_eq!(resp.status(), 200);
+            black_box(resp)
+        })
+    });
+}
+
+criterion_group!(benches, proof);
+criterion_main!(benches);
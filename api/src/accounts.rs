@@ -11,12 +11,14 @@ use crate::{
         account_not_found, resource_not_found, struct_field_not_found, BadRequestError,
         BasicErrorWith404, BasicResponse, BasicResponseStatus, BasicResultWith404, InternalError,
     },
+    state::try_into_resource,
     ApiTags,
 };
 use anyhow::Context as AnyhowContext;
 use aptos_api_types::{
-    AccountData, Address, AptosErrorCode, AsConverter, LedgerInfo, MoveModuleBytecode,
-    MoveModuleId, MoveResource, MoveStructTag, StateKeyWrapper, U64,
+    AccountData, AccountResourcesAtVersion, Address, AptosErrorCode, AsConverter, LedgerInfo,
+    MoveModuleBytecode, MoveModuleId, MoveResource, MoveStructTag, RawAccountResourcesAtVersion,
+    StateKeyWrapper, U64,
 };
 use aptos_types::{
     account_config::{AccountResource, ObjectGroupResource},
@@ -28,6 +30,7 @@ use move_core_types::{
 };
 use poem_openapi::{
     param::{Path, Query},
+    payload::Json,
     OpenApi,
 };
 use std::{collections::BTreeMap, convert::TryInto, sync::Arc};
@@ -124,6 +127,48 @@ impl AccountsApi {
         .await
     }
 
+    /// Get account resources by type
+    ///
+    /// Retrieves the account resources of the given types for a given account, all at a single
+    /// ledger version. If the ledger version is not specified in the request, the latest ledger
+    /// version is used, resolved once for all the resources, so that they make a consistent
+    /// snapshot of the account even as the chain advances. The version comes with the resources.
+    ///
+    /// The resources come back in the order of the requested types. If any of them doesn't exist,
+    /// the server responds with a 404.
+    ///
+    /// The Aptos nodes prune account state history, via a configurable time window.
+    /// If the requested ledger version has been pruned, the server responds with a 410.
+    #[oai(
+        path = "/accounts/:address/resources",
+        method = "post",
+        operation_id = "get_account_resources_by_type",
+        tag = "ApiTags::Accounts"
+    )]
+    async fn get_account_resources_by_type(
+        &self,
+        accept_type: AcceptType,
+        /// Address of account with or without a `0x` prefix
+        address: Path<Address>,
+        /// Types of the resources to retrieve, e.g. `0x1::account::Account`
+        resource_types: Json<Vec<MoveStructTag>>,
+        /// Ledger version to get state of account
+        ///
+        /// If not provided, it will be the latest version
+        ledger_version: Query<Option<U64>>,
+    ) -> BasicResultWith404<AccountResourcesAtVersion> {
+        fail_point_poem("endpoint_get_account_resources_by_type")?;
+        self.context
+            .check_api_output_enabled("Get account resources by type", &accept_type)?;
+
+        let context = self.context.clone();
+        api_spawn_blocking(move || {
+            let account = Account::new(context, address.0, ledger_version.0, None, None)?;
+            account.resources_by_type(&accept_type, resource_types.0)
+        })
+        .await
+    }
+
     /// Get account modules
     ///
     /// Retrieves all account modules' bytecode for a given account at a specific ledger version.
@@ -376,6 +421,116 @@ impl Account {
         }
     }
 
+    /// Retrieves the move resources of the given types, all at the lookup ledger version
+    ///
+    /// * JSON: Return a JSON encoded version of [`AccountResourcesAtVersion`]
+    /// * BCS: Return a BCS encoded version of [`RawAccountResourcesAtVersion`]
+    pub fn resources_by_type(
+        self,
+        accept_type: &AcceptType,
+        resource_types: Vec<MoveStructTag>,
+    ) -> BasicResultWith404<AccountResourcesAtVersion> {
+        let max_resource_types = self.context.max_account_resources_page_size() as usize;
+        if resource_types.len() > max_resource_types {
+            return Err(BasicErrorWith404::bad_request_with_code(
+                format!(
+                    "Requested {} resource types, at most {} are allowed",
+                    resource_types.len(),
+                    max_resource_types
+                ),
+                AptosErrorCode::InvalidInput,
+                &self.latest_ledger_info,
+            ));
+        }
+        let tags = resource_types
+            .into_iter()
+            .map(|resource_type| {
+                StructTag::try_from(resource_type)
+                    .context("Failed to parse given resource type")
+                    .map_err(|err| {
+                        BasicErrorWith404::bad_request_with_code(
+                            err,
+                            AptosErrorCode::InvalidInput,
+                            &self.latest_ledger_info,
+                        )
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Every resource is read from the same state view, at the version resolved once
+        let state_view = self
+            .context
+            .state_view_at_version(self.ledger_version)
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &self.latest_ledger_info,
+                )
+            })?;
+        let converter = state_view.as_converter(
+            self.context.db.clone(),
+            self.context.table_info_reader.clone(),
+        );
+        let resources = tags
+            .iter()
+            .map(|tag| {
+                let bytes = converter
+                    .find_resource(&state_view, self.address, tag)
+                    .context(format!(
+                        "Failed to query DB to check for {} at {}",
+                        tag, self.address
+                    ))
+                    .map_err(|err| {
+                        BasicErrorWith404::internal_with_code(
+                            err,
+                            AptosErrorCode::InternalError,
+                            &self.latest_ledger_info,
+                        )
+                    })?
+                    .ok_or_else(|| {
+                        resource_not_found(
+                            self.address,
+                            tag,
+                            self.ledger_version,
+                            &self.latest_ledger_info,
+                        )
+                    })?;
+                Ok((tag, bytes))
+            })
+            .collect::<Result<Vec<_>, BasicErrorWith404>>()?;
+
+        match accept_type {
+            AcceptType::Json => {
+                let resources = resources
+                    .iter()
+                    .map(|(tag, bytes)| {
+                        try_into_resource(&converter, tag, bytes, &self.latest_ledger_info)
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                BasicResponse::try_from_json((
+                    AccountResourcesAtVersion {
+                        version: self.ledger_version.into(),
+                        resources,
+                    },
+                    &self.latest_ledger_info,
+                    BasicResponseStatus::Ok,
+                ))
+            },
+            AcceptType::Bcs => BasicResponse::try_from_bcs((
+                RawAccountResourcesAtVersion {
+                    version: self.ledger_version,
+                    resources: resources
+                        .into_iter()
+                        .map(|(_, bytes)| bytes.to_vec())
+                        .collect(),
+                },
+                &self.latest_ledger_info,
+                BasicResponseStatus::Ok,
+            )),
+        }
+    }
+
     /// Retrieves the move modules' bytecode associated with the account
     ///
     /// * JSON: Return a JSON encoded version of [`Vec<MoveModuleBytecode>`] with parsed ABIs
@@ -1,20 +1,22 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::response::{BasicErrorWith404, BasicResponse, BasicResponseStatus, BasicResultWith404};
 use crate::{
     accept_type::AcceptType,
     context::{api_spawn_blocking, Context},
     generate_error_response, generate_success_response,
-    response::{InternalError, ServiceUnavailableError},
+    response::{
+        BasicErrorWith404, BasicResponse, BasicResponseStatus, BasicResultWith404, InternalError,
+        ServiceUnavailableError,
+    },
     ApiTags,
 };
 use anyhow::Context as AnyhowContext;
-use aptos_api_types::{AptosErrorCode, U64};
+use aptos_api_types::{AptosErrorCode, LedgerInfo, U64};
 use aptos_crypto::HashValue;
-use aptos_types::block_info::BlockHeight;
-use aptos_types::transaction::Version;
-use poem_openapi::{param::Query, payload::Html, Object, OpenApi};
+use aptos_storage_interface::AptosDbError;
+use aptos_types::{block_info::BlockHeight, transaction::Version};
+use poem_openapi::{param::Query, payload::Html, Enum, Object, OpenApi};
 use serde::{Deserialize, Serialize};
 use std::{
     ops::Sub,
@@ -28,6 +30,8 @@ const OPEN_API_HTML: &str = include_str!("../doc/spec.html");
 generate_success_response!(HealthCheckResponse, (200, Ok));
 generate_error_response!(HealthCheckError, (503, ServiceUnavailable), (500, Internal));
 pub type HealthCheckResult<T> = poem::Result<HealthCheckResponse<T>, HealthCheckError>;
+generate_success_response!(ProofStatusResponse, (200, Ok), (503, ServiceUnavailable));
+pub type ProofStatusResult<T> = poem::Result<ProofStatusResponse<T>, HealthCheckError>;
 
 /// Basic API does healthchecking and shows the OpenAPI spec
 pub struct BasicApi {
@@ -35,9 +39,66 @@ pub struct BasicApi {
 }
 
 /// Representation of a successful healthcheck
+///
+/// Fields of the verbose healthcheck are left out of the JSON when unset, but always encoded in
+/// BCS, whose layout can't skip fields.
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize, Object)]
 pub struct HealthCheckSuccess {
     message: String,
+    /// Oldest transaction version that hasn't been pruned, only set for a verbose healthcheck
+    #[oai(skip_serializing_if_is_none)]
+    oldest_ledger_version: Option<U64>,
+    /// First version of the oldest block that hasn't been pruned, only set for a verbose
+    /// healthcheck
+    #[oai(skip_serializing_if_is_none)]
+    first_viable_version: Option<U64>,
+    /// Height of the oldest block that hasn't been pruned, only set for a verbose healthcheck
+    #[oai(skip_serializing_if_is_none)]
+    oldest_block_height: Option<U64>,
+    /// Latest version the state tree is committed at, only set for a verbose healthcheck
+    #[oai(skip_serializing_if_is_none)]
+    latest_state_checkpoint: Option<U64>,
+}
+
+/// Whether the node can currently serve proofs
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize, Object)]
+pub struct ProofStatus {
+    pub available: bool,
+    /// Why proofs can't be served, empty if they can
+    pub reasons: Vec<ProofDegradation>,
+}
+
+/// A reason the node can't serve proofs
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Eq, Serialize, Enum)]
+#[oai(rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ProofDegradation {
+    /// Proofs are only served as BCS, which is disabled
+    BcsOutputDisabled,
+    /// The state tree isn't committed at the latest ledger version, so the latest state can't be
+    /// proven
+    StateNotAtCheckpoint,
+    /// Neither a table info reader nor the DB indexer is available, so the table items of proven
+    /// resources can't be decoded
+    TableInfoReaderMissing,
+}
+
+/// Summary of the ledger versions the node holds, for clients to tell which versions it can serve
+/// state and proofs for
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize, Object)]
+pub struct LedgerSummary {
+    /// Version of the latest ledger info
+    pub ledger_version: U64,
+    /// Epoch of the latest ledger info
+    pub epoch: U64,
+    /// Oldest transaction version that hasn't been pruned, if any transaction is left
+    pub oldest_ledger_version: Option<U64>,
+    /// First version of the oldest block that hasn't been pruned
+    pub first_viable_version: U64,
+    /// Latest version the state tree is committed at, if any
+    pub latest_state_checkpoint: Option<U64>,
+    /// Version of the latest state snapshot at or before the latest ledger version, if any
+    pub snapshot_before: Option<U64>,
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize, Object)]
@@ -52,8 +113,46 @@ impl HealthCheckSuccess {
     pub fn new() -> Self {
         Self {
             message: "aptos-node:ok".to_string(),
+            oldest_ledger_version: None,
+            first_viable_version: None,
+            oldest_block_height: None,
+            latest_state_checkpoint: None,
         }
     }
+
+    /// Adds the pruning window of `context`, to tell which versions the node can serve proofs
+    /// for
+    fn with_pruning_window(
+        mut self,
+        context: &Context,
+        ledger_info: &LedgerInfo,
+    ) -> Result<Self, HealthCheckError> {
+        let internal = |name: &str, err: AptosDbError| {
+            HealthCheckError::internal_with_code(
+                format!("{}: {}", name, err),
+                AptosErrorCode::InternalError,
+                ledger_info,
+            )
+        };
+        let oldest_ledger_version = context
+            .db
+            .get_first_txn_version()
+            .map_err(|err| internal("first transaction version", err))?;
+        let (first_viable_version, oldest_block_height) = context
+            .db
+            .get_first_viable_block()
+            .map_err(|err| internal("first viable block", err))?;
+        let latest_state_checkpoint = context
+            .db
+            .get_latest_state_checkpoint_version()
+            .map_err(|err| internal("latest state checkpoint", err))?;
+
+        self.oldest_ledger_version = oldest_ledger_version.map(U64::from);
+        self.first_viable_version = Some(first_viable_version.into());
+        self.oldest_block_height = Some(oldest_block_height.into());
+        self.latest_state_checkpoint = latest_state_checkpoint.map(U64::from);
+        Ok(self)
+    }
 }
 
 #[OpenApi]
@@ -81,6 +180,9 @@ impl BasicApi {
     /// 200 if the following condition is true:
     ///
     /// `server_latest_ledger_info_timestamp >= server_current_time_timestamp - duration_secs`
+    ///
+    /// If verbose is true, the response also carries the pruning window of the node and its
+    /// latest state checkpoint, to tell which versions it can serve proofs for.
     #[oai(
         path = "/-/healthy",
         method = "get",
@@ -94,9 +196,22 @@ impl BasicApi {
         ///
         /// If not provided, the healthcheck will always succeed
         duration_secs: Query<Option<u32>>,
+        /// If true, include the pruning window and latest state checkpoint in the response
+        ///
+        /// If not provided, only the status message is returned
+        verbose: Query<Option<bool>>,
     ) -> HealthCheckResult<HealthCheckSuccess> {
         let context = self.context.clone();
-        let ledger_info = api_spawn_blocking(move || context.get_latest_ledger_info()).await?;
+        let (ledger_info, success) = api_spawn_blocking(move || {
+            let ledger_info = context.get_latest_ledger_info()?;
+            let success = if verbose.0.unwrap_or_default() {
+                HealthCheckSuccess::new().with_pruning_window(&context, &ledger_info)?
+            } else {
+                HealthCheckSuccess::new()
+            };
+            Ok::<_, HealthCheckError>((ledger_info, success))
+        })
+        .await?;
 
         // If we have a duration, check that it's close to the current time, otherwise it's ok
         if let Some(duration) = duration_secs.0 {
@@ -124,34 +239,144 @@ impl BasicApi {
             }
         }
         HealthCheckResponse::try_from_rust_value((
-            HealthCheckSuccess::new(),
+            success,
             &ledger_info,
             HealthCheckResponseStatus::Ok,
             &accept_type,
         ))
     }
 
+    /// Check proof serving health
+    ///
+    /// Reports whether the node can currently serve proofs, along with the reasons if it can't.
+    /// Returns a 503 when proof serving is degraded, so load balancers can route proof requests
+    /// away from the node.
     #[oai(
-        path = "/-/test",
+        path = "/-/proof_status",
         method = "get",
-        operation_id = "test",
+        operation_id = "proof_status",
         tag = "ApiTags::General"
     )]
-    async fn test(&self, accept_type: AcceptType) -> BasicResultWith404<TestPayload> {
-        let (ledger_info, ledger_version, _) = self.context.state_view(None)?;
+    async fn proof_status(&self, accept_type: AcceptType) -> ProofStatusResult<ProofStatus> {
+        let context = self.context.clone();
+        let (ledger_info, reasons) = api_spawn_blocking(move || {
+            let ledger_info = context.get_latest_ledger_info()?;
+            let mut reasons = vec![];
+            if !context.node_config.api.bcs_output_enabled {
+                reasons.push(ProofDegradation::BcsOutputDisabled);
+            }
+            let latest_state_checkpoint = context
+                .db
+                .get_latest_state_checkpoint_version()
+                .map_err(|err| {
+                    HealthCheckError::internal_with_code(
+                        format!("latest state checkpoint: {}", err),
+                        AptosErrorCode::InternalError,
+                        &ledger_info,
+                    )
+                })?;
+            if latest_state_checkpoint != Some(ledger_info.version()) {
+                reasons.push(ProofDegradation::StateNotAtCheckpoint);
+            }
+            // Table infos are read from the DB indexer if there is no reader
+            if context.table_info_reader.is_none() && !context.db.indexer_enabled() {
+                reasons.push(ProofDegradation::TableInfoReaderMissing);
+            }
+            Ok::<_, HealthCheckError>((ledger_info, reasons))
+        })
+        .await?;
 
-        let latest_li_w_sig = self
-            .context
-            .get_latest_ledger_info_with_signatures()
-            .map_err(|err| {
+        let status = if reasons.is_empty() {
+            ProofStatusResponseStatus::Ok
+        } else {
+            ProofStatusResponseStatus::ServiceUnavailable
+        };
+        ProofStatusResponse::try_from_rust_value((
+            ProofStatus {
+                available: reasons.is_empty(),
+                reasons,
+            },
+            &ledger_info,
+            status,
+            &accept_type,
+        ))
+    }
+
+    /// Get ledger summary
+    ///
+    /// Retrieves the latest ledger version and epoch along with the versions the node still holds:
+    /// the oldest unpruned transaction and block, the latest state checkpoint and the latest state
+    /// snapshot. Versions the node doesn't have are left out rather than reported as 0.
+    #[oai(
+        path = "/ledger/summary",
+        method = "get",
+        operation_id = "get_ledger_summary",
+        tag = "ApiTags::General"
+    )]
+    async fn ledger_summary(&self, accept_type: AcceptType) -> BasicResultWith404<LedgerSummary> {
+        self.context
+            .check_api_output_enabled("Get ledger summary", &accept_type)?;
+
+        let context = self.context.clone();
+        api_spawn_blocking(move || {
+            let ledger_info = context.get_latest_ledger_info()?;
+            let internal = |name: &str, err: AptosDbError| {
                 BasicErrorWith404::internal_with_code(
-                    err,
+                    format!("{}: {}", name, err),
                     AptosErrorCode::InternalError,
                     &ledger_info,
                 )
-            })?;
+            };
+            let oldest_ledger_version = context
+                .db
+                .get_first_txn_version()
+                .map_err(|err| internal("first transaction version", err))?;
+            let (first_viable_version, _) = context
+                .db
+                .get_first_viable_block()
+                .map_err(|err| internal("first viable block", err))?;
+            let latest_state_checkpoint = context
+                .db
+                .get_latest_state_checkpoint_version()
+                .map_err(|err| internal("latest state checkpoint", err))?;
+            let snapshot_before = context
+                .db
+                .get_state_snapshot_before(ledger_info.version() + 1)
+                .map_err(|err| internal("state snapshot before", err))?;
 
-        assert_eq!(latest_li_w_sig.ledger_info().version(), ledger_version, "Retrieved a latest signed ledger info with a different version than latest ledger info.");
+            let summary = LedgerSummary {
+                ledger_version: ledger_info.ledger_version,
+                epoch: ledger_info.epoch,
+                oldest_ledger_version: oldest_ledger_version.map(U64::from),
+                first_viable_version: first_viable_version.into(),
+                latest_state_checkpoint: latest_state_checkpoint.map(U64::from),
+                snapshot_before: snapshot_before.map(|(version, _)| U64::from(version)),
+            };
+            BasicResponse::try_from_rust_value((
+                summary,
+                &ledger_info,
+                BasicResponseStatus::Ok,
+                &accept_type,
+            ))
+        })
+        .await
+    }
+
+    #[oai(
+        path = "/-/test",
+        method = "get",
+        operation_id = "test",
+        tag = "ApiTags::General"
+    )]
+    async fn test(&self, accept_type: AcceptType) -> BasicResultWith404<TestPayload> {
+        let (mut ledger_info, mut ledger_version, _) = self.context.state_view(None)?;
+        let mut signed_version = self.latest_signed_version(&ledger_info)?;
+        // A new ledger info can be committed between the two reads, read both again once
+        if signed_version != ledger_version {
+            (ledger_info, ledger_version, _) = self.context.state_view(None)?;
+            signed_version = self.latest_signed_version(&ledger_info)?;
+        }
+        check_signed_version(signed_version, &ledger_info)?;
 
         let (first_viable_version, _): (Version, BlockHeight) =
             self.context.db.get_first_viable_block().map_err(|err| {
@@ -223,3 +448,108 @@ impl BasicApi {
         }
     }
 }
+
+impl BasicApi {
+    /// Version of the latest signed ledger info
+    fn latest_signed_version(
+        &self,
+        ledger_info: &LedgerInfo,
+    ) -> Result<Version, BasicErrorWith404> {
+        self.context
+            .get_latest_ledger_info_with_signatures()
+            .map(|latest_li_w_sig| latest_li_w_sig.ledger_info().version())
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    ledger_info,
+                )
+            })
+    }
+}
+
+/// Checks that the latest signed ledger info is at the version of `ledger_info`, which was read
+/// separately. They only differ while a new ledger info is being committed, so the caller can
+/// retry.
+fn check_signed_version<E: ServiceUnavailableError>(
+    signed_version: Version,
+    ledger_info: &LedgerInfo,
+) -> Result<(), E> {
+    if signed_version == ledger_info.version() {
+        Ok(())
+    } else {
+        Err(E::service_unavailable_with_code(
+            format!(
+                "Latest signed ledger info at version {} doesn't match latest ledger info at version {}",
+                signed_version,
+                ledger_info.version()
+            ),
+            AptosErrorCode::InternalError,
+            ledger_info,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_signed_version, HealthCheckSuccess};
+    use crate::response::BasicErrorWith404;
+    use aptos_api_types::LedgerInfo;
+    use aptos_crypto::HashValue;
+    use aptos_types::{
+        aggregate_signature::AggregateSignature, block_info::BlockInfo, chain_id::ChainId,
+        ledger_info::LedgerInfoWithSignatures,
+    };
+    use poem_openapi::types::ToJSON;
+
+    fn ledger_info(version: u64) -> LedgerInfo {
+        let ledger_info = aptos_types::ledger_info::LedgerInfo::new(
+            BlockInfo::new(1, 0, HashValue::zero(), HashValue::zero(), version, 0, None),
+            HashValue::zero(),
+        );
+        LedgerInfo::new(
+            &ChainId::test(),
+            &LedgerInfoWithSignatures::new(ledger_info, AggregateSignature::empty()),
+            0,
+            0,
+            0,
+        )
+    }
+
+    #[test]
+    fn test_check_signed_version() {
+        let ledger_info = ledger_info(10);
+        assert!(check_signed_version::<BasicErrorWith404>(10, &ledger_info).is_ok());
+
+        // A ledger info committed between the two reads
+        assert!(matches!(
+            check_signed_version::<BasicErrorWith404>(11, &ledger_info),
+            Err(BasicErrorWith404::ServiceUnavailable(..))
+        ));
+    }
+
+    #[test]
+    fn test_health_check_success_encoding() {
+        let verbose = HealthCheckSuccess {
+            oldest_ledger_version: Some(1.into()),
+            first_viable_version: Some(2.into()),
+            oldest_block_height: Some(3.into()),
+            latest_state_checkpoint: None,
+            ..HealthCheckSuccess::new()
+        };
+        for success in [HealthCheckSuccess::new(), verbose.clone()] {
+            let bytes = bcs::to_bytes(&success).unwrap();
+            assert_eq!(
+                bcs::from_bytes::<HealthCheckSuccess>(&bytes).unwrap(),
+                success
+            );
+        }
+
+        // Only the JSON leaves out unset fields
+        let json = HealthCheckSuccess::new().to_json().unwrap();
+        assert_eq!(json, serde_json::json!({ "message": "aptos-node:ok" }));
+        let json = verbose.to_json().unwrap();
+        assert_eq!(json["first_viable_version"], "2");
+        assert!(json.get("latest_state_checkpoint").is_none());
+    }
+}
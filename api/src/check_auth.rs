@@ -0,0 +1,242 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::compress_proof::ProofCompression;
+use aptos_api_types::{AptosError, AptosErrorCode};
+use poem::{
+    http::{header::WWW_AUTHENTICATE, HeaderMap, StatusCode},
+    web::{
+        headers::{
+            authorization::{Authorization, Bearer},
+            HeaderMapExt,
+        },
+        LocalAddr,
+    },
+    Endpoint, EndpointExt, Error, IntoResponse, Middleware, Request, Result,
+};
+use poem_openapi::payload::Json;
+use serde::Deserialize;
+use std::net::SocketAddr;
+
+/// Wraps an endpoint serving proofs with [`ProofAuth`] and [`ProofCompression`].
+/// Proof operations opt in with `#[oai(transform = "proof_endpoint")]`.
+pub(crate) fn proof_endpoint<E: Endpoint>(ep: E) -> impl Endpoint {
+    ep.with(ProofCompression::new(ProofRequests::All))
+        .with(ProofAuth::new(ProofRequests::All))
+}
+
+/// Like [`proof_endpoint`], for endpoints serving a proof only when
+/// `with_proof=true` is in the query.
+pub(crate) fn opt_in_proof_endpoint<E: Endpoint>(ep: E) -> impl Endpoint {
+    ep.with(ProofCompression::new(ProofRequests::WithProof))
+        .with(ProofAuth::new(ProofRequests::WithProof))
+}
+
+/// Which requests of an endpoint are for a proof.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum ProofRequests {
+    All,
+    WithProof,
+}
+
+#[derive(Deserialize)]
+struct WithProofQuery {
+    with_proof: Option<bool>,
+}
+
+impl ProofRequests {
+    /// Whether the request is for a proof. A query that can't be parsed is
+    /// treated as one, so it can't be used to skip authentication.
+    pub(crate) fn matches(self, req: &Request) -> bool {
+        match self {
+            ProofRequests::All => true,
+            ProofRequests::WithProof => req
+                .params::<WithProofQuery>()
+                .map_or(true, |query| query.with_proof.unwrap_or_default()),
+        }
+    }
+}
+
+/// Identities allowed to access proofs, added to every request as data. If
+/// neither is configured, proofs are open to everyone.
+#[derive(Clone, Debug, Default)]
+pub struct ProofAuthConfig {
+    /// Bearer token expected in the `Authorization` header
+    pub token: Option<String>,
+    /// Address of the listener only accepting clients with a certificate
+    /// signed by the configured CA. Requests it accepted are authorized by
+    /// their client certificate.
+    pub mtls_address: Option<SocketAddr>,
+}
+
+impl ProofAuthConfig {
+    fn authorizes(&self, local_addr: &LocalAddr, headers: &HeaderMap) -> bool {
+        if self.token.is_none() && self.mtls_address.is_none() {
+            return true;
+        }
+        let client_certificate = self.mtls_address.map_or(false, |address| {
+            local_addr.as_socket_addr() == Some(&address)
+        });
+        let bearer_token = self.token.as_ref().map_or(false, |token| {
+            headers
+                .typed_get::<Authorization<Bearer>>()
+                .map_or(false, |auth| {
+                    constant_time_eq(auth.token().as_bytes(), token.as_bytes())
+                })
+        });
+        client_certificate || bearer_token
+    }
+}
+
+/// This middleware requires a bearer token or a client certificate on the
+/// proof requests of the endpoint it wraps, as configured by the
+/// [`ProofAuthConfig`] of the request.
+pub struct ProofAuth {
+    requests: ProofRequests,
+}
+
+impl ProofAuth {
+    pub(crate) fn new(requests: ProofRequests) -> Self {
+        Self { requests }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for ProofAuth {
+    type Output = ProofAuthEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        ProofAuthEndpoint {
+            inner: ep,
+            requests: self.requests,
+        }
+    }
+}
+
+/// Endpoint for ProofAuth middleware.
+pub struct ProofAuthEndpoint<E> {
+    inner: E,
+    requests: ProofRequests,
+}
+
+#[async_trait::async_trait]
+impl<E: Endpoint> Endpoint for ProofAuthEndpoint<E> {
+    type Output = E::Output;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let config = match req.data::<ProofAuthConfig>() {
+            Some(config) if self.requests.matches(&req) => config,
+            _ => return self.inner.call(req).await,
+        };
+
+        if !config.authorizes(req.local_addr(), req.headers()) {
+            return Err(Error::from_response(
+                Json(AptosError::new_with_error_code(
+                    "A valid bearer token or client certificate is required to access proofs",
+                    AptosErrorCode::Unauthorized,
+                ))
+                .with_status(StatusCode::UNAUTHORIZED)
+                .with_header(WWW_AUTHENTICATE, "Bearer")
+                .into_response(),
+            ));
+        }
+
+        self.inner.call(req).await
+    }
+}
+
+/// Compares the token in time independent of where the bytes differ, so it
+/// can't be guessed one byte at a time. Only the length may leak.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{constant_time_eq, ProofAuthConfig, ProofRequests};
+    use poem::{
+        http::{header::AUTHORIZATION, HeaderMap},
+        web::LocalAddr,
+        Addr, Request,
+    };
+
+    fn local_addr(address: &str) -> LocalAddr {
+        LocalAddr(Addr::SocketAddr(address.parse().unwrap()))
+    }
+
+    fn headers(authorization: Option<&str>) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if let Some(authorization) = authorization {
+            headers.insert(AUTHORIZATION, authorization.parse().unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn test_authorizes() {
+        let api = local_addr("127.0.0.1:8080");
+        let mtls = local_addr("127.0.0.1:8443");
+
+        let open = ProofAuthConfig::default();
+        assert!(open.authorizes(&api, &headers(None)));
+
+        let token = ProofAuthConfig {
+            token: Some("secret".to_string()),
+            mtls_address: None,
+        };
+        assert!(token.authorizes(&api, &headers(Some("Bearer secret"))));
+        assert!(!token.authorizes(&api, &headers(None)));
+        assert!(!token.authorizes(&api, &headers(Some("Bearer secre"))));
+        assert!(!token.authorizes(&api, &headers(Some("Basic secret"))));
+
+        let client_certificate = ProofAuthConfig {
+            token: None,
+            mtls_address: Some("127.0.0.1:8443".parse().unwrap()),
+        };
+        assert!(client_certificate.authorizes(&mtls, &headers(None)));
+        assert!(!client_certificate.authorizes(&api, &headers(None)));
+        assert!(!client_certificate.authorizes(&api, &headers(Some("Bearer secret"))));
+
+        let both = ProofAuthConfig {
+            token: Some("secret".to_string()),
+            mtls_address: Some("127.0.0.1:8443".parse().unwrap()),
+        };
+        assert!(both.authorizes(&mtls, &headers(None)));
+        assert!(both.authorizes(&api, &headers(Some("Bearer secret"))));
+        assert!(!both.authorizes(&api, &headers(None)));
+    }
+
+    #[test]
+    fn test_proof_requests_matches() {
+        let matches = |requests: ProofRequests, uri: &str| {
+            requests.matches(&Request::builder().uri(uri.parse().unwrap()).finish())
+        };
+        assert!(matches(ProofRequests::All, "/v1/epoch/state"));
+        assert!(matches(
+            ProofRequests::WithProof,
+            "/v1/accounts/0x1/resource/0x1::account::Account?ledger_version=5&with_proof=true"
+        ));
+        // Unparsable queries are treated as proof requests
+        assert!(matches(
+            ProofRequests::WithProof,
+            "/v1/accounts/0x1/resource/0x1::account::Account?with_proof=yes"
+        ));
+
+        assert!(!matches(
+            ProofRequests::WithProof,
+            "/v1/accounts/0x1/resource/0x1::account::Account"
+        ));
+        assert!(!matches(
+            ProofRequests::WithProof,
+            "/v1/accounts/0x1/resource/0x1::account::Account?with_proof=false"
+        ));
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(constant_time_eq(b"", b""));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret1"));
+        assert!(!constant_time_eq(b"", b"secret"));
+    }
+}
@@ -0,0 +1,130 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::check_auth::ProofRequests;
+use aptos_api_types::mime_types::BCS;
+use flate2::{write::GzEncoder, Compression};
+use poem::{
+    http::{
+        header::{ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_LENGTH, VARY},
+        HeaderMap, HeaderValue,
+    },
+    Endpoint, IntoResponse, Middleware, Request, Response, Result,
+};
+use std::io::Write;
+
+/// This middleware gzips the BCS responses to the proof requests of the
+/// endpoint it wraps for clients advertising gzip in `Accept-Encoding`, since
+/// epoch change proofs and batch proofs can be hundreds of KB. All other
+/// responses are left untouched.
+pub struct ProofCompression {
+    requests: ProofRequests,
+}
+
+impl ProofCompression {
+    pub(crate) fn new(requests: ProofRequests) -> Self {
+        Self { requests }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for ProofCompression {
+    type Output = ProofCompressionEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        ProofCompressionEndpoint {
+            inner: ep,
+            requests: self.requests,
+        }
+    }
+}
+
+/// Endpoint for ProofCompression middleware.
+pub struct ProofCompressionEndpoint<E> {
+    inner: E,
+    requests: ProofRequests,
+}
+
+#[async_trait::async_trait]
+impl<E: Endpoint> Endpoint for ProofCompressionEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        if !self.requests.matches(&req) {
+            return self.inner.call(req).await.map(IntoResponse::into_response);
+        }
+        let accepts_gzip = accepts_gzip(req.headers());
+
+        let mut resp = self.inner.call(req).await?.into_response();
+        if !resp.status().is_success() || resp.content_type() != Some(BCS) {
+            return Ok(resp);
+        }
+        resp.headers_mut()
+            .append(VARY, HeaderValue::from_static("Accept-Encoding"));
+        if !accepts_gzip {
+            return Ok(resp);
+        }
+
+        // Fall back to the uncompressed body if it can't be compressed
+        let body = resp.take_body().into_bytes().await?;
+        match gzip(&body) {
+            Ok(compressed) => {
+                resp.headers_mut().remove(CONTENT_LENGTH);
+                resp.headers_mut()
+                    .insert(CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+                resp.set_body(compressed);
+            },
+            Err(_) => resp.set_body(body),
+        }
+        Ok(resp)
+    }
+}
+
+/// Whether `Accept-Encoding` lists gzip, or `*`, without a zero quality value.
+fn accepts_gzip(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(ACCEPT_ENCODING)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|coding| {
+            let mut params = coding.split(';').map(str::trim);
+            let name = params.next().unwrap_or_default();
+            let quality = params
+                .find_map(|param| param.strip_prefix("q="))
+                .map_or(1.0, |quality| quality.parse::<f32>().unwrap_or(0.0));
+            (name.eq_ignore_ascii_case("gzip") || name == "*") && quality > 0.0
+        })
+}
+
+fn gzip(bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(bytes)?;
+    encoder.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::accepts_gzip;
+    use poem::http::{header::ACCEPT_ENCODING, HeaderMap};
+
+    fn accepts(accept_encoding: Option<&str>) -> bool {
+        let mut headers = HeaderMap::new();
+        if let Some(accept_encoding) = accept_encoding {
+            headers.insert(ACCEPT_ENCODING, accept_encoding.parse().unwrap());
+        }
+        accepts_gzip(&headers)
+    }
+
+    #[test]
+    fn test_accepts_gzip() {
+        assert!(!accepts(None));
+        assert!(!accepts(Some("identity")));
+        assert!(!accepts(Some("br, deflate")));
+        assert!(!accepts(Some("gzip;q=0")));
+        assert!(!accepts(Some("gzip; q=0.0, identity")));
+        assert!(accepts(Some("gzip")));
+        assert!(accepts(Some("GZIP")));
+        assert!(accepts(Some("deflate, gzip;q=0.5")));
+        assert!(accepts(Some("br;q=1.0, *;q=0.1")));
+    }
+}
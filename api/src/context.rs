@@ -33,6 +33,7 @@ use aptos_types::{
     block_executor::config::BlockExecutorConfigFromOnchain,
     chain_id::ChainId,
     contract_event::EventWithVersion,
+    epoch_state::EpochState,
     event::EventKey,
     ledger_info::LedgerInfoWithSignatures,
     on_chain_config::{GasSchedule, GasScheduleV2, OnChainConfig, OnChainExecutionConfig},
@@ -72,6 +73,7 @@ pub struct Context {
     gas_schedule_cache: Arc<RwLock<GasScheduleCache>>,
     gas_estimation_cache: Arc<RwLock<GasEstimationCache>>,
     gas_limit_cache: Arc<RwLock<GasLimitCache>>,
+    epoch_state_cache: Arc<RwLock<EpochStateCache>>,
     view_function_stats: Arc<FunctionStats>,
     simulate_txn_stats: Arc<FunctionStats>,
     pub table_info_reader: Option<Arc<dyn TableInfoReader>>,
@@ -125,6 +127,10 @@ impl Context {
                 block_executor_onchain_config: OnChainExecutionConfig::default_if_missing()
                     .block_executor_onchain_config(),
             })),
+            epoch_state_cache: Arc::new(RwLock::new(EpochStateCache {
+                last_updated_epoch: None,
+                epoch_state: None,
+            })),
             view_function_stats,
             simulate_txn_stats,
             table_info_reader,
@@ -202,6 +208,38 @@ impl Context {
         self.node_config.api.max_submit_transaction_batch_size
     }
 
+    pub fn max_state_value_chunk_size(&self) -> usize {
+        self.node_config.api.max_state_value_chunk_size
+    }
+
+    pub fn max_epoch_change_proof_size(&self) -> usize {
+        self.node_config.api.max_epoch_change_proof_size
+    }
+
+    pub fn max_epoch_change_proof_span(&self) -> usize {
+        self.node_config.api.max_epoch_change_proof_span
+    }
+
+    pub fn max_resource_proof_versions(&self) -> usize {
+        self.node_config.api.max_resource_proof_versions
+    }
+
+    pub fn account_proof_resources(&self) -> &[String] {
+        &self.node_config.api.account_proof_resources
+    }
+
+    pub fn max_state_delta_resources(&self) -> usize {
+        self.node_config.api.max_state_delta_resources
+    }
+
+    pub fn max_account_proof_batch_size(&self) -> usize {
+        self.node_config.api.max_account_proof_batch_size
+    }
+
+    pub fn max_table_item_batch_size(&self) -> usize {
+        self.node_config.api.max_table_item_batch_size
+    }
+
     pub async fn submit_transaction(&self, txn: SignedTransaction) -> Result<SubmissionStatus> {
         let (req_sender, callback) = oneshot::channel();
         self.mp_sender
@@ -444,6 +482,44 @@ impl Context {
         Ok((kvs, next_key))
     }
 
+    /// State keys of the resources and resource groups of `address` at `version`, starting at
+    /// `start`, at most `limit` of them, and the state key following the last one, if any.
+    pub fn get_resource_state_keys(
+        &self,
+        address: AccountAddress,
+        start: Option<&StateKey>,
+        version: u64,
+        limit: usize,
+    ) -> Result<(Vec<StateKey>, Option<StateKey>)> {
+        let mut state_keys = self
+            .db
+            .get_prefixed_state_value_iterator(&StateKeyPrefix::from(address), start, version)?
+            .filter_map(|res| match res {
+                Ok((k, _)) => match k.inner() {
+                    StateKeyInner::AccessPath(AccessPath { address: _, path }) => {
+                        match Path::try_from(path.as_slice()) {
+                            Ok(Path::Resource(_)) | Ok(Path::ResourceGroup(_)) => Some(Ok(k)),
+                            Ok(Path::Code(_)) => None,
+                            Err(e) => Some(Err(anyhow::Error::from(e))),
+                        }
+                    },
+                    _ => Some(Err(format_err!(
+                        "storage prefix scan return inconsistent key ({:?})",
+                        k
+                    ))),
+                },
+                Err(e) => Some(Err(e.into())),
+            })
+            .take(limit + 1)
+            .collect::<Result<Vec<_>>>()?;
+        let next_state_key = if state_keys.len() > limit {
+            state_keys.pop()
+        } else {
+            None
+        };
+        Ok((state_keys, next_state_key))
+    }
+
     pub fn get_modules_by_pagination(
         &self,
         address: AccountAddress,
@@ -522,6 +598,44 @@ impl Context {
         )
     }
 
+    /// Epoch of the block at `height`, whose validators sign the ledger infos of its versions
+    pub fn epoch_for_block_height<E: StdApiError>(
+        &self,
+        height: u64,
+        latest_ledger_info: &LedgerInfo,
+    ) -> Result<u64, E> {
+        if height < latest_ledger_info.oldest_block_height.0 {
+            return Err(block_pruned_by_height(height, latest_ledger_info));
+        } else if height > latest_ledger_info.block_height.0 {
+            return Err(block_not_found_by_height(height, latest_ledger_info));
+        }
+
+        let (_, _, new_block_event) = self
+            .db
+            .get_block_info_by_height(height)
+            .map_err(|_| block_not_found_by_height(height, latest_ledger_info))?;
+        Ok(new_block_event.epoch())
+    }
+
+    /// Epoch of the block containing `version`
+    pub fn epoch_for_version<E: StdApiError>(
+        &self,
+        version: u64,
+        latest_ledger_info: &LedgerInfo,
+    ) -> Result<u64, E> {
+        if version < latest_ledger_info.oldest_ledger_version.0 {
+            return Err(version_pruned(version, latest_ledger_info));
+        } else if version > latest_ledger_info.version() {
+            return Err(version_not_found(version, latest_ledger_info));
+        }
+
+        let (_, _, new_block_event) = self
+            .db
+            .get_block_info_by_version(version)
+            .map_err(|_| block_not_found_by_version(version, latest_ledger_info))?;
+        Ok(new_block_event.epoch())
+    }
+
     pub fn get_block_by_version<E: StdApiError>(
         &self,
         version: u64,
@@ -1245,6 +1359,45 @@ impl Context {
         }
     }
 
+    /// The state of the latest epoch, expected to be `epoch`, cached until the epoch advances so
+    /// proof requests don't deserialize the validator set each time.
+    pub fn latest_epoch_state<E: InternalError>(
+        &self,
+        epoch: u64,
+        ledger_info: &LedgerInfo,
+    ) -> Result<EpochState, E> {
+        // If it's the same epoch, use the cached results
+        {
+            let cache = self.epoch_state_cache.read().unwrap();
+            if cache.last_updated_epoch == Some(epoch) {
+                if let Some(ref epoch_state) = cache.epoch_state {
+                    return Ok(epoch_state.clone());
+                }
+            }
+        }
+
+        // Otherwise refresh the cache
+        {
+            let mut cache = self.epoch_state_cache.write().unwrap();
+            // If a different thread updated the cache, we can exit early
+            if cache.last_updated_epoch == Some(epoch) {
+                if let Some(ref epoch_state) = cache.epoch_state {
+                    return Ok(epoch_state.clone());
+                }
+            }
+
+            let epoch_state = self.db.get_latest_epoch_state().map_err(|e| {
+                E::internal_with_code(e, AptosErrorCode::InternalError, ledger_info)
+            })?;
+
+            // The epoch may have advanced past `epoch` in the meantime, the cache is keyed by the
+            // epoch actually read
+            cache.last_updated_epoch = Some(epoch_state.epoch);
+            cache.epoch_state = Some(epoch_state.clone());
+            Ok(epoch_state)
+        }
+    }
+
     pub fn check_api_output_enabled<E: ForbiddenError>(
         &self,
         api_name: &'static str,
@@ -1304,6 +1457,11 @@ pub struct GasLimitCache {
     block_executor_onchain_config: BlockExecutorConfigFromOnchain,
 }
 
+pub struct EpochStateCache {
+    last_updated_epoch: Option<u64>,
+    epoch_state: Option<EpochState>,
+}
+
 /// This function just calls tokio::task::spawn_blocking with the given closure and in
 /// the case of an error when joining the task converts it into a 500.
 pub async fn api_spawn_blocking<F, T, E>(func: F) -> Result<T, E>
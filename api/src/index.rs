@@ -8,7 +8,7 @@ use crate::{
     response::{BasicResponse, BasicResponseStatus, BasicResult},
     ApiTags,
 };
-use aptos_api_types::{IndexResponse, IndexResponseBcs};
+use aptos_api_types::{IndexResponse, IndexResponseBcs, LedgerInfo};
 use poem_openapi::OpenApi;
 use std::sync::Arc;
 
@@ -56,4 +56,35 @@ impl IndexApi {
         })
         .await
     }
+
+    /// Get ledger info summary
+    ///
+    /// Get only the latest ledger information, including the oldest and latest ledger versions
+    /// and block heights the node can serve. Clients can use it to learn the range of blocks to
+    /// request proofs for.
+    #[oai(
+        path = "/ledger_info",
+        method = "get",
+        operation_id = "get_ledger_info_summary",
+        tag = "ApiTags::General"
+    )]
+    async fn get_ledger_info_summary(&self, accept_type: AcceptType) -> BasicResult<LedgerInfo> {
+        self.context
+            .check_api_output_enabled("Get ledger info summary", &accept_type)?;
+        let ledger_info = self.context.get_latest_ledger_info()?;
+
+        api_spawn_blocking(move || match accept_type {
+            AcceptType::Json => BasicResponse::try_from_json((
+                ledger_info.clone(),
+                &ledger_info,
+                BasicResponseStatus::Ok,
+            )),
+            AcceptType::Bcs => BasicResponse::try_from_bcs((
+                ledger_info.clone(),
+                &ledger_info,
+                BasicResponseStatus::Ok,
+            )),
+        })
+        .await
+    }
 }
@@ -9,7 +9,9 @@ mod accounts;
 mod basic;
 mod bcs_payload;
 mod blocks;
+mod check_auth;
 mod check_size;
+mod compress_proof;
 pub mod context;
 mod error_converter;
 mod events;
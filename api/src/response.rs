@@ -718,6 +718,24 @@ pub fn table_item_not_found<E: NotFoundError>(
     )
 }
 
+pub fn event_not_found<E: NotFoundError>(
+    address: Address,
+    creation_number: u64,
+    sequence_number: u64,
+    ledger_version: u64,
+    ledger_info: &LedgerInfo,
+) -> E {
+    build_not_found(
+        "Event",
+        format!(
+            "Address({}), Creation number({}), Sequence number({}) and Ledger version({})",
+            address, creation_number, sequence_number, ledger_version
+        ),
+        AptosErrorCode::EventNotFound,
+        ledger_info,
+    )
+}
+
 pub fn block_not_found_by_height<E: NotFoundError>(
     block_height: u64,
     ledger_info: &LedgerInfo,
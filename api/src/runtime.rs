@@ -3,10 +3,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    accounts::AccountsApi, basic::BasicApi, blocks::BlocksApi, check_size::PostSizeLimit,
-    context::Context, error_converter::convert_error, events::EventsApi, index::IndexApi,
-    log::middleware_log, set_failpoints, state::StateApi, transactions::TransactionsApi,
-    view_function::ViewFunctionApi,
+    accounts::AccountsApi, basic::BasicApi, blocks::BlocksApi, check_auth::ProofAuthConfig,
+    check_size::PostSizeLimit, context::Context, error_converter::convert_error, events::EventsApi,
+    index::IndexApi, log::middleware_log, set_failpoints, state::StateApi,
+    transactions::TransactionsApi, view_function::ViewFunctionApi,
 };
 use anyhow::Context as AnyhowContext;
 use aptos_config::config::{ApiConfig, NodeConfig};
@@ -175,8 +175,10 @@ pub fn attach_poem_to_runtime(
         address.set_port(0);
     }
 
-    // Build listener with or without TLS
-    let listener = match (&config.api.tls_cert_path, &config.api.tls_key_path) {
+    // Build listener with or without TLS, along with the listener authorizing proofs by client
+    // certificate if configured
+    let (listener, proof_mtls_enabled) = match (&config.api.tls_cert_path, &config.api.tls_key_path)
+    {
         (Some(tls_cert_path), Some(tls_key_path)) => {
             info!("Using TLS for API");
             let cert = std::fs::read_to_string(tls_cert_path).context(format!(
@@ -187,13 +189,35 @@ pub fn attach_poem_to_runtime(
                 "Failed to read TLS key from path: {}",
                 tls_key_path
             ))?;
-            let rustls_certificate = RustlsCertificate::new().cert(cert).key(key);
+            let rustls_certificate = RustlsCertificate::new().cert(cert.clone()).key(key.clone());
             let rustls_config = RustlsConfig::new().fallback(rustls_certificate);
-            TcpListener::bind(address).rustls(rustls_config).boxed()
+            let listener = TcpListener::bind(address).rustls(rustls_config);
+            match (
+                config.api.proof_mtls_address,
+                &config.api.proof_mtls_client_ca_path,
+            ) {
+                (Some(mut mtls_address), Some(client_ca_path)) => {
+                    info!("Using mTLS for proofs");
+                    let client_ca = std::fs::read_to_string(client_ca_path).context(format!(
+                        "Failed to read client CA from path: {}",
+                        client_ca_path
+                    ))?;
+                    if random_port {
+                        mtls_address.set_port(0);
+                    }
+                    let rustls_certificate = RustlsCertificate::new().cert(cert).key(key);
+                    let rustls_config = RustlsConfig::new()
+                        .fallback(rustls_certificate)
+                        .client_auth_required(client_ca);
+                    let mtls_listener = TcpListener::bind(mtls_address).rustls(rustls_config);
+                    (listener.combine(mtls_listener).boxed(), true)
+                },
+                _ => (listener.boxed(), false),
+            }
         },
         _ => {
             info!("Not using TLS for API");
-            TcpListener::bind(address).boxed()
+            (TcpListener::bind(address).boxed(), false)
         },
     };
 
@@ -207,6 +231,19 @@ pub fn attach_poem_to_runtime(
     let actual_address = *actual_address
         .as_socket_addr()
         .context("Failed to get socket addr from local addr for Poem webserver")?;
+    // Requests accepted by the mTLS listener were authenticated by their client certificate
+    let proof_auth_config = ProofAuthConfig {
+        token: config.api.proof_auth_token.clone(),
+        mtls_address: if proof_mtls_enabled {
+            Some(
+                *acceptor.local_addr()[1]
+                    .as_socket_addr()
+                    .context("Failed to get socket addr from local addr for mTLS listener")?,
+            )
+        } else {
+            None
+        },
+    };
     runtime_handle.spawn(async move {
         let cors = Cors::new()
             // To allow browsers to use cookies (for cookie-based sticky
@@ -233,6 +270,7 @@ pub fn attach_poem_to_runtime(
             )
             .with(cors)
             .with(PostSizeLimit::new(size_limit))
+            .data(proof_auth_config)
             // NOTE: Make sure to keep this after all the `with` middleware.
             .catch_all_error(convert_error)
             .around(middleware_log);
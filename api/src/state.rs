@@ -3,44 +3,68 @@
 
 use crate::{
     accept_type::AcceptType,
+    bcs_payload::Bcs,
+    check_auth::{opt_in_proof_endpoint, proof_endpoint},
     context::api_spawn_blocking,
     failpoint::fail_point_poem,
+    page::determine_limit,
     response::{
-        api_forbidden, build_not_found, module_not_found, resource_not_found, table_item_not_found,
-        BadRequestError, BasicErrorWith404, BasicResponse, BasicResponseStatus, BasicResultWith404,
-        InternalError,
+        api_disabled, api_forbidden, build_not_found, event_not_found, module_not_found,
+        resource_not_found, table_item_not_found, transaction_not_found_by_hash, version_not_found,
+        version_pruned, BadRequestError, BasicErrorWith404, BasicResponse, BasicResponseStatus,
+        BasicResultWith404, InternalError,
     },
     ApiTags, Context,
 };
-use anyhow::Context as AnyhowContext;
+use anyhow::{format_err, Context as AnyhowContext};
 use aptos_api_types::{
-    verify_module_identifier, Address, AptosErrorCode, AsConverter, IdentifierWrapper, LedgerInfo,
-    MoveModuleBytecode, MoveResource, MoveStructTag, MoveValue, RawStateValueRequest,
-    RawTableItemRequest, TableItemRequest, VerifyInput, VerifyInputWithRecursion, U64,
+    epoch_change::{EpochChangeSummary, EpochChangeSummaryPage, EpochProgress},
+    ledger_info::EpochState as EpochStateView,
+    proof_payload::{
+        check_encoded_size, AccountProofView, AccountResourcesProofPayload,
+        AccountStateDeltaPayload, BootstrapBundle, EpochChangeProofPage, EpochChangeProofPayload,
+        EventProofPayload, ProofCheckReport, ProofTooLarge, ProvenResource, ResourceDelta,
+        ResourceWithProof, StateAnchor, StateProofPayload, StateProofView, StateValueChunk,
+        TransactionProofPayload, TrimmedAccountProofPayload,
+    },
+    proof_schema::ProofSchema,
+    sparse_merkle_proof::CompressedSiblings,
+    verify_module_identifier,
+    waypoint::Waypoint as WaypointView,
+    AccountProofRequest, Address, AptosError, AptosErrorCode, AsConverter,
+    HashValue as HashValueView, IdentifierWrapper, LedgerInfo, MoveConverter, MoveModuleBytecode,
+    MoveResource, MoveStructTag, MoveValue, RawStateValueRequest, RawTableItemRequest,
+    RawTableItemResult, StateKeyWrapper, StateValueWithKeyHash, TableItemRequest, TableItemResult,
+    VerifyInput, VerifyInputWithRecursion, U64,
+};
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_storage_interface::{state_view::DbStateView, DbReader};
+use aptos_types::{
+    account_config::{AccountResource, BlockResource, CORE_CODE_ADDRESS},
+    epoch_change::EpochChangeProof,
+    event::EventKey,
+    ledger_info::LedgerInfoWithSignatures,
+    proof::SparseMerkleProof,
+    state_store::{
+        state_key::StateKey, state_value::StateValue, table::TableHandle, MoveResourceExt,
+        StateView, TStateView,
+    },
+    transaction::Version,
+    trusted_state::TrustedState,
+    waypoint::Waypoint,
 };
-use aptos_crypto::hash::CryptoHash;
-use aptos_crypto::HashValue;
-use aptos_storage_interface::DbReader;
-use aptos_types::account_config::AccountResource;
-use aptos_types::epoch_change::EpochChangeProof;
-use aptos_types::ledger_info::LedgerInfoWithSignatures;
-use aptos_types::proof::{SparseMerkleProof, TransactionAccumulatorProof};
-use aptos_types::state_store::{state_key::StateKey, table::TableHandle, TStateView};
-use aptos_types::transaction::TransactionInfo;
-use aptos_types::trusted_state::TrustedState;
-use aptos_types::validator_verifier::ValidatorVerifier;
-use aptos_types::waypoint::Waypoint;
 use aptos_vm::data_cache::AsMoveResolver;
-use move_core_types::move_resource::MoveStructType;
-use move_core_types::{language_storage::StructTag, resolver::MoveResolver};
+use move_core_types::{
+    language_storage::{StructTag, TypeTag},
+    move_resource::MoveStructType,
+    resolver::MoveResolver,
+};
 use poem_openapi::{
     param::{Path, Query},
     payload::Json,
     OpenApi,
 };
-use serde::{Deserialize, Serialize};
-use std::ops::Deref;
-use std::{convert::TryInto, sync::Arc};
+use std::{collections::BTreeSet, convert::TryInto, sync::Arc};
 
 /// API for retrieving individual state
 #[derive(Clone)]
@@ -48,32 +72,6 @@ pub struct StateApi {
     pub context: Arc<Context>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-struct AccountProofPayload {
-    /// Proof for the account inclusion
-    state_proof: SparseMerkleProof,
-    /// Account leaf key
-    element_key: HashValue,
-    /// Account state value
-    element_hash: HashValue,
-    /// Proof for the transaction inclusion
-    transaction_proof: TransactionAccumulatorProof,
-    /// Hashed representation of the transaction
-    transaction: TransactionInfo,
-    /// Transaction version.
-    transaction_index: u64,
-    /// Signed Ledger info with the transaction
-    ledger_info_v0: LedgerInfoWithSignatures,
-    /// ValidatorVerifier valid for the proof
-    validator_verifier: ValidatorVerifier,
-}
-
-#[derive(Serialize, Deserialize, Debug, Clone)]
-struct EpochChangeProofPayload {
-    epoch_change_proof: EpochChangeProof,
-    trusted_state: TrustedState,
-}
-
 #[OpenApi]
 impl StateApi {
     /// Get account resource
@@ -83,11 +81,15 @@ impl StateApi {
     ///
     /// The Aptos nodes prune account state history, via a configurable time window.
     /// If the requested ledger version has been pruned, the server responds with a 410.
+    ///
+    /// With `with_proof`, the BCS response is a `ResourceWithProof`: the state value of the
+    /// resource along with its sparse merkle proof.
     #[oai(
         path = "/accounts/:address/resource/:resource_type",
         method = "get",
         operation_id = "get_account_resource",
-        tag = "ApiTags::Accounts"
+        tag = "ApiTags::Accounts",
+        transform = "opt_in_proof_endpoint"
     )]
     async fn get_account_resource(
         &self,
@@ -100,6 +102,11 @@ impl StateApi {
         ///
         /// If not provided, it will be the latest version
         ledger_version: Query<Option<U64>>,
+        /// Whether to prove the resource along with it, BCS only
+        ///
+        /// The resource must be stored under its own state key, not in a resource group, and
+        /// `ledger_version` must be the last version of a block
+        with_proof: Query<Option<bool>>,
     ) -> BasicResultWith404<MoveResource> {
         resource_type
             .0
@@ -109,6 +116,14 @@ impl StateApi {
                 BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
             })?;
         fail_point_poem("endpoint_get_account_resource")?;
+
+        let with_proof = with_proof.0.unwrap_or_default();
+        if with_proof && AcceptType::Json == accept_type {
+            return Err(api_forbidden(
+                "Get account resource with proof",
+                "Only BCS is supported as an AcceptType.",
+            ));
+        }
         self.context
             .check_api_output_enabled("Get account resource", &accept_type)?;
 
@@ -119,17 +134,26 @@ impl StateApi {
                 address.0,
                 resource_type.0,
                 ledger_version.0.map(|inner| inner.0),
+                with_proof,
             )
         })
         .await
     }
 
+    /// Get account proof
+    ///
+    /// Proves the account resource, or another resource of the account, at the end of a block.
+    /// The proof is BCS encoded by default. A JSON rendering, always a list of proofs, can be
+    /// requested to inspect it by hand, but only BCS proofs can be trimmed or compressed. To also
+    /// prove the resources configured by the node, use the account proof bundle instead.
     #[oai(
         path = "/accounts/:address/proof",
         method = "get",
         operation_id = "get_account_proof",
-        tag = "ApiTags::Accounts"
+        tag = "ApiTags::Accounts",
+        transform = "proof_endpoint"
     )]
+    #[allow(clippy::too_many_arguments)]
     async fn get_account_proof(
         &self,
         accept_type: AcceptType,
@@ -137,229 +161,1227 @@ impl StateApi {
         address: Path<Address>,
         /// Block height to get state of account
         ///
-        /// If not provided, it will be the latest block
+        /// If neither this nor `ledger_version` is provided, it will be the latest ledger version
         block_height: Query<Option<U64>>,
-    ) -> BasicResultWith404<Vec<u8>> {
+        /// Ledger version to get state of account, which must be the last version of a block
+        ///
+        /// Can't be provided along with `block_height`
+        ledger_version: Query<Option<U64>>,
+        /// Hash of a transaction to get state of account after, at the end of its block
+        ///
+        /// Can't be provided along with `block_height` or `ledger_version`
+        transaction_hash: Query<Option<aptos_api_types::HashValue>>,
+        /// Whether to include the BCS encoded state key of the account in the proof
+        include_state_key: Query<Option<bool>>,
+        /// Index of the chunk of the account state value to include in the proof
+        ///
+        /// If not provided, the state value is not included
+        value_chunk: Query<Option<U64>>,
+        /// Whether to elide the placeholder siblings of the sparse merkle proof
+        compress_state_proof: Query<Option<bool>>,
+        /// Whether to return a `TrimmedAccountProofPayload`, for clients that verify the ledger
+        /// info without storing it
+        trim_ledger_info: Query<Option<bool>>,
+        /// Whether to include the transaction and its accumulator proof, for clients that verify
+        /// the sparse merkle proof against a state root they already trust when false
+        ///
+        /// If not provided, they are included. Can't be false along with `trim_ledger_info`
+        include_transaction_proof: Query<Option<bool>>,
+        /// Hash of the ledger info the client expects the proof to be anchored to
+        ///
+        /// If the proof would be anchored to another ledger info, e.g. because a newer block was
+        /// committed, the request fails instead
+        expected_ledger_info_hash: Query<Option<aptos_api_types::HashValue>>,
+        /// Name of the struct to prove instead of the account resource e.g.
+        /// `0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>`
+        ///
+        /// If not provided, the `0x1::account::Account` resource is proven
+        resource_type: Query<Option<MoveStructTag>>,
+        /// Chain ID the client expects the node to serve
+        ///
+        /// If the node serves another chain, the request fails instead
+        chain_id: Query<Option<u8>>,
+        /// Whether to return how many bytes each component of the BCS encoded proof takes instead
+        /// of the proof, to see what dominates its size, usually the validator set
+        ///
+        /// Can't be provided along with `trim_ledger_info`
+        breakdown: Query<Option<bool>>,
+    ) -> BasicResultWith404<AccountProofView> {
+        if let Some(resource_type) = &resource_type.0 {
+            resource_type
+                .verify(0)
+                .context("'resource_type' invalid")
+                .map_err(|err| {
+                    BasicErrorWith404::bad_request_with_code_no_info(
+                        err,
+                        AptosErrorCode::InvalidInput,
+                    )
+                })?;
+        }
         fail_point_poem("endpoint_get_account_proof")?;
         self.context
             .check_api_output_enabled("Get account proof", &accept_type)?;
 
         let api = self.clone();
         api_spawn_blocking(move || {
-            api.proof(&accept_type, address.0, block_height.0.map(|inner| inner.0))
+            api.proof(
+                &accept_type,
+                address.0,
+                AccountProofOptions {
+                    block_height: block_height.0.map(|inner| inner.0),
+                    ledger_version: ledger_version.0.map(|inner| inner.0),
+                    transaction_hash: transaction_hash.0.map(Into::into),
+                    include_state_key: include_state_key.0.unwrap_or_default(),
+                    value_chunk: value_chunk.0.map(|inner| inner.0),
+                    compress_state_proof: compress_state_proof.0.unwrap_or_default(),
+                    trim_ledger_info: trim_ledger_info.0.unwrap_or_default(),
+                    include_transaction_proof: include_transaction_proof.0.unwrap_or(true),
+                    expected_ledger_info_hash: expected_ledger_info_hash.0.map(Into::into),
+                    resource_type: resource_type.0,
+                    chain_id: chain_id.0,
+                },
+                breakdown.0.unwrap_or_default(),
+            )
         })
         .await
     }
 
+    /// Get account proof bundle
+    ///
+    /// Proves the account resource, or another resource of the account, along with the resources
+    /// configured by the node, e.g. the coin store, at the end of a block. A list of proofs
+    /// sharing the same ledger info is returned, the requested resource first, whatever the
+    /// encoding. Resources the account doesn't have are left out. Each proof must still be
+    /// verified by the client.
     #[oai(
-        path = "/epoch/proof",
+        path = "/accounts/:address/proof/bundle",
         method = "get",
-        operation_id = "get_epoch_change_proof",
-        tag = "ApiTags::General"
+        operation_id = "get_account_proof_bundle",
+        tag = "ApiTags::Accounts",
+        transform = "proof_endpoint"
     )]
-    async fn get_epoch_change_proof(
+    async fn get_account_proof_bundle(
         &self,
         accept_type: AcceptType,
-        /// Epoch number for the change proof
+        /// Address of account with or without a `0x` prefix
+        address: Path<Address>,
+        /// Block height to get state of account
         ///
-        /// If not provided, it will be the latest epoch change
-        epoch_number: Query<Option<U64>>,
-    ) -> BasicResultWith404<Vec<u8>> {
+        /// If neither this nor `ledger_version` is provided, it will be the latest ledger version
+        block_height: Query<Option<U64>>,
+        /// Ledger version to get state of account, which must be the last version of a block
+        ///
+        /// Can't be provided along with `block_height`
+        ledger_version: Query<Option<U64>>,
+        /// Hash of a transaction to get state of account after, at the end of its block
+        ///
+        /// Can't be provided along with `block_height` or `ledger_version`
+        transaction_hash: Query<Option<aptos_api_types::HashValue>>,
+        /// Whether to include the BCS encoded state key of each resource in its proof
+        include_state_key: Query<Option<bool>>,
+        /// Index of the chunk of each state value to include in its proof
+        ///
+        /// If not provided, the state values are not included
+        value_chunk: Query<Option<U64>>,
+        /// Whether to elide the placeholder siblings of the sparse merkle proofs
+        compress_state_proof: Query<Option<bool>>,
+        /// Whether to return `TrimmedAccountProofPayload`s, for clients that verify the ledger
+        /// info without storing it
+        trim_ledger_info: Query<Option<bool>>,
+        /// Whether to include the transaction and its accumulator proof in each proof
+        ///
+        /// If not provided, they are included. Can't be false along with `trim_ledger_info`
+        include_transaction_proof: Query<Option<bool>>,
+        /// Hash of the ledger info the client expects the proofs to be anchored to
+        ///
+        /// If the proofs would be anchored to another ledger info, the request fails instead
+        expected_ledger_info_hash: Query<Option<aptos_api_types::HashValue>>,
+        /// Name of the struct to prove first instead of the account resource
+        ///
+        /// If not provided, the `0x1::account::Account` resource is proven first
+        resource_type: Query<Option<MoveStructTag>>,
+        /// Chain ID the client expects the node to serve
+        ///
+        /// If the node serves another chain, the request fails instead
+        chain_id: Query<Option<u8>>,
+    ) -> BasicResultWith404<Vec<StateProofView>> {
+        if let Some(resource_type) = &resource_type.0 {
+            resource_type
+                .verify(0)
+                .context("'resource_type' invalid")
+                .map_err(|err| {
+                    BasicErrorWith404::bad_request_with_code_no_info(
+                        err,
+                        AptosErrorCode::InvalidInput,
+                    )
+                })?;
+        }
+        fail_point_poem("endpoint_get_account_proof_bundle")?;
         self.context
-            .check_api_output_enabled("Get account resource", &accept_type)?;
+            .check_api_output_enabled("Get account proof bundle", &accept_type)?;
 
         let api = self.clone();
         api_spawn_blocking(move || {
-            api.epoch_change_proof(&accept_type, epoch_number.0.map(|inner| inner.0))
+            api.proof_bundle(&accept_type, address.0, AccountProofOptions {
+                block_height: block_height.0.map(|inner| inner.0),
+                ledger_version: ledger_version.0.map(|inner| inner.0),
+                transaction_hash: transaction_hash.0.map(Into::into),
+                include_state_key: include_state_key.0.unwrap_or_default(),
+                value_chunk: value_chunk.0.map(|inner| inner.0),
+                compress_state_proof: compress_state_proof.0.unwrap_or_default(),
+                trim_ledger_info: trim_ledger_info.0.unwrap_or_default(),
+                include_transaction_proof: include_transaction_proof.0.unwrap_or(true),
+                expected_ledger_info_hash: expected_ledger_info_hash.0.map(Into::into),
+                resource_type: resource_type.0,
+                chain_id: chain_id.0,
+            })
         })
         .await
     }
 
-    /// Get account module
+    /// Get batch account proof
     ///
-    /// Retrieves an individual module from a given account and at a specific ledger version. If the
-    /// ledger version is not specified in the request, the latest ledger version is used.
+    /// Proves the account resource, or the requested resource, of each of the given accounts, all
+    /// at the latest version and against the same ledger info, for clients bootstrapping many
+    /// accounts at once. The proofs are returned in the order of the request.
     ///
-    /// The Aptos nodes prune account state history, via a configurable time window.
-    /// If the requested ledger version has been pruned, the server responds with a 410.
+    /// The whole batch fails if one of the resources doesn't exist.
     #[oai(
-        path = "/accounts/:address/module/:module_name",
+        path = "/accounts/proof/batch",
+        method = "post",
+        operation_id = "get_batch_account_proof",
+        tag = "ApiTags::Accounts",
+        transform = "proof_endpoint"
+    )]
+    async fn get_batch_account_proof(
+        &self,
+        accept_type: AcceptType,
+        /// Accounts to prove, each with an optional resource type
+        requests: Json<Vec<AccountProofRequest>>,
+    ) -> BasicResultWith404<Vec<u8>> {
+        for request in &requests.0 {
+            request
+                .verify()
+                .context("'requests' invalid")
+                .map_err(|err| {
+                    BasicErrorWith404::bad_request_with_code_no_info(
+                        err,
+                        AptosErrorCode::InvalidInput,
+                    )
+                })?;
+        }
+        fail_point_poem("endpoint_get_batch_account_proof")?;
+        self.context
+            .check_api_output_enabled("Get batch account proof", &accept_type)?;
+
+        let api = self.clone();
+        api_spawn_blocking(move || api.batch_proof(&accept_type, requests.0)).await
+    }
+
+    /// Verify account proof
+    ///
+    /// Generates the account proof served with the same parameters and verifies it the way a
+    /// client would, reporting whether the state proof, the transaction accumulator proof and the
+    /// ledger info signatures each check out. Meant as a known-good reference for client
+    /// developers debugging their own verification.
+    #[oai(
+        path = "/accounts/:address/proof/verify",
         method = "get",
-        operation_id = "get_account_module",
-        tag = "ApiTags::Accounts"
+        operation_id = "verify_account_proof",
+        tag = "ApiTags::Accounts",
+        transform = "proof_endpoint"
     )]
-    async fn get_account_module(
+    async fn verify_account_proof(
         &self,
         accept_type: AcceptType,
         /// Address of account with or without a `0x` prefix
         address: Path<Address>,
-        /// Name of module to retrieve e.g. `coin`
-        module_name: Path<IdentifierWrapper>,
-        /// Ledger version to get state of account
+        /// Block height to get state of account
         ///
-        /// If not provided, it will be the latest version
-        ledger_version: Query<Option<U64>>,
-    ) -> BasicResultWith404<MoveModuleBytecode> {
-        verify_module_identifier(module_name.0.as_str())
-            .context("'module_name' invalid")
-            .map_err(|err| {
-                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
-            })?;
-        fail_point_poem("endpoint_get_account_module")?;
+        /// If not provided, it will be the latest block
+        block_height: Query<Option<U64>>,
+        /// Hash of a transaction to get state of account after, at the end of its block
+        ///
+        /// Can't be provided along with `block_height`
+        transaction_hash: Query<Option<aptos_api_types::HashValue>>,
+    ) -> BasicResultWith404<ProofCheckReport> {
+        fail_point_poem("endpoint_verify_account_proof")?;
         self.context
-            .check_api_output_enabled("Get account module", &accept_type)?;
+            .check_api_output_enabled("Verify account proof", &accept_type)?;
+
         let api = self.clone();
         api_spawn_blocking(move || {
-            api.module(&accept_type, address.0, module_name.0, ledger_version.0)
+            api.verify_proof(
+                &accept_type,
+                address.0,
+                block_height.0.map(|inner| inner.0),
+                transaction_hash.0.map(Into::into),
+            )
         })
         .await
     }
 
-    /// Get table item
-    ///
-    /// Get a table item at a specific ledger version from the table identified by {table_handle}
-    /// in the path and the "key" (TableItemRequest) provided in the request body.
+    /// Get account resource proofs
     ///
-    /// This is a POST endpoint because the "key" for requesting a specific
-    /// table item (TableItemRequest) could be quite complex, as each of its
-    /// fields could themselves be composed of other structs. This makes it
-    /// impractical to express using query params, meaning GET isn't an option.
+    /// Proves the state of a resource at each of the given versions, for clients auditing how it
+    /// changed over time. Every proof comes with its own ledger info and validator set.
     ///
-    /// The Aptos nodes prune account state history, via a configurable time window.
-    /// If the requested ledger version has been pruned, the server responds with a 410.
+    /// Only the last version of a block can be proven, and resources stored in resource groups
+    /// are not supported.
     #[oai(
-        path = "/tables/:table_handle/item",
+        path = "/accounts/:address/resource/:resource_type/proof",
         method = "post",
-        operation_id = "get_table_item",
-        tag = "ApiTags::Tables"
+        operation_id = "get_account_resource_proofs",
+        tag = "ApiTags::Accounts",
+        transform = "proof_endpoint"
     )]
-    async fn get_table_item(
+    async fn get_account_resource_proofs(
         &self,
         accept_type: AcceptType,
-        /// Table handle hex encoded 32-byte string
-        table_handle: Path<Address>,
-        /// Table request detailing the key type, key, and value type
-        table_item_request: Json<TableItemRequest>,
-        /// Ledger version to get state of account
-        ///
-        /// If not provided, it will be the latest version
-        ledger_version: Query<Option<U64>>,
-    ) -> BasicResultWith404<MoveValue> {
-        table_item_request
+        /// Address of account with or without a `0x` prefix
+        address: Path<Address>,
+        /// Name of struct to prove e.g. `0x1::account::Account`
+        resource_type: Path<MoveStructTag>,
+        /// Versions to prove the resource at
+        versions: Json<Vec<U64>>,
+    ) -> BasicResultWith404<Vec<u8>> {
+        resource_type
             .0
-            .verify()
-            .context("'table_item_request' invalid")
+            .verify(0)
+            .context("'resource_type' invalid")
             .map_err(|err| {
                 BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
             })?;
-        fail_point_poem("endpoint_get_table_item")?;
+        fail_point_poem("endpoint_get_account_resource_proofs")?;
         self.context
-            .check_api_output_enabled("Get table item", &accept_type)?;
+            .check_api_output_enabled("Get account resource proofs", &accept_type)?;
+
         let api = self.clone();
         api_spawn_blocking(move || {
-            api.table_item(
+            api.resource_proofs(
                 &accept_type,
-                table_handle.0,
-                table_item_request.0,
-                ledger_version.0,
+                address.0,
+                resource_type.0,
+                versions.0.into_iter().map(|inner| inner.0).collect(),
             )
         })
         .await
     }
 
-    /// Get raw table item
-    ///
-    /// Get a table item at a specific ledger version from the table identified by {table_handle}
-    /// in the path and the "key" (RawTableItemRequest) provided in the request body.
+    /// Get account resources proof
     ///
-    /// The `get_raw_table_item` requires only a serialized key comparing to the full move type information
-    /// comparing to the `get_table_item` api, and can only return the query in the bcs format.
+    /// Proves a page of the resources and resource groups of an account, for clients walking its
+    /// whole state without knowing the resource types up front. All proofs of a page share the
+    /// same ledger info.
     ///
-    /// The Aptos nodes prune account state history, via a configurable time window.
-    /// If the requested ledger version has been pruned, the server responds with a 410.
+    /// Only the last version of a block can be proven.
     #[oai(
-        path = "/tables/:table_handle/raw_item",
-        method = "post",
-        operation_id = "get_raw_table_item",
-        tag = "ApiTags::Tables"
+        path = "/accounts/:address/resources/proof",
+        method = "get",
+        operation_id = "get_account_resources_proof",
+        tag = "ApiTags::Accounts",
+        transform = "proof_endpoint"
     )]
-    async fn get_raw_table_item(
+    async fn get_account_resources_proof(
         &self,
         accept_type: AcceptType,
-        /// Table handle hex encoded 32-byte string
-        table_handle: Path<Address>,
-        /// Table request detailing the key type, key, and value type
-        table_item_request: Json<RawTableItemRequest>,
-        /// Ledger version to get state of account
+        /// Address of account with or without a `0x` prefix
+        address: Path<Address>,
+        /// Ledger version to prove the resources at
         ///
         /// If not provided, it will be the latest version
         ledger_version: Query<Option<U64>>,
-    ) -> BasicResultWith404<MoveValue> {
-        fail_point_poem("endpoint_get_table_item")?;
-
-        if AcceptType::Json == accept_type {
-            return Err(api_forbidden(
-                "Get raw table item",
-                "Only BCS is supported as an AcceptType.",
-            ));
-        }
+        /// Cursor specifying where to start for pagination
+        ///
+        /// This cursor cannot be derived manually client-side. Instead, you must
+        /// call this endpoint once without this query parameter specified, and
+        /// then use the cursor returned in the X-Aptos-Cursor header in the
+        /// response.
+        start: Query<Option<StateKeyWrapper>>,
+        /// Max number of resources to prove
+        ///
+        /// If not provided, defaults to the max account proof batch size.
+        limit: Query<Option<u16>>,
+    ) -> BasicResultWith404<Vec<u8>> {
+        fail_point_poem("endpoint_get_account_resources_proof")?;
         self.context
-            .check_api_output_enabled("Get raw table item", &accept_type)?;
+            .check_api_output_enabled("Get account resources proof", &accept_type)?;
 
         let api = self.clone();
         api_spawn_blocking(move || {
-            api.raw_table_item(
+            api.resources_proof(
                 &accept_type,
-                table_handle.0,
-                table_item_request.0,
-                ledger_version.0,
+                address.0,
+                ledger_version.0.map(|inner| inner.0),
+                start.0.map(StateKey::from),
+                limit.0,
             )
         })
         .await
     }
 
-    /// Get raw state value.
+    /// Get account state delta
     ///
-    /// Get a state value at a specific ledger version, identified by the key provided
-    /// in the request body.
+    /// Proves the resources of an account at two versions, so clients can tell which ones changed
+    /// in between without fetching the whole account state at both. A resource missing at one of
+    /// the versions comes with a proof of its absence at that version.
     ///
-    /// The Aptos nodes prune account state history, via a configurable time window.
-    /// If the requested ledger version has been pruned, the server responds with a 410.
+    /// If either version has been pruned, the server responds with a 410.
+    ///
+    /// Only the last version of a block can be proven.
     #[oai(
-        path = "/experimental/state_values/raw",
-        method = "post",
-        operation_id = "get_raw_state_value",
-        tag = "ApiTags::Experimental",
-        hidden
+        path = "/accounts/:address/proof/delta",
+        method = "get",
+        operation_id = "get_account_state_delta",
+        tag = "ApiTags::Accounts",
+        transform = "proof_endpoint"
     )]
-    async fn get_raw_state_value(
+    async fn get_account_state_delta(
         &self,
         accept_type: AcceptType,
-        /// Request that carries the state key.
-        request: Json<RawStateValueRequest>,
-        /// Ledger version at which the value is got.
-        ///
-        /// If not provided, it will be the latest version
-        ledger_version: Query<Option<U64>>,
-    ) -> BasicResultWith404<MoveValue> {
-        fail_point_poem("endpoint_get_raw_state_value")?;
+        /// Address of account with or without a `0x` prefix
+        address: Path<Address>,
+        /// Version to prove the resources at before the changes
+        from_version: Query<U64>,
+        /// Version to prove the resources at after the changes
+        to_version: Query<U64>,
+    ) -> BasicResultWith404<Vec<u8>> {
+        fail_point_poem("endpoint_get_account_state_delta")?;
+        self.context
+            .check_api_output_enabled("Get account state delta", &accept_type)?;
 
-        if AcceptType::Json == accept_type {
-            return Err(api_forbidden(
-                "Get raw state value",
-                "Only BCS is supported as an AcceptType.",
-            ));
+        let api = self.clone();
+        api_spawn_blocking(move || {
+            api.state_delta(&accept_type, address.0, from_version.0 .0, to_version.0 .0)
+        })
+        .await
+    }
+
+    /// Get state checkpoint proof
+    ///
+    /// Proves the account resource, or another resource of the account, at the latest state
+    /// checkpoint, or at the last state snapshot before `before_version`. The proof is taken at
+    /// exactly that version, so it lines up with a persisted snapshot the client may have
+    /// obtained independently. If the latest checkpoint isn't covered by the latest ledger info
+    /// yet, the last state snapshot at or before the ledger info is proven instead.
+    ///
+    /// If there is no such checkpoint, the server responds with a 404.
+    #[oai(
+        path = "/state/checkpoint/proof",
+        method = "get",
+        operation_id = "get_state_checkpoint_proof",
+        tag = "ApiTags::Accounts",
+        transform = "proof_endpoint"
+    )]
+    async fn get_state_checkpoint_proof(
+        &self,
+        accept_type: AcceptType,
+        /// Address of account with or without a `0x` prefix
+        address: Query<Address>,
+        /// Name of the struct to prove instead of the account resource
+        ///
+        /// If not provided, the `0x1::account::Account` resource is proven
+        resource_type: Query<Option<MoveStructTag>>,
+        /// Version the state snapshot must be older than
+        ///
+        /// If not provided, the latest state checkpoint is used
+        before_version: Query<Option<U64>>,
+    ) -> BasicResultWith404<Vec<u8>> {
+        if let Some(resource_type) = &resource_type.0 {
+            resource_type
+                .verify(0)
+                .context("'resource_type' invalid")
+                .map_err(|err| {
+                    BasicErrorWith404::bad_request_with_code_no_info(
+                        err,
+                        AptosErrorCode::InvalidInput,
+                    )
+                })?;
         }
+        fail_point_poem("endpoint_get_state_checkpoint_proof")?;
         self.context
-            .check_api_output_enabled("Get raw state value", &accept_type)?;
+            .check_api_output_enabled("Get state checkpoint proof", &accept_type)?;
 
         let api = self.clone();
-        api_spawn_blocking(move || api.raw_value(&accept_type, request.0, ledger_version.0)).await
+        api_spawn_blocking(move || {
+            api.state_checkpoint_proof(
+                &accept_type,
+                address.0,
+                resource_type.0,
+                before_version.0.map(|inner| inner.0),
+            )
+        })
+        .await
     }
-}
 
-impl StateApi {
-    /// Read a resource at the ledger version
+    /// Get state root
+    ///
+    /// Returns the root hash of the sparse merkle tree of the state at a version, the state
+    /// checkpoint hash committed to by the transaction at that version. Clients can fetch it
+    /// separately from a proof and check that the two match.
+    ///
+    /// The state tree is only committed at the end of blocks, for any other version the server
+    /// responds with a 400.
+    ///
+    /// The Aptos nodes prune account state history, via a configurable time window.
+    /// If the requested ledger version has been pruned, the server responds with a 410.
+    #[oai(
+        path = "/state/:version/root",
+        method = "get",
+        operation_id = "get_state_root",
+        tag = "ApiTags::Accounts",
+        transform = "proof_endpoint"
+    )]
+    async fn get_state_root(
+        &self,
+        accept_type: AcceptType,
+        /// Ledger version of the state
+        version: Path<U64>,
+    ) -> BasicResultWith404<HashValueView> {
+        fail_point_poem("endpoint_get_state_root")?;
+        self.context
+            .check_api_output_enabled("Get state root", &accept_type)?;
+
+        let api = self.clone();
+        api_spawn_blocking(move || api.state_root(&accept_type, version.0 .0)).await
+    }
+
+    /// Get transaction proof
+    ///
+    /// Proves that the transaction at a version was committed, against the latest ledger info, for
+    /// clients verifying a transaction without fetching any state.
+    #[oai(
+        path = "/transactions/:version/proof",
+        method = "get",
+        operation_id = "get_transaction_proof",
+        tag = "ApiTags::Transactions",
+        transform = "proof_endpoint"
+    )]
+    async fn get_transaction_proof(
+        &self,
+        accept_type: AcceptType,
+        /// Version of the transaction to prove
+        version: Path<U64>,
+    ) -> BasicResultWith404<Vec<u8>> {
+        fail_point_poem("endpoint_get_transaction_proof")?;
+        self.context
+            .check_api_output_enabled("Get transaction proof", &accept_type)?;
+
+        let api = self.clone();
+        api_spawn_blocking(move || api.transaction_proof(&accept_type, version.0 .0)).await
+    }
+
+    /// Get event proof
+    ///
+    /// Proves an event of an account event stream, along with the transaction that emitted it, so
+    /// clients can verify the event without fetching the whole transaction.
+    ///
+    /// The event is looked up as of the ledger version, and the proof anchored as for the account
+    /// proof. If either the ledger version or the transaction that emitted the event has been
+    /// pruned, the server responds with a 410.
+    #[oai(
+        path = "/accounts/:address/events/:creation_number/proof",
+        method = "get",
+        operation_id = "get_event_proof",
+        tag = "ApiTags::Events",
+        transform = "proof_endpoint"
+    )]
+    async fn get_event_proof(
+        &self,
+        accept_type: AcceptType,
+        /// Address of account with or without a `0x` prefix
+        address: Path<Address>,
+        /// Creation number corresponding to the event stream originating
+        /// from the given account.
+        creation_number: Path<U64>,
+        /// Sequence number of the event in the event stream
+        sequence_number: Query<U64>,
+        /// Ledger version to look up the event at
+        ///
+        /// If not provided, it will be the latest version
+        ledger_version: Query<Option<U64>>,
+    ) -> BasicResultWith404<Vec<u8>> {
+        fail_point_poem("endpoint_get_event_proof")?;
+        self.context
+            .check_api_output_enabled("Get event proof", &accept_type)?;
+
+        let api = self.clone();
+        api_spawn_blocking(move || {
+            api.event_proof(
+                &accept_type,
+                address.0,
+                creation_number.0 .0,
+                sequence_number.0 .0,
+                ledger_version.0.map(|inner| inner.0),
+            )
+        })
+        .await
+    }
+
+    #[oai(
+        path = "/epoch/proof",
+        method = "get",
+        operation_id = "get_epoch_change_proof",
+        tag = "ApiTags::General",
+        transform = "proof_endpoint"
+    )]
+    async fn get_epoch_change_proof(
+        &self,
+        accept_type: AcceptType,
+        /// Epoch number for the change proof
+        ///
+        /// If not provided, it will be the latest epoch change
+        epoch_number: Query<Option<U64>>,
+        /// If true, only return the validator set changes of each epoch as an
+        /// `EpochChangeSummaryPage`, without the signed ledger infos needed for ratcheting. If the
+        /// range spans more epochs than the node serves at once, `more` is set along with the
+        /// epoch to continue from
+        ///
+        /// If not provided, the full proof is returned
+        summary: Query<Option<bool>>,
+        /// Number of validators to keep in each list of a summary, those with the most voting
+        /// power, the others being aggregated
+        ///
+        /// Only applies to summaries, proofs always carry the full validator set
+        top_n: Query<Option<U64>>,
+        /// Epoch already trusted by the client, to prove the epoch changes from it to the latest
+        /// epoch along with the state trusted at it, instead of the last epoch change only
+        ///
+        /// Can't be combined with `epoch_number`. Proofs spanning more epochs than the node serves
+        /// at once are truncated, with `more` set
+        from_epoch: Query<Option<U64>>,
+        /// Chain ID the client expects the node to serve
+        ///
+        /// If the node serves another chain, the request fails instead
+        chain_id: Query<Option<u8>>,
+    ) -> BasicResultWith404<Vec<u8>> {
+        self.context
+            .check_api_output_enabled("Get account resource", &accept_type)?;
+
+        let api = self.clone();
+        api_spawn_blocking(move || {
+            api.epoch_change_proof(
+                &accept_type,
+                epoch_number.0.map(|inner| inner.0),
+                summary.0.unwrap_or_default(),
+                top_n.0.map(|inner| inner.0),
+                from_epoch.0.map(|inner| inner.0),
+                chain_id.0,
+            )
+        })
+        .await
+    }
+
+    /// Get bootstrap bundle
+    ///
+    /// Retrieves everything a light client starting from scratch needs in one request: the state
+    /// trusted before the last epoch change, the proof of that epoch change and the latest signed
+    /// ledger info, all read from the same latest ledger info.
+    #[oai(
+        path = "/bootstrap",
+        method = "get",
+        operation_id = "get_bootstrap_bundle",
+        tag = "ApiTags::General",
+        transform = "proof_endpoint"
+    )]
+    async fn get_bootstrap_bundle(&self, accept_type: AcceptType) -> BasicResultWith404<Vec<u8>> {
+        fail_point_poem("endpoint_get_bootstrap_bundle")?;
+        self.context
+            .check_api_output_enabled("Get bootstrap bundle", &accept_type)?;
+
+        let api = self.clone();
+        api_spawn_blocking(move || api.bootstrap_bundle(&accept_type)).await
+    }
+
+    /// Get epoch change proof over a range of epochs
+    ///
+    /// Proves the epoch changes from `start_epoch` to `end_epoch`, along with the state trusted at
+    /// `start_epoch`, for light clients catching up across many epochs in one request. The last
+    /// ledger info of the proof is the one ending the epoch before `end_epoch`.
+    ///
+    /// Ranges spanning more epochs than the node serves at once are truncated, with `more` set
+    /// in the proof, and the client continues from where the proof stops.
+    #[oai(
+        path = "/epoch/proof/range",
+        method = "get",
+        operation_id = "get_epoch_change_proof_range",
+        tag = "ApiTags::General",
+        transform = "proof_endpoint"
+    )]
+    async fn get_epoch_change_proof_range(
+        &self,
+        accept_type: AcceptType,
+        /// Epoch the client trusts, at least 1
+        start_epoch: Query<U64>,
+        /// Epoch to reach, at most the latest epoch
+        end_epoch: Query<U64>,
+    ) -> BasicResultWith404<Vec<u8>> {
+        fail_point_poem("endpoint_get_epoch_change_proof_range")?;
+        self.context
+            .check_api_output_enabled("Get epoch change proof range", &accept_type)?;
+
+        let api = self.clone();
+        api_spawn_blocking(move || {
+            api.epoch_change_proof_range(&accept_type, start_epoch.0 .0, end_epoch.0 .0)
+        })
+        .await
+    }
+
+    /// Get epoch change proof between waypoints
+    ///
+    /// Proves the epoch changes from the epoch-ending ledger info of waypoint `from`, exclusive, to
+    /// the one of waypoint `to`, inclusive, for clients moving from one pinned waypoint to a newer
+    /// one. The last ledger info of the proof is the one `to` was derived from.
+    ///
+    /// Waypoints more epochs apart than the node proves at once are rejected, the client has to
+    /// pin an intermediate waypoint.
+    #[oai(
+        path = "/epoch/proof/between",
+        method = "get",
+        operation_id = "get_epoch_change_proof_between_waypoints",
+        tag = "ApiTags::General",
+        transform = "proof_endpoint"
+    )]
+    async fn get_epoch_change_proof_between_waypoints(
+        &self,
+        accept_type: AcceptType,
+        /// Waypoint the client trusts, as `version:hash`
+        from: Query<String>,
+        /// Newer waypoint to reach, as `version:hash`
+        to: Query<String>,
+    ) -> BasicResultWith404<Vec<u8>> {
+        let parse = |waypoint: &str, name: &str| {
+            waypoint
+                .parse::<Waypoint>()
+                .with_context(|| format!("'{}' invalid", name))
+                .map_err(|err| {
+                    BasicErrorWith404::bad_request_with_code_no_info(
+                        err,
+                        AptosErrorCode::InvalidInput,
+                    )
+                })
+        };
+        let from = parse(&from.0, "from")?;
+        let to = parse(&to.0, "to")?;
+        fail_point_poem("endpoint_get_epoch_change_proof_between_waypoints")?;
+        self.context
+            .check_api_output_enabled("Get epoch change proof between waypoints", &accept_type)?;
+
+        let api = self.clone();
+        api_spawn_blocking(move || api.epoch_change_proof_between(&accept_type, from, to)).await
+    }
+
+    /// Stream epoch change proof
+    ///
+    /// Retrieves the epoch-ending ledger infos from `start_epoch` to the latest epoch one page at a
+    /// time, for resource-constrained clients syncing epochs incrementally instead of buffering a
+    /// whole proof. Each page is an `EpochChangeProofPage`, with `more` set and the epoch to request
+    /// the next page from if more pages follow.
+    #[oai(
+        path = "/epoch/proof/stream",
+        method = "get",
+        operation_id = "stream_epoch_change_proof",
+        tag = "ApiTags::General",
+        transform = "proof_endpoint"
+    )]
+    async fn stream_epoch_change_proof(
+        &self,
+        accept_type: AcceptType,
+        /// Epoch of the first ledger info of the page, the one ending it
+        start_epoch: Query<U64>,
+        /// Number of epochs per page
+        ///
+        /// If not provided, or larger than the number of epochs the node proves at once, the
+        /// latter is used
+        chunk_size: Query<Option<U64>>,
+    ) -> BasicResultWith404<Vec<u8>> {
+        fail_point_poem("endpoint_stream_epoch_change_proof")?;
+        self.context
+            .check_api_output_enabled("Stream epoch change proof", &accept_type)?;
+
+        let api = self.clone();
+        api_spawn_blocking(move || {
+            api.epoch_change_proof_page(
+                &accept_type,
+                start_epoch.0 .0,
+                chunk_size.0.map(|inner| inner.0),
+            )
+        })
+        .await
+    }
+
+    /// Derive waypoint
+    ///
+    /// Computes the waypoint of a BCS encoded `LedgerInfoWithSignatures`, as a reference for
+    /// clients debugging their own waypoint derivation. Signatures are not checked.
+    ///
+    /// Only available when the developer tools are enabled on the node.
+    #[oai(
+        path = "/waypoint/derive",
+        method = "post",
+        operation_id = "derive_waypoint",
+        tag = "ApiTags::General",
+        transform = "proof_endpoint"
+    )]
+    async fn derive_waypoint(
+        &self,
+        accept_type: AcceptType,
+        data: Bcs,
+    ) -> BasicResultWith404<WaypointView> {
+        if !self.context.node_config.api.developer_tools_enabled {
+            return Err(api_disabled("Derive waypoint"));
+        }
+        self.context
+            .check_api_output_enabled("Derive waypoint", &accept_type)?;
+
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        let ledger_info_with_sigs: LedgerInfoWithSignatures = bcs::from_bytes(&data.0)
+            .context("Failed to deserialize input into LedgerInfoWithSignatures")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code(
+                    err,
+                    AptosErrorCode::InvalidInput,
+                    &ledger_info,
+                )
+            })?;
+        let waypoint = Waypoint::new_any(ledger_info_with_sigs.ledger_info());
+
+        BasicResponse::try_from_rust_value((
+            WaypointView::from(waypoint),
+            &ledger_info,
+            BasicResponseStatus::Ok,
+            &accept_type,
+        ))
+    }
+
+    /// Get epoch waypoint
+    ///
+    /// Retrieves the waypoint of the ledger info ending the epoch before `epoch_number`, which a
+    /// client can bootstrap its trust in `epoch_number` from, e.g. with an epoch change proof.
+    #[oai(
+        path = "/epoch/waypoint",
+        method = "get",
+        operation_id = "get_epoch_waypoint",
+        tag = "ApiTags::General",
+        transform = "proof_endpoint"
+    )]
+    async fn get_epoch_waypoint(
+        &self,
+        accept_type: AcceptType,
+        /// Epoch to get the waypoint of, at least 1
+        ///
+        /// If not provided, it will be the latest epoch
+        epoch_number: Query<Option<U64>>,
+    ) -> BasicResultWith404<WaypointView> {
+        fail_point_poem("endpoint_get_epoch_waypoint")?;
+        self.context
+            .check_api_output_enabled("Get epoch waypoint", &accept_type)?;
+
+        let api = self.clone();
+        api_spawn_blocking(move || {
+            api.epoch_waypoint(&accept_type, epoch_number.0.map(|inner| inner.0))
+        })
+        .await
+    }
+
+    /// Get epoch progress
+    ///
+    /// Retrieves the current epoch, its first version and the latest version, along with a
+    /// best-effort estimate of the versions left before the next epoch change.
+    #[oai(
+        path = "/epoch/progress",
+        method = "get",
+        operation_id = "get_epoch_progress",
+        tag = "ApiTags::General",
+        transform = "proof_endpoint"
+    )]
+    async fn get_epoch_progress(
+        &self,
+        accept_type: AcceptType,
+    ) -> BasicResultWith404<EpochProgress> {
+        self.context
+            .check_api_output_enabled("Get epoch progress", &accept_type)?;
+
+        let api = self.clone();
+        api_spawn_blocking(move || api.epoch_progress(&accept_type)).await
+    }
+
+    /// Get proof schema
+    ///
+    /// Describes the BCS layout of the proofs served by the API: the fields of
+    /// `AccountProofPayload` and `EpochChangeProofPayload` and the variants of `TrustedState`, in
+    /// encoding order. The schema is versioned, its version bumped whenever a layout changes.
+    #[oai(
+        path = "/proof/schema",
+        method = "get",
+        operation_id = "get_proof_schema",
+        tag = "ApiTags::General",
+        transform = "proof_endpoint"
+    )]
+    async fn get_proof_schema(&self, accept_type: AcceptType) -> BasicResultWith404<ProofSchema> {
+        self.context
+            .check_api_output_enabled("Get proof schema", &accept_type)?;
+
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        match accept_type {
+            AcceptType::Json => BasicResponse::try_from_json((
+                ProofSchema::new(),
+                &ledger_info,
+                BasicResponseStatus::Ok,
+            )),
+            AcceptType::Bcs => Err(api_forbidden(
+                "Get proof schema",
+                "Only JSON is supported as an AcceptType.",
+            )),
+        }
+    }
+
+    /// Get epoch state
+    ///
+    /// Retrieves the epoch number and validator set of an epoch, without the signed ledger infos
+    /// of an epoch change proof, for clients that only need to know the validators.
+    #[oai(
+        path = "/epoch/state",
+        method = "get",
+        operation_id = "get_epoch_state",
+        tag = "ApiTags::General",
+        transform = "proof_endpoint"
+    )]
+    async fn get_epoch_state(
+        &self,
+        accept_type: AcceptType,
+        /// Epoch to get the state of, at least 1
+        ///
+        /// If not provided, it will be the latest epoch
+        epoch_number: Query<Option<U64>>,
+    ) -> BasicResultWith404<EpochStateView> {
+        fail_point_poem("endpoint_get_epoch_state")?;
+        self.context
+            .check_api_output_enabled("Get epoch state", &accept_type)?;
+
+        let api = self.clone();
+        api_spawn_blocking(move || {
+            api.latest_or_historical_epoch_state(&accept_type, epoch_number.0.map(|inner| inner.0))
+        })
+        .await
+    }
+
+    /// Get account module
+    ///
+    /// Retrieves an individual module from a given account and at a specific ledger version. If the
+    /// ledger version is not specified in the request, the latest ledger version is used.
+    ///
+    /// The Aptos nodes prune account state history, via a configurable time window.
+    /// If the requested ledger version has been pruned, the server responds with a 410.
+    #[oai(
+        path = "/accounts/:address/module/:module_name",
+        method = "get",
+        operation_id = "get_account_module",
+        tag = "ApiTags::Accounts"
+    )]
+    async fn get_account_module(
+        &self,
+        accept_type: AcceptType,
+        /// Address of account with or without a `0x` prefix
+        address: Path<Address>,
+        /// Name of module to retrieve e.g. `coin`
+        module_name: Path<IdentifierWrapper>,
+        /// Ledger version to get state of account
+        ///
+        /// If not provided, it will be the latest version
+        ledger_version: Query<Option<U64>>,
+    ) -> BasicResultWith404<MoveModuleBytecode> {
+        verify_module_identifier(module_name.0.as_str())
+            .context("'module_name' invalid")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        fail_point_poem("endpoint_get_account_module")?;
+        self.context
+            .check_api_output_enabled("Get account module", &accept_type)?;
+        let api = self.clone();
+        api_spawn_blocking(move || {
+            api.module(&accept_type, address.0, module_name.0, ledger_version.0)
+        })
+        .await
+    }
+
+    /// Get account module proof
+    ///
+    /// Proves the bytecode of a module of a given account in the latest committed state, against
+    /// the latest signed ledger info. The proof comes with the BCS encoded state key of the module.
+    #[oai(
+        path = "/accounts/:address/module/:module_name/proof",
+        method = "get",
+        operation_id = "get_account_module_proof",
+        tag = "ApiTags::Accounts",
+        transform = "proof_endpoint"
+    )]
+    async fn get_account_module_proof(
+        &self,
+        accept_type: AcceptType,
+        /// Address of account with or without a `0x` prefix
+        address: Path<Address>,
+        /// Name of module to prove e.g. `coin`
+        module_name: Path<IdentifierWrapper>,
+    ) -> BasicResultWith404<Vec<u8>> {
+        verify_module_identifier(module_name.0.as_str())
+            .context("'module_name' invalid")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        fail_point_poem("endpoint_get_account_module_proof")?;
+        self.context
+            .check_api_output_enabled("Get account module proof", &accept_type)?;
+
+        let api = self.clone();
+        api_spawn_blocking(move || api.module_proof(&accept_type, address.0, module_name.0)).await
+    }
+
+    /// Get table item
+    ///
+    /// Get a table item at a specific ledger version from the table identified by {table_handle}
+    /// in the path and the "key" (TableItemRequest) provided in the request body.
+    ///
+    /// This is a POST endpoint because the "key" for requesting a specific
+    /// table item (TableItemRequest) could be quite complex, as each of its
+    /// fields could themselves be composed of other structs. This makes it
+    /// impractical to express using query params, meaning GET isn't an option.
+    ///
+    /// The Aptos nodes prune account state history, via a configurable time window.
+    /// If the requested ledger version has been pruned, the server responds with a 410.
+    #[oai(
+        path = "/tables/:table_handle/item",
+        method = "post",
+        operation_id = "get_table_item",
+        tag = "ApiTags::Tables"
+    )]
+    async fn get_table_item(
+        &self,
+        accept_type: AcceptType,
+        /// Table handle hex encoded 32-byte string
+        table_handle: Path<Address>,
+        /// Table request detailing the key type, key, and value type
+        table_item_request: Json<TableItemRequest>,
+        /// Ledger version to get state of account
+        ///
+        /// If not provided, it will be the latest version
+        ledger_version: Query<Option<U64>>,
+    ) -> BasicResultWith404<MoveValue> {
+        table_item_request
+            .0
+            .verify()
+            .context("'table_item_request' invalid")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        fail_point_poem("endpoint_get_table_item")?;
+        self.context
+            .check_api_output_enabled("Get table item", &accept_type)?;
+        let api = self.clone();
+        api_spawn_blocking(move || {
+            api.table_item(
+                &accept_type,
+                table_handle.0,
+                table_item_request.0,
+                ledger_version.0,
+            )
+        })
+        .await
+    }
+
+    /// Get table items
+    ///
+    /// Get a batch of table items at the same ledger version from the table identified by
+    /// {table_handle} in the path, one for each "key" (TableItemRequest) in the request body.
+    ///
+    /// The items come back in the order of the requests, each with either its value or the error
+    /// that prevented reading it, so that a missing item doesn't fail the whole batch. In BCS, the
+    /// response is a list of `RawTableItemResult`, whose values are the BCS encoded table items.
+    /// If more items than the configured maximum batch size are requested, the server responds
+    /// with a 400.
+    ///
+    /// The Aptos nodes prune account state history, via a configurable time window.
+    /// If the requested ledger version has been pruned, the server responds with a 410.
+    #[oai(
+        path = "/tables/:table_handle/items/batch",
+        method = "post",
+        operation_id = "get_table_items",
+        tag = "ApiTags::Tables"
+    )]
+    async fn get_table_items(
+        &self,
+        accept_type: AcceptType,
+        /// Table handle hex encoded 32-byte string
+        table_handle: Path<Address>,
+        /// Table requests detailing the key type, key, and value type of each item
+        table_item_requests: Json<Vec<TableItemRequest>>,
+        /// Ledger version to get state of account
+        ///
+        /// If not provided, it will be the latest version
+        ledger_version: Query<Option<U64>>,
+    ) -> BasicResultWith404<Vec<TableItemResult>> {
+        fail_point_poem("endpoint_get_table_items")?;
+        self.context
+            .check_api_output_enabled("Get table items", &accept_type)?;
+        let api = self.clone();
+        api_spawn_blocking(move || {
+            api.table_items(
+                &accept_type,
+                table_handle.0,
+                table_item_requests.0,
+                ledger_version.0,
+            )
+        })
+        .await
+    }
+
+    /// Get table item proof
+    ///
+    /// Proves a table item in the latest committed state, against the latest signed ledger info,
+    /// so clients can verify table-backed data structures. The item is identified by {table_handle}
+    /// in the path and the "key" (TableItemRequest) provided in the request body, as for
+    /// `get_table_item`. The proof comes with the BCS encoded state key of the item.
+    #[oai(
+        path = "/tables/:table_handle/item/proof",
+        method = "post",
+        operation_id = "get_table_item_proof",
+        tag = "ApiTags::Tables",
+        transform = "proof_endpoint"
+    )]
+    async fn get_table_item_proof(
+        &self,
+        accept_type: AcceptType,
+        /// Table handle hex encoded 32-byte string
+        table_handle: Path<Address>,
+        /// Table request detailing the key type, key, and value type
+        table_item_request: Json<TableItemRequest>,
+    ) -> BasicResultWith404<Vec<u8>> {
+        table_item_request
+            .0
+            .verify()
+            .context("'table_item_request' invalid")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        fail_point_poem("endpoint_get_table_item_proof")?;
+        self.context
+            .check_api_output_enabled("Get table item proof", &accept_type)?;
+        let api = self.clone();
+        api_spawn_blocking(move || {
+            api.table_item_proof(&accept_type, table_handle.0, table_item_request.0)
+        })
+        .await
+    }
+
+    /// Get raw table item
+    ///
+    /// Get a table item at a specific ledger version from the table identified by {table_handle}
+    /// in the path and the "key" (RawTableItemRequest) provided in the request body.
+    ///
+    /// The `get_raw_table_item` requires only a serialized key comparing to the full move type information
+    /// comparing to the `get_table_item` api, and can only return the query in the bcs format.
+    ///
+    /// The Aptos nodes prune account state history, via a configurable time window.
+    /// If the requested ledger version has been pruned, the server responds with a 410.
+    #[oai(
+        path = "/tables/:table_handle/raw_item",
+        method = "post",
+        operation_id = "get_raw_table_item",
+        tag = "ApiTags::Tables"
+    )]
+    async fn get_raw_table_item(
+        &self,
+        accept_type: AcceptType,
+        /// Table handle hex encoded 32-byte string
+        table_handle: Path<Address>,
+        /// Table request detailing the key type, key, and value type
+        table_item_request: Json<RawTableItemRequest>,
+        /// Ledger version to get state of account
+        ///
+        /// If not provided, it will be the latest version
+        ledger_version: Query<Option<U64>>,
+        /// Whether to return a `StateValueWithKeyHash`, along with the hash of the state key that
+        /// locates the item in the sparse merkle tree, instead of the item bytes alone
+        with_key_hash: Query<Option<bool>>,
+    ) -> BasicResultWith404<MoveValue> {
+        fail_point_poem("endpoint_get_table_item")?;
+
+        if AcceptType::Json == accept_type {
+            return Err(api_forbidden(
+                "Get raw table item",
+                "Only BCS is supported as an AcceptType.",
+            ));
+        }
+        self.context
+            .check_api_output_enabled("Get raw table item", &accept_type)?;
+
+        let api = self.clone();
+        api_spawn_blocking(move || {
+            api.raw_table_item(
+                &accept_type,
+                table_handle.0,
+                table_item_request.0,
+                ledger_version.0,
+                with_key_hash.0.unwrap_or_default(),
+            )
+        })
+        .await
+    }
+
+    /// Get raw state value.
+    ///
+    /// Get a state value at a specific ledger version, identified by the key provided
+    /// in the request body.
+    ///
+    /// The Aptos nodes prune account state history, via a configurable time window.
+    /// If the requested ledger version has been pruned, the server responds with a 410.
+    #[oai(
+        path = "/experimental/state_values/raw",
+        method = "post",
+        operation_id = "get_raw_state_value",
+        tag = "ApiTags::Experimental",
+        hidden
+    )]
+    async fn get_raw_state_value(
+        &self,
+        accept_type: AcceptType,
+        /// Request that carries the state key.
+        request: Json<RawStateValueRequest>,
+        /// Ledger version at which the value is got.
+        ///
+        /// If not provided, it will be the latest version
+        ledger_version: Query<Option<U64>>,
+        /// Whether to return a `StateValueWithKeyHash`, along with the hash of the state key that
+        /// locates the value in the sparse merkle tree, instead of the state value alone
+        with_key_hash: Query<Option<bool>>,
+    ) -> BasicResultWith404<MoveValue> {
+        fail_point_poem("endpoint_get_raw_state_value")?;
+
+        if AcceptType::Json == accept_type {
+            return Err(api_forbidden(
+                "Get raw state value",
+                "Only BCS is supported as an AcceptType.",
+            ));
+        }
+        self.context
+            .check_api_output_enabled("Get raw state value", &accept_type)?;
+
+        let api = self.clone();
+        api_spawn_blocking(move || {
+            api.raw_value(
+                &accept_type,
+                request.0,
+                ledger_version.0,
+                with_key_hash.0.unwrap_or_default(),
+            )
+        })
+        .await
+    }
+}
+
+impl StateApi {
+    /// Read a resource at the ledger version
     ///
     /// JSON: Convert to MoveResource
     /// BCS: Leave it encoded as the resource
@@ -367,9 +1389,1302 @@ impl StateApi {
         &self,
         accept_type: &AcceptType,
         address: Address,
-        resource_type: MoveStructTag,
+        resource_type: MoveStructTag,
+        ledger_version: Option<u64>,
+        with_proof: bool,
+    ) -> BasicResultWith404<MoveResource> {
+        let tag: StructTag = resource_type
+            .try_into()
+            .context("Failed to parse given resource type")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+
+        let (ledger_info, ledger_version, state_view) = self.context.state_view(ledger_version)?;
+        if with_proof {
+            return self.resource_with_proof(address, &tag, ledger_version, &ledger_info);
+        }
+        let bytes = state_view
+            .as_converter(
+                self.context.db.clone(),
+                self.context.table_info_reader.clone(),
+            )
+            .find_resource(&state_view, address, &tag)
+            .context(format!(
+                "Failed to query DB to check for {} at {}",
+                tag, address
+            ))
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?
+            .ok_or_else(|| resource_not_found(address, &tag, ledger_version, &ledger_info))?;
+
+        match accept_type {
+            AcceptType::Json => {
+                let resource = try_into_resource(
+                    &state_view.as_converter(
+                        self.context.db.clone(),
+                        self.context.table_info_reader.clone(),
+                    ),
+                    &tag,
+                    &bytes,
+                    &ledger_info,
+                )?;
+
+                BasicResponse::try_from_json((resource, &ledger_info, BasicResponseStatus::Ok))
+            },
+            AcceptType::Bcs => BasicResponse::try_from_encoded((
+                bytes.to_vec(),
+                &ledger_info,
+                BasicResponseStatus::Ok,
+            )),
+        }
+    }
+
+    fn resource_with_proof(
+        &self,
+        address: Address,
+        tag: &StructTag,
+        ledger_version: u64,
+        ledger_info: &LedgerInfo,
+    ) -> BasicResultWith404<MoveResource> {
+        // The state tree is only committed at the end of blocks
+        let block = self
+            .context
+            .get_block_by_version(ledger_version, ledger_info, false)?;
+        if block.last_version != ledger_version {
+            return Err(BasicErrorWith404::bad_request_with_code(
+                format!(
+                    "Only the last version of a block can be proven, not version {}",
+                    ledger_version
+                ),
+                AptosErrorCode::InvalidInput,
+                ledger_info,
+            ));
+        }
+
+        let state_key = StateKey::resource(address.inner(), tag).map_err(|err| {
+            BasicErrorWith404::internal_with_code(err, AptosErrorCode::InternalError, ledger_info)
+        })?;
+        let (state_value, state_proof) = self
+            .context
+            .db
+            .get_state_value_with_proof_by_version(&state_key, ledger_version)
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    ledger_info,
+                )
+            })?;
+        let state_value = state_value
+            .ok_or_else(|| resource_not_found(address, tag, ledger_version, ledger_info))?;
+
+        let resource = ResourceWithProof {
+            version: ledger_version,
+            state_value,
+            state_proof,
+        };
+        BasicResponse::try_from_encoded((
+            bcs::to_bytes(&resource).unwrap(),
+            ledger_info,
+            BasicResponseStatus::Ok,
+        ))
+    }
+
+    fn epoch_change_proof(
+        &self,
+        accept_type: &AcceptType,
+        epoch_number: Option<u64>,
+        summary: bool,
+        top_n: Option<u64>,
+        from_epoch: Option<u64>,
+        chain_id: Option<u8>,
+    ) -> BasicResultWith404<Vec<u8>> {
+        let (ledger_info, _, _) = self.context.state_view(None)?;
+        check_chain_id(chain_id, &ledger_info)?;
+        if top_n.is_some() && !summary {
+            return Err(BasicErrorWith404::bad_request_with_code(
+                "top_n only applies to summaries, proofs carry the full validator set",
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            ));
+        }
+        if epoch_number.is_some() && from_epoch.is_some() {
+            return Err(BasicErrorWith404::bad_request_with_code(
+                "epoch_number and from_epoch can't be combined",
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            ));
+        }
+
+        let latest_epoch = self
+            .context
+            .db
+            .get_latest_epoch_state()
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?
+            .epoch;
+        // The proof covers the epoch changes from `start_epoch` to `end_epoch`, along with the
+        // state trusted at `start_epoch`
+        let (start_epoch, end_epoch, truncated) = match from_epoch {
+            Some(from_epoch) => {
+                // The state trusted at `from_epoch` comes from the ledger info ending the epoch
+                // before
+                if from_epoch < 1 || from_epoch > latest_epoch {
+                    return Err(BasicErrorWith404::bad_request_with_code(
+                        format!(
+                            "Epoch {} is out of range, valid epochs are 1 to {}",
+                            from_epoch, latest_epoch
+                        ),
+                        AptosErrorCode::InvalidInput,
+                        &ledger_info,
+                    ));
+                }
+                let max_span = self.context.max_epoch_change_proof_span() as u64;
+                let truncated = latest_epoch - from_epoch > max_span;
+                let end_epoch = if truncated {
+                    from_epoch + max_span
+                } else {
+                    latest_epoch
+                };
+                (from_epoch, end_epoch, truncated)
+            },
+            None => {
+                let epoch_number = epoch_number.unwrap_or(latest_epoch);
+                // The proof spans the two epochs before `epoch_number`
+                if epoch_number < 2 || epoch_number > latest_epoch {
+                    return Err(BasicErrorWith404::bad_request_with_code(
+                        format!(
+                            "Epoch {} is out of range, valid epochs are 2 to {}",
+                            epoch_number, latest_epoch
+                        ),
+                        AptosErrorCode::InvalidInput,
+                        &ledger_info,
+                    ));
+                }
+                (epoch_number - 1, epoch_number, false)
+            },
+        };
+
+        // Large validator sets can make proofs too big to serve, so check before serializing
+        let max_size = self.context.max_epoch_change_proof_size();
+        let check_size = |size_check: Result<usize, ProofTooLarge>| {
+            size_check.map_err(|err| {
+                BasicErrorWith404::bad_request_with_code(
+                    format!("{}, request a smaller range of epochs", err),
+                    AptosErrorCode::InvalidInput,
+                    &ledger_info,
+                )
+            })
+        };
+
+        let bytes = if summary {
+            let epoch_change_proof: EpochChangeProof = self
+                .context
+                .db
+                .get_epoch_ending_ledger_infos(start_epoch - 1, end_epoch)
+                .map_err(|err| {
+                    BasicErrorWith404::internal_with_code(
+                        err,
+                        AptosErrorCode::InternalError,
+                        &ledger_info,
+                    )
+                })?;
+            let mut summaries =
+                EpochChangeSummary::from_ledger_infos(&epoch_change_proof.ledger_info_with_sigs)
+                    .map_err(|err| {
+                        BasicErrorWith404::internal_with_code(
+                            err,
+                            AptosErrorCode::InternalError,
+                            &ledger_info,
+                        )
+                    })?;
+            if let Some(top_n) = top_n {
+                for summary in &mut summaries {
+                    summary.diff.truncate(top_n as usize);
+                }
+            }
+            // The DB may return fewer ledger infos than requested, in which case the summaries
+            // stop short of `end_epoch` too. They cover the epochs after `start_epoch`, one each.
+            let more = truncated || epoch_change_proof.more;
+            let page = EpochChangeSummaryPage {
+                next_epoch: more.then_some(start_epoch + summaries.len() as u64),
+                summaries,
+                more,
+            };
+            check_size(check_encoded_size(&page, max_size))?;
+            bcs::to_bytes(&page).unwrap()
+        } else {
+            let mut payload =
+                self.epoch_change_proof_payload(start_epoch, end_epoch, &ledger_info)?;
+            payload.epoch_change_proof.more |= truncated;
+            if from_epoch.is_none() && payload.epoch_change_proof.ledger_info_with_sigs.len() != 1 {
+                return Err(BasicErrorWith404::internal_with_code(
+                    format!(
+                        "Expected two LedgerInfoWithSignatures in EpochChangeProof, got {}",
+                        payload.epoch_change_proof.ledger_info_with_sigs.len() + 1
+                    ),
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                ));
+            }
+            check_size(check_encoded_size(&payload, max_size))?;
+            bcs::to_bytes(&payload).unwrap()
+        };
+
+        match accept_type {
+            AcceptType::Bcs => {
+                BasicResponse::try_from_encoded((bytes, &ledger_info, BasicResponseStatus::Ok))
+            },
+            _ => Err(api_forbidden(
+                "Get epoch change proof",
+                "Only BCS is supported as an AcceptType.",
+            )),
+        }
+    }
+
+    fn bootstrap_bundle(&self, accept_type: &AcceptType) -> BasicResultWith404<Vec<u8>> {
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        let latest_ledger_info = self
+            .context
+            .get_latest_ledger_info_with_signatures()
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?;
+
+        // The same window as the latest epoch change proof, the trusted state can't be before
+        // epoch 1 as it comes from the ledger info ending the epoch before
+        let latest_epoch = latest_ledger_info.ledger_info().next_block_epoch();
+        let payload = self.epoch_change_proof_payload(
+            latest_epoch.saturating_sub(1).max(1),
+            latest_epoch,
+            &ledger_info,
+        )?;
+        let bundle = BootstrapBundle {
+            trusted_state: payload.trusted_state,
+            epoch_change_proof: payload.epoch_change_proof,
+            latest_ledger_info,
+        };
+        check_encoded_size(&bundle, self.context.max_epoch_change_proof_size()).map_err(|err| {
+            BasicErrorWith404::internal_with_code(err, AptosErrorCode::InternalError, &ledger_info)
+        })?;
+
+        match accept_type {
+            AcceptType::Bcs => BasicResponse::try_from_encoded((
+                bcs::to_bytes(&bundle).unwrap(),
+                &ledger_info,
+                BasicResponseStatus::Ok,
+            )),
+            _ => Err(api_forbidden(
+                "Get bootstrap bundle",
+                "Only BCS is supported as an AcceptType.",
+            )),
+        }
+    }
+
+    /// Proves the epoch changes from `start_epoch` to `end_epoch`, along with the state trusted at
+    /// `start_epoch`, anchored at the ledger info ending the epoch before it.
+    ///
+    /// The proof is truncated, with `more` set, if the DB can't return the whole range at once.
+    fn epoch_change_proof_payload(
+        &self,
+        start_epoch: u64,
+        end_epoch: u64,
+        ledger_info: &LedgerInfo,
+    ) -> Result<EpochChangeProofPayload, BasicErrorWith404> {
+        let mut epoch_change_proof: EpochChangeProof = self
+            .context
+            .db
+            .get_epoch_ending_ledger_infos(start_epoch - 1, end_epoch)
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    ledger_info,
+                )
+            })?;
+
+        if epoch_change_proof.ledger_info_with_sigs.is_empty() {
+            return Err(BasicErrorWith404::internal_with_code(
+                "Expected at least one LedgerInfoWithSignatures in EpochChangeProof, got 0",
+                AptosErrorCode::InternalError,
+                ledger_info,
+            ));
+        }
+
+        // Without any epoch change after `start_epoch`, the proof is empty and only the trusted
+        // state is returned
+        let anchor_li = epoch_change_proof.ledger_info_with_sigs.remove(0);
+        let waypoint = Waypoint::new_any(anchor_li.ledger_info());
+
+        // A malformed epoch boundary is reported in the payload rather than as a server error, the
+        // client only gets the waypoint to trust
+        let next_epoch_state = match anchor_li.ledger_info().next_epoch_state() {
+            Some(next_epoch_state) => next_epoch_state,
+            None => {
+                return Ok(EpochChangeProofPayload {
+                    epoch_change_proof,
+                    trusted_state: TrustedState::EpochWaypoint(waypoint),
+                    next_epoch_state_present: false,
+                })
+            },
+        };
+        let epoch_state = aptos_types::epoch_state::EpochState::new(
+            start_epoch,
+            next_epoch_state.verifier.clone(),
+        );
+        if anchor_li.ledger_info().epoch() + 1 != epoch_state.epoch {
+            return Err(BasicErrorWith404::internal_with_code(
+                format!(
+                    "Trusted EpochState for epoch {} doesn't follow LedgerInfo for epoch {}",
+                    epoch_state.epoch,
+                    anchor_li.ledger_info().epoch()
+                ),
+                AptosErrorCode::InternalError,
+                ledger_info,
+            ));
+        }
+
+        Ok(EpochChangeProofPayload {
+            epoch_change_proof,
+            trusted_state: TrustedState::EpochState {
+                waypoint,
+                epoch_state,
+            },
+            next_epoch_state_present: true,
+        })
+    }
+
+    fn epoch_change_proof_range(
+        &self,
+        accept_type: &AcceptType,
+        start_epoch: u64,
+        end_epoch: u64,
+    ) -> BasicResultWith404<Vec<u8>> {
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        let latest_epoch = ledger_info.epoch.0;
+        // The state trusted at `start_epoch` comes from the ledger info ending the epoch before
+        if start_epoch < 1 || start_epoch >= end_epoch || end_epoch > latest_epoch {
+            return Err(BasicErrorWith404::bad_request_with_code(
+                format!(
+                    "Epoch range {}..{} is out of range, valid epochs are 1 to {}",
+                    start_epoch, end_epoch, latest_epoch
+                ),
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            ));
+        }
+
+        let max_span = self.context.max_epoch_change_proof_span() as u64;
+        let truncated = end_epoch - start_epoch > max_span;
+        let end_epoch = if truncated {
+            start_epoch + max_span
+        } else {
+            end_epoch
+        };
+        let mut payload = self.epoch_change_proof_payload(start_epoch, end_epoch, &ledger_info)?;
+        payload.epoch_change_proof.more |= truncated;
+
+        check_encoded_size(&payload, self.context.max_epoch_change_proof_size()).map_err(
+            |err| {
+                BasicErrorWith404::bad_request_with_code(
+                    format!("{}, request a smaller range of epochs", err),
+                    AptosErrorCode::InvalidInput,
+                    &ledger_info,
+                )
+            },
+        )?;
+
+        match accept_type {
+            AcceptType::Bcs => BasicResponse::try_from_encoded((
+                bcs::to_bytes(&payload).unwrap(),
+                &ledger_info,
+                BasicResponseStatus::Ok,
+            )),
+            _ => Err(api_forbidden(
+                "Get epoch change proof range",
+                "Only BCS is supported as an AcceptType.",
+            )),
+        }
+    }
+
+    fn epoch_change_proof_page(
+        &self,
+        accept_type: &AcceptType,
+        start_epoch: u64,
+        chunk_size: Option<u64>,
+    ) -> BasicResultWith404<Vec<u8>> {
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        let latest_epoch = ledger_info.epoch.0;
+        if start_epoch > latest_epoch {
+            return Err(BasicErrorWith404::bad_request_with_code(
+                format!(
+                    "Start epoch {} is out of range, valid epochs are 0 to {}",
+                    start_epoch, latest_epoch
+                ),
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            ));
+        }
+        let max_span = self.context.max_epoch_change_proof_span() as u64;
+        let chunk_size = match chunk_size {
+            Some(0) => {
+                return Err(BasicErrorWith404::bad_request_with_code(
+                    "chunk_size must be at least 1",
+                    AptosErrorCode::InvalidInput,
+                    &ledger_info,
+                ))
+            },
+            Some(chunk_size) => chunk_size.min(max_span),
+            None => max_span,
+        };
+
+        // The latest epoch hasn't ended, its ledger info isn't part of the proof
+        let end_epoch = latest_epoch.min(start_epoch.saturating_add(chunk_size));
+        let mut epoch_change_proof = if start_epoch < end_epoch {
+            self.context
+                .db
+                .get_epoch_ending_ledger_infos(start_epoch, end_epoch)
+                .map_err(|err| {
+                    BasicErrorWith404::internal_with_code(
+                        err,
+                        AptosErrorCode::InternalError,
+                        &ledger_info,
+                    )
+                })?
+        } else {
+            EpochChangeProof::new(vec![], false)
+        };
+        // The DB may stop short of `end_epoch` too, the cursor follows the last ledger info
+        epoch_change_proof.more |= end_epoch < latest_epoch;
+        let next_epoch = epoch_change_proof
+            .more
+            .then(|| start_epoch + epoch_change_proof.ledger_info_with_sigs.len() as u64);
+        let page = EpochChangeProofPage {
+            epoch_change_proof,
+            next_epoch,
+        };
+
+        check_encoded_size(&page, self.context.max_epoch_change_proof_size()).map_err(|err| {
+            BasicErrorWith404::bad_request_with_code(
+                format!("{}, request a smaller chunk_size", err),
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            )
+        })?;
+
+        match accept_type {
+            AcceptType::Bcs => BasicResponse::try_from_encoded((
+                bcs::to_bytes(&page).unwrap(),
+                &ledger_info,
+                BasicResponseStatus::Ok,
+            )),
+            _ => Err(api_forbidden(
+                "Stream epoch change proof",
+                "Only BCS is supported as an AcceptType.",
+            )),
+        }
+    }
+
+    fn epoch_change_proof_between(
+        &self,
+        accept_type: &AcceptType,
+        from: Waypoint,
+        to: Waypoint,
+    ) -> BasicResultWith404<Vec<u8>> {
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        if to.version() < from.version() {
+            return Err(BasicErrorWith404::bad_request_with_code(
+                format!("Waypoint 'to' {} is older than 'from' {}", to, from),
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            ));
+        }
+        let from_epoch = self.waypoint_epoch(&from, &ledger_info)?;
+        let to_epoch = self.waypoint_epoch(&to, &ledger_info)?;
+        // Unlike a range, the proof can't be truncated as it has to end at `to`
+        let max_span = self.context.max_epoch_change_proof_span() as u64;
+        if to_epoch - from_epoch > max_span {
+            return Err(BasicErrorWith404::bad_request_with_code(
+                format!(
+                    "Waypoints {} and {} are {} epochs apart, more than the {} proven at once, pin an intermediate waypoint",
+                    from,
+                    to,
+                    to_epoch - from_epoch,
+                    max_span
+                ),
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            ));
+        }
+
+        let epoch_change_proof = if from_epoch == to_epoch {
+            EpochChangeProof::new(vec![], false)
+        } else {
+            self.context
+                .db
+                .get_epoch_ending_ledger_infos(from_epoch + 1, to_epoch + 1)
+                .map_err(|err| {
+                    BasicErrorWith404::internal_with_code(
+                        err,
+                        AptosErrorCode::InternalError,
+                        &ledger_info,
+                    )
+                })?
+        };
+        // The DB caps the ledger infos read at once, a truncated proof wouldn't reach `to`
+        if epoch_change_proof.more {
+            return Err(BasicErrorWith404::bad_request_with_code(
+                format!(
+                    "Waypoints {} and {} are {} epochs apart, more than the node reads at once, pin an intermediate waypoint",
+                    from,
+                    to,
+                    to_epoch - from_epoch
+                ),
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            ));
+        }
+        check_encoded_size(
+            &epoch_change_proof,
+            self.context.max_epoch_change_proof_size(),
+        )
+        .map_err(|err| {
+            BasicErrorWith404::bad_request_with_code(
+                format!("{}, pin an intermediate waypoint", err),
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            )
+        })?;
+
+        match accept_type {
+            AcceptType::Bcs => BasicResponse::try_from_encoded((
+                bcs::to_bytes(&epoch_change_proof).unwrap(),
+                &ledger_info,
+                BasicResponseStatus::Ok,
+            )),
+            _ => Err(api_forbidden(
+                "Get epoch change proof between waypoints",
+                "Only BCS is supported as an AcceptType.",
+            )),
+        }
+    }
+
+    fn epoch_waypoint(
+        &self,
+        accept_type: &AcceptType,
+        epoch_number: Option<u64>,
+    ) -> BasicResultWith404<WaypointView> {
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        let latest_epoch = ledger_info.epoch.0;
+        let epoch_number = epoch_number.unwrap_or(latest_epoch);
+        // The waypoint is the one of the ledger info ending the previous epoch
+        if epoch_number < 1 || epoch_number > latest_epoch {
+            return Err(BasicErrorWith404::bad_request_with_code(
+                format!(
+                    "Epoch {} is out of range, valid epochs are 1 to {}",
+                    epoch_number, latest_epoch
+                ),
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            ));
+        }
+
+        let epoch_ending_li = self
+            .context
+            .db
+            .get_epoch_ending_ledger_infos(epoch_number - 1, epoch_number)
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?
+            .ledger_info_with_sigs
+            .pop()
+            .ok_or_else(|| {
+                BasicErrorWith404::internal_with_code(
+                    format!("No LedgerInfo ending epoch {}", epoch_number - 1),
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?;
+        let waypoint =
+            Waypoint::new_epoch_boundary(epoch_ending_li.ledger_info()).map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?;
+
+        BasicResponse::try_from_rust_value((
+            WaypointView::from(waypoint),
+            &ledger_info,
+            BasicResponseStatus::Ok,
+            accept_type,
+        ))
+    }
+
+    /// Resolves `waypoint` to the epoch ended by the ledger info it was derived from.
+    fn waypoint_epoch(
+        &self,
+        waypoint: &Waypoint,
+        ledger_info: &LedgerInfo,
+    ) -> Result<u64, BasicErrorWith404> {
+        let epoch = self
+            .context
+            .epoch_for_version(waypoint.version(), ledger_info)?;
+        let mismatch = || {
+            BasicErrorWith404::bad_request_with_code(
+                format!(
+                    "Waypoint {} doesn't match any epoch-ending LedgerInfo",
+                    waypoint
+                ),
+                AptosErrorCode::InvalidInput,
+                ledger_info,
+            )
+        };
+        // The latest epoch hasn't ended yet
+        if epoch >= ledger_info.epoch.0 {
+            return Err(mismatch());
+        }
+
+        let epoch_ending_li = self
+            .context
+            .db
+            .get_epoch_ending_ledger_infos(epoch, epoch + 1)
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    ledger_info,
+                )
+            })?
+            .ledger_info_with_sigs
+            .pop()
+            .ok_or_else(mismatch)?;
+        waypoint
+            .verify(epoch_ending_li.ledger_info())
+            .map_err(|_| mismatch())?;
+        Ok(epoch)
+    }
+
+    fn epoch_progress(&self, accept_type: &AcceptType) -> BasicResultWith404<EpochProgress> {
+        let (ledger_info, ledger_version, state_view) = self.context.state_view(None)?;
+        let epoch = ledger_info.epoch();
+
+        // The epoch starts right after the ledger info ending the previous one
+        let (start_version, epoch_start_usecs) = if epoch == 0 {
+            (0, 0)
+        } else {
+            let epoch_change_proof = self
+                .context
+                .db
+                .get_epoch_ending_ledger_infos(epoch - 1, epoch)
+                .map_err(|err| {
+                    BasicErrorWith404::internal_with_code(
+                        err,
+                        AptosErrorCode::InternalError,
+                        &ledger_info,
+                    )
+                })?;
+            let previous_li = epoch_change_proof
+                .ledger_info_with_sigs
+                .first()
+                .ok_or_else(|| {
+                    BasicErrorWith404::internal_with_code(
+                        format!("No LedgerInfo ending epoch {}", epoch - 1),
+                        AptosErrorCode::InternalError,
+                        &ledger_info,
+                    )
+                })?
+                .ledger_info();
+            (previous_li.version() + 1, previous_li.timestamp_usecs())
+        };
+
+        let epoch_interval_usecs =
+            BlockResource::fetch_move_resource(&state_view, &CORE_CODE_ADDRESS)
+                .map_err(|err| {
+                    BasicErrorWith404::internal_with_code(
+                        err,
+                        AptosErrorCode::InternalError,
+                        &ledger_info,
+                    )
+                })?
+                .map(|block_resource| block_resource.epoch_interval());
+
+        let progress = EpochProgress::new(
+            epoch,
+            start_version,
+            ledger_version,
+            epoch_start_usecs,
+            ledger_info.timestamp(),
+            epoch_interval_usecs,
+        );
+        BasicResponse::try_from_rust_value((
+            progress,
+            &ledger_info,
+            BasicResponseStatus::Ok,
+            accept_type,
+        ))
+    }
+
+    fn latest_or_historical_epoch_state(
+        &self,
+        accept_type: &AcceptType,
+        epoch_number: Option<u64>,
+    ) -> BasicResultWith404<EpochStateView> {
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        let latest_epoch_state = self.context.db.get_latest_epoch_state().map_err(|err| {
+            BasicErrorWith404::internal_with_code(err, AptosErrorCode::InternalError, &ledger_info)
+        })?;
+        let latest_epoch = latest_epoch_state.epoch;
+        let epoch_state = match epoch_number {
+            None => latest_epoch_state,
+            Some(epoch_number) if epoch_number == latest_epoch => latest_epoch_state,
+            // The state of an epoch is committed by the ledger info ending the previous one
+            Some(epoch_number) if epoch_number >= 1 && epoch_number < latest_epoch => {
+                self.epoch_state(epoch_number, &ledger_info)?
+            },
+            Some(epoch_number) => {
+                return Err(BasicErrorWith404::bad_request_with_code(
+                    format!(
+                        "Epoch {} is out of range, valid epochs are 1 to {}",
+                        epoch_number, latest_epoch
+                    ),
+                    AptosErrorCode::InvalidInput,
+                    &ledger_info,
+                ))
+            },
+        };
+
+        BasicResponse::try_from_rust_value((
+            EpochStateView::from(epoch_state),
+            &ledger_info,
+            BasicResponseStatus::Ok,
+            accept_type,
+        ))
+    }
+
+    /// The validators of `epoch`, as committed by the ledger info ending the previous epoch
+    fn epoch_state(
+        &self,
+        epoch: u64,
+        ledger_info: &LedgerInfo,
+    ) -> Result<aptos_types::epoch_state::EpochState, BasicErrorWith404> {
+        let epoch_change_proof = self
+            .context
+            .db
+            .get_epoch_ending_ledger_infos(epoch - 1, epoch)
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    ledger_info,
+                )
+            })?;
+        epoch_change_proof
+            .ledger_info_with_sigs
+            .first()
+            .and_then(|li| li.ledger_info().next_epoch_state())
+            .cloned()
+            .ok_or_else(|| {
+                BasicErrorWith404::internal_with_code(
+                    format!("No EpochState committed for epoch {}", epoch),
+                    AptosErrorCode::InternalError,
+                    ledger_info,
+                )
+            })
+    }
+
+    fn proof(
+        &self,
+        accept_type: &AcceptType,
+        address: Address,
+        options: AccountProofOptions,
+        breakdown: bool,
+    ) -> BasicResultWith404<AccountProofView> {
+        // Get latest ledger info
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        if breakdown && options.trim_ledger_info {
+            return Err(BasicErrorWith404::bad_request_with_code(
+                "breakdown can't be provided along with trim_ledger_info",
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            ));
+        }
+        // The breakdown is of the BCS encoded proof, whatever the response is encoded as
+        let proof_type = if breakdown {
+            &AcceptType::Bcs
+        } else {
+            accept_type
+        };
+        let mut proofs =
+            self.requested_account_proofs(proof_type, &ledger_info, address, &options, false)?;
+        // Without the configured resources, only the requested one is proven
+        let proof = proofs.remove(0);
+
+        if breakdown {
+            return BasicResponse::try_from_rust_value((
+                AccountProofView::SizeBreakdown(proof.size_breakdown()),
+                &ledger_info,
+                BasicResponseStatus::Ok,
+                accept_type,
+            ));
+        }
+
+        match accept_type {
+            AcceptType::Bcs => {
+                let bytes = if options.trim_ledger_info {
+                    bcs::to_bytes(&trim_proof(&proof, &ledger_info)?)
+                } else {
+                    bcs::to_bytes(&proof)
+                }
+                .unwrap();
+                BasicResponse::try_from_encoded((bytes, &ledger_info, BasicResponseStatus::Ok))
+            },
+            AcceptType::Json => BasicResponse::try_from_json((
+                AccountProofView::Proofs(proof_views(vec![proof], &ledger_info)?),
+                &ledger_info,
+                BasicResponseStatus::Ok,
+            )),
+        }
+    }
+
+    fn proof_bundle(
+        &self,
+        accept_type: &AcceptType,
+        address: Address,
+        options: AccountProofOptions,
+    ) -> BasicResultWith404<Vec<StateProofView>> {
+        // Get latest ledger info
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        let proofs =
+            self.requested_account_proofs(accept_type, &ledger_info, address, &options, true)?;
+
+        match accept_type {
+            AcceptType::Bcs => {
+                let bytes = if options.trim_ledger_info {
+                    bcs::to_bytes(
+                        &proofs
+                            .iter()
+                            .map(|proof| trim_proof(proof, &ledger_info))
+                            .collect::<Result<Vec<_>, _>>()?,
+                    )
+                } else {
+                    bcs::to_bytes(&proofs)
+                }
+                .unwrap();
+                BasicResponse::try_from_encoded((bytes, &ledger_info, BasicResponseStatus::Ok))
+            },
+            AcceptType::Json => BasicResponse::try_from_json((
+                proof_views(proofs, &ledger_info)?,
+                &ledger_info,
+                BasicResponseStatus::Ok,
+            )),
+        }
+    }
+
+    /// Proves the account as requested by `options`, along with the configured resources if
+    /// `include_resources` is set, after checking the options apply to `accept_type`.
+    fn requested_account_proofs(
+        &self,
+        accept_type: &AcceptType,
+        ledger_info: &LedgerInfo,
+        address: Address,
+        options: &AccountProofOptions,
+        include_resources: bool,
+    ) -> Result<Vec<StateProofPayload>, BasicErrorWith404> {
+        let tag: Option<StructTag> = options
+            .resource_type
+            .clone()
+            .map(|resource_type| {
+                resource_type
+                    .try_into()
+                    .context("Failed to parse given resource type")
+                    .map_err(|err| {
+                        BasicErrorWith404::bad_request_with_code_no_info(
+                            err,
+                            AptosErrorCode::InvalidInput,
+                        )
+                    })
+            })
+            .transpose()?;
+
+        check_chain_id(options.chain_id, ledger_info)?;
+        if accept_type == &AcceptType::Json
+            && (options.trim_ledger_info || options.compress_state_proof)
+        {
+            return Err(BasicErrorWith404::bad_request_with_code(
+                "trim_ledger_info and compress_state_proof only apply to BCS proofs",
+                AptosErrorCode::InvalidInput,
+                ledger_info,
+            ));
+        }
+        if options.trim_ledger_info && !options.include_transaction_proof {
+            return Err(BasicErrorWith404::bad_request_with_code(
+                "trim_ledger_info requires the transaction proof, the ledger info is rebuilt from it",
+                AptosErrorCode::InvalidInput,
+                ledger_info,
+            ));
+        }
+        let mut proofs = self.account_proofs(
+            ledger_info,
+            address,
+            tag,
+            options.block_height,
+            options.ledger_version,
+            options.transaction_hash,
+            options.include_state_key,
+            options.value_chunk,
+            options.compress_state_proof,
+            include_resources,
+        )?;
+
+        if !options.include_transaction_proof {
+            for proof in &mut proofs {
+                proof.transaction_proof = None;
+                proof.transaction = None;
+            }
+        }
+
+        // All proofs share the same ledger info
+        if let Some(expected) = options.expected_ledger_info_hash {
+            let actual = proofs[0].ledger_info_v0.ledger_info().hash();
+            if actual != expected {
+                return Err(BasicErrorWith404::bad_request_with_code(
+                    format!(
+                        "The proof is anchored to LedgerInfo {}, not the expected {}",
+                        actual, expected
+                    ),
+                    AptosErrorCode::InvalidInput,
+                    ledger_info,
+                ));
+            }
+        }
+        Ok(proofs)
+    }
+
+    fn batch_proof(
+        &self,
+        accept_type: &AcceptType,
+        requests: Vec<AccountProofRequest>,
+    ) -> BasicResultWith404<Vec<u8>> {
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        let max_batch_size = self.context.max_account_proof_batch_size();
+        if requests.len() > max_batch_size {
+            return Err(BasicErrorWith404::bad_request_with_code(
+                format!(
+                    "Requested {} account proofs, at most {} are allowed",
+                    requests.len(),
+                    max_batch_size
+                ),
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            ));
+        }
+
+        // Only the proofs of the state values differ, all share the same anchor
+        let version = ledger_info.version();
+        let (anchor_li_w_sig, signing_epoch_state) = self.proof_anchor(None, &ledger_info)?;
+        let proofs = requests
+            .into_iter()
+            .map(|request| -> Result<StateProofPayload, BasicErrorWith404> {
+                let tag: StructTag = match request.resource_type {
+                    Some(resource_type) => resource_type
+                        .try_into()
+                        .context("Failed to parse given resource type")
+                        .map_err(|err| {
+                            BasicErrorWith404::bad_request_with_code(
+                                err,
+                                AptosErrorCode::InvalidInput,
+                                &ledger_info,
+                            )
+                        })?,
+                    None => AccountResource::struct_tag(),
+                };
+                let state_key =
+                    StateKey::resource(request.address.inner(), &tag).map_err(|err| {
+                        BasicErrorWith404::bad_request_with_code(
+                            err,
+                            AptosErrorCode::InvalidInput,
+                            &ledger_info,
+                        )
+                    })?;
+                let (_, mut proof) = self.anchored_state_proof(
+                    &state_key,
+                    version,
+                    anchor_li_w_sig.clone(),
+                    signing_epoch_state.clone(),
+                    &ledger_info,
+                    || resource_not_found(request.address, &tag, version, &ledger_info),
+                )?;
+                proof.state_key = Some(bcs::to_bytes(&state_key).unwrap());
+                Ok(proof)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        match accept_type {
+            AcceptType::Bcs => BasicResponse::try_from_encoded((
+                bcs::to_bytes(&proofs).unwrap(),
+                &ledger_info,
+                BasicResponseStatus::Ok,
+            )),
+            _ => Err(api_forbidden(
+                "Get batch account proof",
+                "Only BCS is supported as an AcceptType.",
+            )),
+        }
+    }
+
+    fn verify_proof(
+        &self,
+        accept_type: &AcceptType,
+        address: Address,
+        block_height: Option<u64>,
+        transaction_hash: Option<HashValue>,
+    ) -> BasicResultWith404<ProofCheckReport> {
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        let proofs = self.account_proofs(
+            &ledger_info,
+            address,
+            None,
+            block_height,
+            None,
+            transaction_hash,
+            false,
+            None,
+            false,
+            false,
+        )?;
+        let proof = &proofs[0];
+
+        // Signatures are checked against the validators committed for the signing epoch, not the
+        // ones carried by the proof. Genesis has no such validators, nor signatures.
+        let signing_epoch = proof.ledger_info_v0.ledger_info().epoch();
+        let epoch_state = if signing_epoch == 0 {
+            aptos_types::epoch_state::EpochState::new(0, proof.validator_verifier.clone())
+        } else {
+            self.epoch_state(signing_epoch, &ledger_info)?
+        };
+        BasicResponse::try_from_rust_value((
+            proof.check(&epoch_state),
+            &ledger_info,
+            BasicResponseStatus::Ok,
+            accept_type,
+        ))
+    }
+
+    /// Proves the `resource_type` resource of `address`, the account resource by default, followed
+    /// by the configured resources if `include_resources` is set, all at the same version and
+    /// against the same ledger info.
+    fn account_proofs(
+        &self,
+        ledger_info: &LedgerInfo,
+        address: Address,
+        resource_type: Option<StructTag>,
+        block_height: Option<u64>,
         ledger_version: Option<u64>,
-    ) -> BasicResultWith404<MoveResource> {
+        transaction_hash: Option<HashValue>,
+        include_state_key: bool,
+        value_chunk: Option<u64>,
+        compress_state_proof: bool,
+        include_resources: bool,
+    ) -> Result<Vec<StateProofPayload>, BasicErrorWith404> {
+        let num_provided = [
+            block_height.is_some(),
+            ledger_version.is_some(),
+            transaction_hash.is_some(),
+        ]
+        .iter()
+        .filter(|provided| **provided)
+        .count();
+        if num_provided > 1 {
+            return Err(BasicErrorWith404::bad_request_with_code(
+                "Only one of block_height, ledger_version and transaction_hash can be provided",
+                AptosErrorCode::InvalidInput,
+                ledger_info,
+            ));
+        }
+        let (tx_version, block_epoch) = match (block_height, ledger_version, transaction_hash) {
+            (Some(block_height), _, _) => {
+                let block = self
+                    .context
+                    .get_block_by_height(block_height, ledger_info, false)?;
+                let block_epoch = self
+                    .context
+                    .epoch_for_block_height(block_height, ledger_info)?;
+                (block.last_version, Some(block_epoch))
+            },
+            (None, Some(ledger_version), _) => {
+                if ledger_version > ledger_info.version() {
+                    return Err(version_not_found(ledger_version, ledger_info));
+                }
+                let block =
+                    self.context
+                        .get_block_by_version(ledger_version, ledger_info, false)?;
+                if block.last_version != ledger_version {
+                    return Err(BasicErrorWith404::bad_request_with_code(
+                        format!(
+                            "Only the last version of a block can be proven, not version {}",
+                            ledger_version
+                        ),
+                        AptosErrorCode::InvalidInput,
+                        ledger_info,
+                    ));
+                }
+                let block_epoch = self
+                    .context
+                    .epoch_for_version(ledger_version, ledger_info)?;
+                (ledger_version, Some(block_epoch))
+            },
+            // Only the end of a block can be proven, the first one including the transaction
+            (None, None, Some(hash)) => {
+                let version = self
+                    .context
+                    .db
+                    .get_transaction_by_hash(hash, ledger_info.version(), false)
+                    .map_err(|err| {
+                        BasicErrorWith404::internal_with_code(
+                            err,
+                            AptosErrorCode::InternalError,
+                            ledger_info,
+                        )
+                    })?
+                    .ok_or_else(|| transaction_not_found_by_hash(hash, ledger_info))?
+                    .version;
+                if version < ledger_info.oldest_ledger_version.0 {
+                    return Err(version_pruned(version, ledger_info));
+                }
+                let block = self
+                    .context
+                    .get_block_by_version(version, ledger_info, false)?;
+                let block_epoch = self.context.epoch_for_version(version, ledger_info)?;
+                (block.last_version, Some(block_epoch))
+            },
+            (None, None, None) => (ledger_info.version(), None),
+        };
+
+        // Compute account key
+        let requested_resource = resource_type.is_some();
+        let tag = resource_type.unwrap_or_else(AccountResource::struct_tag);
+        let account_key = StateKey::resource(address.inner(), &tag).map_err(|err| {
+            BasicErrorWith404::internal_with_code(err, AptosErrorCode::InternalError, ledger_info)
+        })?;
+
+        let (state_value, mut proof) =
+            self.state_proof(&account_key, tx_version, block_epoch, ledger_info, || {
+                if requested_resource {
+                    resource_not_found(address, &tag, tx_version, ledger_info)
+                } else {
+                    BasicErrorWith404::internal_with_code(
+                        "No state value from get_state_value_with_proof_by_version",
+                        AptosErrorCode::InternalError,
+                        ledger_info,
+                    )
+                }
+            })?;
+
+        proof.value_chunk = value_chunk
+            .map(|index| {
+                let chunks =
+                    StateValueChunk::split(&state_value, self.context.max_state_value_chunk_size());
+                let num_chunks = chunks.len();
+                chunks.into_iter().nth(index as usize).ok_or_else(|| {
+                    BasicErrorWith404::bad_request_with_code(
+                        format!(
+                            "Value chunk {} is out of range, the state value has {} chunks",
+                            index, num_chunks
+                        ),
+                        AptosErrorCode::InvalidInput,
+                        ledger_info,
+                    )
+                })
+            })
+            .transpose()?;
+        proof.state_key = include_state_key.then(|| bcs::to_bytes(&account_key).unwrap());
+
+        // The resources are proven at the same version as the account, so all proofs share the
+        // same ledger info
+        let mut proofs = vec![proof];
+        if include_resources {
+            for resource in self.context.account_proof_resources() {
+                let tag: StructTag = resource.parse().map_err(|err| {
+                    BasicErrorWith404::internal_with_code(
+                        format!("Invalid account proof resource {}: {}", resource, err),
+                        AptosErrorCode::InternalError,
+                        ledger_info,
+                    )
+                })?;
+                let state_key = StateKey::resource(address.inner(), &tag).map_err(|err| {
+                    BasicErrorWith404::internal_with_code(
+                        err,
+                        AptosErrorCode::InternalError,
+                        ledger_info,
+                    )
+                })?;
+                // Already proven as the requested resource
+                if state_key == account_key {
+                    continue;
+                }
+                let exists = self
+                    .context
+                    .db
+                    .get_state_value_by_version(&state_key, tx_version)
+                    .map_err(|err| {
+                        BasicErrorWith404::internal_with_code(
+                            err,
+                            AptosErrorCode::InternalError,
+                            ledger_info,
+                        )
+                    })?
+                    .is_some();
+                if !exists {
+                    continue;
+                }
+                let (_, mut proof) =
+                    self.state_proof(&state_key, tx_version, block_epoch, ledger_info, || {
+                        resource_not_found(address, &tag, tx_version, ledger_info)
+                    })?;
+                proof.state_key = Some(bcs::to_bytes(&state_key).unwrap());
+                proofs.push(proof);
+            }
+        }
+
+        if compress_state_proof {
+            for proof in &mut proofs {
+                proof.compressed_siblings =
+                    Some(CompressedSiblings::new(proof.state_proof.siblings()));
+                proof.state_proof = SparseMerkleProof::new(proof.state_proof.leaf(), vec![]);
+            }
+        }
+
+        Ok(proofs)
+    }
+
+    fn resource_proofs(
+        &self,
+        accept_type: &AcceptType,
+        address: Address,
+        resource_type: MoveStructTag,
+        versions: Vec<u64>,
+    ) -> BasicResultWith404<Vec<u8>> {
         let tag: StructTag = resource_type
             .try_into()
             .context("Failed to parse given resource type")
@@ -377,158 +2692,561 @@ impl StateApi {
                 BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
             })?;
 
-        let (ledger_info, ledger_version, state_view) = self.context.state_view(ledger_version)?;
-        let bytes = state_view
-            .as_converter(
-                self.context.db.clone(),
-                self.context.table_info_reader.clone(),
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        let max_versions = self.context.max_resource_proof_versions();
+        if versions.len() > max_versions {
+            return Err(BasicErrorWith404::bad_request_with_code(
+                format!(
+                    "Requested {} versions, at most {} are allowed",
+                    versions.len(),
+                    max_versions
+                ),
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            ));
+        }
+
+        let state_key = StateKey::resource(address.inner(), &tag).map_err(|err| {
+            BasicErrorWith404::bad_request_with_code(
+                err,
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
             )
-            .find_resource(&state_view, address, &tag)
-            .context(format!(
-                "Failed to query DB to check for {} at {}",
-                tag, address
-            ))
+        })?;
+        let proofs = versions
+            .into_iter()
+            .map(|version| -> Result<StateProofPayload, BasicErrorWith404> {
+                let epoch = self.context.epoch_for_version(version, &ledger_info)?;
+                let (_, mut proof) =
+                    self.state_proof(&state_key, version, Some(epoch), &ledger_info, || {
+                        resource_not_found(address, &tag, version, &ledger_info)
+                    })?;
+                proof.state_key = Some(bcs::to_bytes(&state_key).unwrap());
+                Ok(proof)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        match accept_type {
+            AcceptType::Bcs => BasicResponse::try_from_encoded((
+                bcs::to_bytes(&proofs).unwrap(),
+                &ledger_info,
+                BasicResponseStatus::Ok,
+            )),
+            _ => Err(api_forbidden(
+                "Get account resource proofs",
+                "Only BCS is supported as an AcceptType.",
+            )),
+        }
+    }
+
+    fn resources_proof(
+        &self,
+        accept_type: &AcceptType,
+        address: Address,
+        ledger_version: Option<u64>,
+        start: Option<StateKey>,
+        limit: Option<u16>,
+    ) -> BasicResultWith404<Vec<u8>> {
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        let (version, epoch) = match ledger_version {
+            Some(version) => (
+                version,
+                Some(self.context.epoch_for_version(version, &ledger_info)?),
+            ),
+            None => (ledger_info.version(), None),
+        };
+        if version < ledger_info.oldest_version() {
+            return Err(version_pruned(version, &ledger_info));
+        }
+        let max_limit =
+            u16::try_from(self.context.max_account_proof_batch_size()).unwrap_or(u16::MAX);
+        let limit = determine_limit(limit, max_limit, max_limit, &ledger_info)?;
+
+        let (state_keys, next_state_key) = self
+            .context
+            .get_resource_state_keys(*address.inner(), start.as_ref(), version, limit as usize)
             .map_err(|err| {
                 BasicErrorWith404::internal_with_code(
                     err,
                     AptosErrorCode::InternalError,
                     &ledger_info,
                 )
-            })?
-            .ok_or_else(|| resource_not_found(address, &tag, ledger_version, &ledger_info))?;
+            })?;
 
-        match accept_type {
-            AcceptType::Json => {
-                let resource = state_view
-                    .as_converter(
-                        self.context.db.clone(),
-                        self.context.table_info_reader.clone(),
-                    )
-                    .try_into_resource(&tag, &bytes)
-                    .context("Failed to deserialize resource data retrieved from DB")
-                    .map_err(|err| {
+        // Only the proofs of the state values differ, all share the same anchor
+        let (anchor_li_w_sig, signing_epoch_state) = self.proof_anchor(epoch, &ledger_info)?;
+        let resources = state_keys
+            .iter()
+            .map(|state_key| -> Result<ProvenResource, BasicErrorWith404> {
+                let (state_value, mut proof) = self.anchored_state_proof(
+                    state_key,
+                    version,
+                    anchor_li_w_sig.clone(),
+                    signing_epoch_state.clone(),
+                    &ledger_info,
+                    || {
                         BasicErrorWith404::internal_with_code(
-                            err,
+                            "No state value from get_state_value_with_proof_by_version",
                             AptosErrorCode::InternalError,
                             &ledger_info,
                         )
-                    })?;
+                    },
+                )?;
+                proof.state_key = Some(bcs::to_bytes(state_key).unwrap());
+                Ok(ProvenResource { state_value, proof })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
 
-                BasicResponse::try_from_json((resource, &ledger_info, BasicResponseStatus::Ok))
-            },
+        let payload = AccountResourcesProofPayload { version, resources };
+        match accept_type {
             AcceptType::Bcs => BasicResponse::try_from_encoded((
-                bytes.to_vec(),
+                bcs::to_bytes(&payload).unwrap(),
                 &ledger_info,
                 BasicResponseStatus::Ok,
+            ))
+            .map(|response| response.with_cursor(next_state_key)),
+            _ => Err(api_forbidden(
+                "Get account resources proof",
+                "Only BCS is supported as an AcceptType.",
             )),
         }
     }
 
-    fn epoch_change_proof(
+    fn state_delta(
         &self,
         accept_type: &AcceptType,
-        epoch_number: Option<u64>,
+        address: Address,
+        from_version: u64,
+        to_version: u64,
     ) -> BasicResultWith404<Vec<u8>> {
-        let (ledger_info, _, _) = self.context.state_view(None)?;
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        if from_version > to_version {
+            return Err(BasicErrorWith404::bad_request_with_code(
+                format!(
+                    "from_version {} is after to_version {}",
+                    from_version, to_version
+                ),
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            ));
+        }
 
-        fn get_epoch_change_proof_payload(
-            db: &Arc<dyn DbReader>,
-            epoch_number: u64,
-            ledger_info: &LedgerInfo,
-        ) -> Result<(TrustedState, EpochChangeProof), BasicErrorWith404> {
-            let mut epoch_change_proof: EpochChangeProof = db
-                .get_epoch_ending_ledger_infos(epoch_number - 2, epoch_number)
+        // Pruned and future versions are reported before scanning the state at them
+        let from_epoch = self.context.epoch_for_version(from_version, &ledger_info)?;
+        let to_epoch = self.context.epoch_for_version(to_version, &ledger_info)?;
+
+        // Resources of both versions, in state key order, up to the cap
+        let max_resources = self.context.max_state_delta_resources();
+        let mut state_keys = BTreeSet::new();
+        let mut more = false;
+        for version in [from_version, to_version] {
+            let (keys, next_state_key) = self
+                .context
+                .get_resource_state_keys(*address.inner(), None, version, max_resources)
                 .map_err(|err| {
                     BasicErrorWith404::internal_with_code(
                         err,
                         AptosErrorCode::InternalError,
-                        ledger_info,
+                        &ledger_info,
                     )
                 })?;
+            state_keys.extend(keys);
+            more |= next_state_key.is_some();
+        }
+        if state_keys.len() > max_resources {
+            more = true;
+            state_keys = state_keys.into_iter().take(max_resources).collect();
+        }
+
+        // The transaction and ledger info of each version are shared by all of its proofs
+        let anchor_at = |version, epoch| {
+            let (anchor_li_w_sig, signing_epoch_state) =
+                self.proof_anchor(Some(epoch), &ledger_info)?;
+            self.state_anchor(version, anchor_li_w_sig, signing_epoch_state, &ledger_info)
+        };
+        let from = anchor_at(from_version, from_epoch)?;
+        let to = anchor_at(to_version, to_epoch)?;
+        let internal_error = |err: String| {
+            BasicErrorWith404::internal_with_code(err, AptosErrorCode::InternalError, &ledger_info)
+        };
+        let from_state_root = from
+            .state_root()
+            .map_err(|err| internal_error(err.to_string()))?;
+        let to_state_root = to
+            .state_root()
+            .map_err(|err| internal_error(err.to_string()))?;
+
+        // Missing resources come with a proof of their absence
+        let prove = |state_key: &StateKey, version, state_root| {
+            let (state_value, proof) = self
+                .context
+                .db
+                .get_state_value_with_proof_by_version(state_key, version)
+                .map_err(|err| internal_error(err.to_string()))?;
+            let value_hash = state_value.as_ref().map(CryptoHash::hash);
+            proof
+                .verify_by_hash(state_root, state_key.hash(), value_hash)
+                .map_err(|err| internal_error(err.to_string()))?;
+            Ok::<_, BasicErrorWith404>((value_hash, proof))
+        };
+        let resources = state_keys
+            .iter()
+            .map(|state_key| {
+                let (from_value_hash, from_proof) =
+                    prove(state_key, from_version, from_state_root)?;
+                let (to_value_hash, to_proof) = prove(state_key, to_version, to_state_root)?;
+                Ok(ResourceDelta {
+                    state_key: bcs::to_bytes(state_key).unwrap(),
+                    from_value_hash,
+                    from_proof,
+                    to_value_hash,
+                    to_proof,
+                    changed: from_value_hash != to_value_hash,
+                })
+            })
+            .collect::<Result<Vec<_>, BasicErrorWith404>>()?;
 
-            assert_eq!(
-                epoch_change_proof.ledger_info_with_sigs.len(),
-                2,
-                "Expected two LedgerInfoWithSignatures in EpochchangeProof"
-            );
-
-            let penultimate_li = epoch_change_proof.ledger_info_with_sigs.remove(0);
-            let waypoint = Waypoint::new_any(penultimate_li.ledger_info());
-
-            Ok((
-                TrustedState::EpochState {
-                    waypoint,
-                    epoch_state: aptos_types::epoch_state::EpochState::new(
-                        epoch_number - 1,
-                        penultimate_li
-                            .ledger_info()
-                            .next_epoch_state()
-                            .expect("Latest li for epoch change should contain a next EpochState")
-                            .clone()
-                            .verifier,
+        let payload = AccountStateDeltaPayload {
+            from,
+            to,
+            resources,
+            more,
+        };
+        match accept_type {
+            AcceptType::Bcs => BasicResponse::try_from_encoded((
+                bcs::to_bytes(&payload).unwrap(),
+                &ledger_info,
+                BasicResponseStatus::Ok,
+            )),
+            _ => Err(api_forbidden(
+                "Get account state delta",
+                "Only BCS is supported as an AcceptType.",
+            )),
+        }
+    }
+
+    fn state_root(
+        &self,
+        accept_type: &AcceptType,
+        version: u64,
+    ) -> BasicResultWith404<HashValueView> {
+        let (ledger_info, version) = self
+            .context
+            .get_latest_ledger_info_and_verify_lookup_version(Some(version))?;
+        let txn_w_proof = self
+            .context
+            .db
+            .get_transaction_by_version(version, ledger_info.version(), false)
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?;
+        let state_root = txn_w_proof
+            .proof
+            .transaction_info
+            .state_checkpoint_hash()
+            .ok_or_else(|| {
+                BasicErrorWith404::bad_request_with_code(
+                    format!(
+                        "Only the last version of a block commits to a state root, not version {}",
+                        version
                     ),
-                },
-                epoch_change_proof,
-            ))
+                    AptosErrorCode::InvalidInput,
+                    &ledger_info,
+                )
+            })?;
+
+        match accept_type {
+            AcceptType::Json => BasicResponse::try_from_json((
+                state_root.into(),
+                &ledger_info,
+                BasicResponseStatus::Ok,
+            )),
+            AcceptType::Bcs => {
+                BasicResponse::try_from_bcs((state_root, &ledger_info, BasicResponseStatus::Ok))
+            },
         }
+    }
 
-        let (trusted_state, epoch_change_proof): (TrustedState, EpochChangeProof) =
-            match epoch_number {
-                Some(epoch_number) => {
-                    get_epoch_change_proof_payload(&self.context.db, epoch_number, &ledger_info)?
-                },
-                None => {
-                    let latest_epoch_state: aptos_types::epoch_state::EpochState =
-                        self.context.db.get_latest_epoch_state().map_err(|err| {
-                            BasicErrorWith404::internal_with_code(
-                                err,
-                                AptosErrorCode::InternalError,
-                                &ledger_info,
-                            )
-                        })?;
-                    get_epoch_change_proof_payload(
-                        &self.context.db,
-                        latest_epoch_state.epoch,
-                        &ledger_info,
-                    )?
+    fn state_checkpoint_proof(
+        &self,
+        accept_type: &AcceptType,
+        address: Address,
+        resource_type: Option<MoveStructTag>,
+        before_version: Option<u64>,
+    ) -> BasicResultWith404<Vec<u8>> {
+        let tag: StructTag = resource_type
+            .map(|resource_type| {
+                resource_type
+                    .try_into()
+                    .context("Failed to parse given resource type")
+                    .map_err(|err| {
+                        BasicErrorWith404::bad_request_with_code_no_info(
+                            err,
+                            AptosErrorCode::InvalidInput,
+                        )
+                    })
+            })
+            .transpose()?
+            .unwrap_or_else(AccountResource::struct_tag);
+
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        // Checkpoints past the latest ledger info can't be proven yet
+        let checkpoint = match before_version {
+            Some(before_version) => self
+                .context
+                .db
+                .get_state_snapshot_before(before_version.min(ledger_info.version() + 1))
+                .map(|snapshot| snapshot.map(|(version, _)| version)),
+            None => latest_checkpoint_at(self.context.db.as_ref(), ledger_info.version()),
+        }
+        .map_err(|err| {
+            BasicErrorWith404::internal_with_code(err, AptosErrorCode::InternalError, &ledger_info)
+        })?;
+        let version = checkpoint.ok_or_else(|| {
+            build_not_found(
+                "State checkpoint",
+                match before_version {
+                    Some(before_version) => format!("Before version({})", before_version),
+                    None => format!("Ledger version({})", ledger_info.version()),
                 },
-            };
+                AptosErrorCode::VersionNotFound,
+                &ledger_info,
+            )
+        })?;
+        if version < ledger_info.oldest_version() {
+            return Err(version_pruned(version, &ledger_info));
+        }
 
-        let epoch_change_proof_payload = EpochChangeProofPayload {
-            epoch_change_proof,
-            trusted_state,
+        let state_key = StateKey::resource(address.inner(), &tag).map_err(|err| {
+            BasicErrorWith404::bad_request_with_code(
+                err,
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            )
+        })?;
+        let epoch = self.context.epoch_for_version(version, &ledger_info)?;
+        let (_, mut proof) =
+            self.state_proof(&state_key, version, Some(epoch), &ledger_info, || {
+                resource_not_found(address, &tag, version, &ledger_info)
+            })?;
+        proof.state_key = Some(bcs::to_bytes(&state_key).unwrap());
+
+        match accept_type {
+            AcceptType::Bcs => BasicResponse::try_from_encoded((
+                bcs::to_bytes(&proof).unwrap(),
+                &ledger_info,
+                BasicResponseStatus::Ok,
+            )),
+            _ => Err(api_forbidden(
+                "Get state checkpoint proof",
+                "Only BCS is supported as an AcceptType.",
+            )),
+        }
+    }
+
+    fn transaction_proof(
+        &self,
+        accept_type: &AcceptType,
+        version: u64,
+    ) -> BasicResultWith404<Vec<u8>> {
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        if version > ledger_info.version() {
+            return Err(version_not_found(version, &ledger_info));
+        }
+        if version < ledger_info.oldest_version() {
+            return Err(version_pruned(version, &ledger_info));
+        }
+
+        let ledger_info_v0 = self
+            .context
+            .get_latest_ledger_info_with_signatures()
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?;
+        let txn_w_proof = self
+            .context
+            .db
+            .get_transaction_by_version(version, ledger_info_v0.ledger_info().version(), false)
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?;
+        let proof = TransactionProofPayload {
+            transaction_proof: txn_w_proof.proof.ledger_info_to_transaction_info_proof,
+            transaction: txn_w_proof.proof.transaction_info,
+            transaction_index: version,
+            ledger_info_v0,
         };
 
         match accept_type {
             AcceptType::Bcs => BasicResponse::try_from_encoded((
-                bcs::to_bytes(&epoch_change_proof_payload).unwrap(),
+                bcs::to_bytes(&proof).unwrap(),
                 &ledger_info,
                 BasicResponseStatus::Ok,
             )),
             _ => Err(api_forbidden(
-                "Get epoch change proof",
+                "Get transaction proof",
                 "Only BCS is supported as an AcceptType.",
             )),
         }
     }
 
-    fn proof(
+    fn event_proof(
         &self,
         accept_type: &AcceptType,
         address: Address,
-        block_height: Option<u64>,
+        creation_number: u64,
+        sequence_number: u64,
+        ledger_version: Option<u64>,
     ) -> BasicResultWith404<Vec<u8>> {
-        // Get latest ledger info
-        let (ledger_info, ledger_version, state_view) = self.context.state_view(None)?;
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        let (version, epoch) = match ledger_version {
+            Some(version) => (
+                version,
+                Some(self.context.epoch_for_version(version, &ledger_info)?),
+            ),
+            None => (ledger_info.version(), None),
+        };
+        if version < ledger_info.oldest_version() {
+            return Err(version_pruned(version, &ledger_info));
+        }
 
-        let tx_version = if let Some(block_height) = block_height {
-            self.context
-                .get_block_by_height(block_height, &ledger_info, false)?
-                .last_version
-        } else {
-            ledger_version
+        let (anchor_li_w_sig, _) = self.proof_anchor(epoch, &ledger_info)?;
+        let event_key = EventKey::new(creation_number, *address.inner());
+
+        // The event can be emitted by a transaction older than the requested version, which may
+        // have been pruned even if the requested version hasn't
+        let event = self
+            .context
+            .get_events(&event_key, Some(sequence_number), 1, version)
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?
+            .pop()
+            .ok_or_else(|| {
+                event_not_found(
+                    address,
+                    creation_number,
+                    sequence_number,
+                    version,
+                    &ledger_info,
+                )
+            })?;
+        if event.transaction_version < ledger_info.oldest_version() {
+            return Err(version_pruned(event.transaction_version, &ledger_info));
+        }
+
+        let (event, event_index, event_proof) = self
+            .context
+            .db
+            .get_event_with_proof_by_key(&event_key, sequence_number, version)
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?
+            .ok_or_else(|| {
+                event_not_found(
+                    address,
+                    creation_number,
+                    sequence_number,
+                    version,
+                    &ledger_info,
+                )
+            })?;
+
+        let txn_w_proof = self
+            .context
+            .db
+            .get_transaction_by_version(
+                event.transaction_version,
+                anchor_li_w_sig.ledger_info().version(),
+                false,
+            )
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?;
+        let proof = EventProofPayload {
+            event: event.event,
+            event_index,
+            event_proof,
+            transaction_proof: txn_w_proof.proof.ledger_info_to_transaction_info_proof,
+            transaction: txn_w_proof.proof.transaction_info,
+            transaction_index: event.transaction_version,
+            ledger_info_v0: anchor_li_w_sig,
         };
 
+        match accept_type {
+            AcceptType::Bcs => BasicResponse::try_from_encoded((
+                bcs::to_bytes(&proof).unwrap(),
+                &ledger_info,
+                BasicResponseStatus::Ok,
+            )),
+            _ => Err(api_forbidden(
+                "Get event proof",
+                "Only BCS is supported as an AcceptType.",
+            )),
+        }
+    }
+
+    /// Proves the state of `state_key` at `version`, which must be the last version of a block.
+    ///
+    /// The proof is anchored to the ledger info ending `epoch` if it's a past epoch, so it is
+    /// signed by the validators of that epoch, or to the latest ledger info otherwise. Returns the
+    /// proven state value along with the proof, or `not_found()` if there is no value.
+    fn state_proof(
+        &self,
+        state_key: &StateKey,
+        version: u64,
+        epoch: Option<u64>,
+        ledger_info: &LedgerInfo,
+        not_found: impl FnOnce() -> BasicErrorWith404,
+    ) -> Result<(StateValue, StateProofPayload), BasicErrorWith404> {
+        let (anchor_li_w_sig, signing_epoch_state) = self.proof_anchor(epoch, ledger_info)?;
+        self.anchored_state_proof(
+            state_key,
+            version,
+            anchor_li_w_sig,
+            signing_epoch_state,
+            ledger_info,
+            not_found,
+        )
+    }
+
+    /// The ledger info proofs are anchored to, see [`StateApi::state_proof`], along with the epoch
+    /// state whose validators signed it.
+    fn proof_anchor(
+        &self,
+        epoch: Option<u64>,
+        ledger_info: &LedgerInfo,
+    ) -> Result<
+        (
+            LedgerInfoWithSignatures,
+            aptos_types::epoch_state::EpochState,
+        ),
+        BasicErrorWith404,
+    > {
         let latest_li_w_sig = self
             .context
             .get_latest_ledger_info_with_signatures()
@@ -536,111 +3254,173 @@ impl StateApi {
                 BasicErrorWith404::internal_with_code(
                     err,
                     AptosErrorCode::InternalError,
-                    &ledger_info,
+                    ledger_info,
                 )
             })?;
 
-        // Compute account key
-        let account_key = StateKey::resource(address.inner(), &AccountResource::struct_tag())
-            .map_err(|err| {
-                BasicErrorWith404::internal_with_code(
-                    err,
-                    AptosErrorCode::InternalError,
-                    &ledger_info,
-                )
-            })?;
+        let anchor_li_w_sig = match epoch {
+            Some(epoch) if epoch < latest_li_w_sig.ledger_info().epoch() => self
+                .context
+                .db
+                .get_epoch_ending_ledger_infos(epoch, epoch + 1)
+                .map_err(|err| {
+                    BasicErrorWith404::internal_with_code(
+                        err,
+                        AptosErrorCode::InternalError,
+                        ledger_info,
+                    )
+                })?
+                .ledger_info_with_sigs
+                .pop()
+                .ok_or_else(|| {
+                    BasicErrorWith404::internal_with_code(
+                        format!("No LedgerInfo ending epoch {}", epoch),
+                        AptosErrorCode::InternalError,
+                        ledger_info,
+                    )
+                })?,
+            _ => latest_li_w_sig,
+        };
 
-        let latest_epoch_state: aptos_types::epoch_state::EpochState =
-            state_view.db.get_latest_epoch_state().map_err(|err| {
-                BasicErrorWith404::internal_with_code(
-                    err,
-                    AptosErrorCode::InternalError,
-                    &ledger_info,
-                )
-            })?;
+        // At a reconfiguration, the anchor ledger info carries the validators of the next epoch
+        // but is signed by those of its own epoch. The genesis ledger info is the exception, it is
+        // authenticated by the genesis waypoint rather than by signatures, so it comes with the
+        // validators it carries.
+        let anchor_epoch = anchor_li_w_sig.ledger_info().epoch();
+        let signing_epoch_state = if !anchor_li_w_sig.ledger_info().ends_epoch() {
+            self.context.latest_epoch_state(anchor_epoch, ledger_info)?
+        } else if anchor_epoch > 0 {
+            self.epoch_state(anchor_epoch, ledger_info)?
+        } else {
+            anchor_li_w_sig
+                .ledger_info()
+                .next_epoch_state()
+                .cloned()
+                .ok_or_else(|| {
+                    BasicErrorWith404::internal_with_code(
+                        "Genesis LedgerInfo should contain a next EpochState",
+                        AptosErrorCode::InternalError,
+                        ledger_info,
+                    )
+                })?
+        };
 
-        // Get state value and sparse merkle proof
-        let (state_value, state_proof) = state_view
-            .db
-            .get_state_value_with_proof_by_version(&account_key, tx_version)
-            .map_err(|err| {
+        // A reconfiguration committed while the proof is built could leave a stale validator set
+        check_signing_epoch_state(anchor_li_w_sig.ledger_info(), &signing_epoch_state).map_err(
+            |err| {
                 BasicErrorWith404::internal_with_code(
                     err,
                     AptosErrorCode::InternalError,
-                    &ledger_info,
+                    ledger_info,
                 )
-            })?;
+            },
+        )?;
+        Ok((anchor_li_w_sig, signing_epoch_state))
+    }
 
-        let sparse_proof: SparseMerkleProof = state_proof;
-        let element_key = account_key.hash();
-        let element_hash = state_value
-            .ok_or_else(|| {
-                BasicErrorWith404::internal_with_code(
-                    "No state value from get_state_value_with_proof_by_version",
-                    AptosErrorCode::InternalError,
-                    &ledger_info,
-                )
-            })?
-            .hash();
+    /// Same as [`StateApi::state_proof`], anchored to `anchor_li_w_sig` signed by the validators
+    /// of `signing_epoch_state`, as returned by [`StateApi::proof_anchor`].
+    fn anchored_state_proof(
+        &self,
+        state_key: &StateKey,
+        version: u64,
+        anchor_li_w_sig: LedgerInfoWithSignatures,
+        signing_epoch_state: aptos_types::epoch_state::EpochState,
+        ledger_info: &LedgerInfo,
+        not_found: impl FnOnce() -> BasicErrorWith404,
+    ) -> Result<(StateValue, StateProofPayload), BasicErrorWith404> {
+        if version < ledger_info.oldest_version() {
+            return Err(version_pruned(version, ledger_info));
+        }
 
-        let txn_w_proof = self
+        // Get state value and sparse merkle proof
+        let (state_value, sparse_proof) = self
             .context
             .db
-            .get_transaction_by_version(tx_version, latest_li_w_sig.ledger_info().version(), false)
+            .get_state_value_with_proof_by_version(state_key, version)
             .map_err(|err| {
                 BasicErrorWith404::internal_with_code(
                     err,
                     AptosErrorCode::InternalError,
-                    &ledger_info,
+                    ledger_info,
                 )
             })?;
+        let state_value = state_value.ok_or_else(not_found)?;
+        let element_key = state_key.hash();
+        let element_hash = state_value.hash();
+
+        let anchor =
+            self.state_anchor(version, anchor_li_w_sig, signing_epoch_state, ledger_info)?;
+        let state_root = anchor.state_root().map_err(|err| {
+            BasicErrorWith404::internal_with_code(err, AptosErrorCode::InternalError, ledger_info)
+        })?;
 
         // Verify proof
         sparse_proof
-            .verify_by_hash(
-                txn_w_proof
-                    .clone()
-                    .proof
-                    .transaction_info
-                    .deref()
-                    .state_checkpoint_hash()
-                    .unwrap(),
-                element_key,
-                Some(element_hash),
-            )
+            .verify_by_hash(state_root, element_key, Some(element_hash))
             .map_err(|err| {
                 BasicErrorWith404::internal_with_code(
                     err,
                     AptosErrorCode::InternalError,
-                    &ledger_info,
+                    ledger_info,
                 )
             })?;
 
-        let ledger_info_to_transaction_info_proof =
-            txn_w_proof.proof.ledger_info_to_transaction_info_proof;
-
-        let proof = AccountProofPayload {
+        let proof = StateProofPayload {
             state_proof: sparse_proof,
             element_key,
             element_hash,
-            transaction_proof: ledger_info_to_transaction_info_proof,
-            transaction: txn_w_proof.proof.transaction_info.clone(),
-            transaction_index: tx_version,
-            ledger_info_v0: latest_li_w_sig,
-            validator_verifier: latest_epoch_state.verifier,
+            transaction_proof: Some(anchor.transaction_proof),
+            transaction: Some(anchor.transaction),
+            transaction_index: anchor.transaction_index,
+            ledger_info_v0: anchor.ledger_info_v0,
+            validator_verifier: anchor.validator_verifier,
+            state_key: None,
+            value_chunk: None,
+            compressed_siblings: None,
         };
+        Ok((state_value, proof))
+    }
 
-        match accept_type {
-            AcceptType::Bcs => BasicResponse::try_from_encoded((
-                bcs::to_bytes(&proof).unwrap(),
-                &ledger_info,
-                BasicResponseStatus::Ok,
-            )),
-            _ => Err(api_forbidden(
-                "Get account proof",
-                "Only BCS is supported as an AcceptType.",
-            )),
+    /// The transaction at `version` proven against `anchor_li_w_sig`, signed by the validators of
+    /// `signing_epoch_state`. Proofs of the state at `version` are verified against its state root.
+    fn state_anchor(
+        &self,
+        version: u64,
+        anchor_li_w_sig: LedgerInfoWithSignatures,
+        signing_epoch_state: aptos_types::epoch_state::EpochState,
+        ledger_info: &LedgerInfo,
+    ) -> Result<StateAnchor, BasicErrorWith404> {
+        let txn_w_proof = self
+            .context
+            .db
+            .get_transaction_by_version(version, anchor_li_w_sig.ledger_info().version(), false)
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    ledger_info,
+                )
+            })?;
+        let transaction = txn_w_proof.proof.transaction_info;
+        if transaction.state_checkpoint_hash().is_none() {
+            return Err(BasicErrorWith404::bad_request_with_code(
+                format!(
+                    "Only the last version of a block can be proven, not version {}",
+                    version
+                ),
+                AptosErrorCode::InvalidInput,
+                ledger_info,
+            ));
         }
+
+        Ok(StateAnchor {
+            transaction_proof: txn_w_proof.proof.ledger_info_to_transaction_info_proof,
+            transaction,
+            transaction_index: version,
+            ledger_info_v0: anchor_li_w_sig,
+            validator_verifier: signing_epoch_state.verifier,
+        })
     }
 
     /// Retrieve the module
@@ -693,6 +3473,98 @@ impl StateApi {
         }
     }
 
+    fn module_proof(
+        &self,
+        accept_type: &AcceptType,
+        address: Address,
+        name: IdentifierWrapper,
+    ) -> BasicResultWith404<Vec<u8>> {
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        let version = ledger_info.version();
+        let state_key = StateKey::module(address.inner(), &name);
+        let (_, mut proof) = self.state_proof(&state_key, version, None, &ledger_info, || {
+            module_not_found(address, &name, version, &ledger_info)
+        })?;
+        proof.state_key = Some(bcs::to_bytes(&state_key).unwrap());
+
+        match accept_type {
+            AcceptType::Bcs => BasicResponse::try_from_encoded((
+                bcs::to_bytes(&proof).unwrap(),
+                &ledger_info,
+                BasicResponseStatus::Ok,
+            )),
+            _ => Err(api_forbidden(
+                "Get account module proof",
+                "Only BCS is supported as an AcceptType.",
+            )),
+        }
+    }
+
+    /// Computes the state key of a table item, converting its key to the lookup version for DB
+    fn table_item_state_key(
+        &self,
+        state_view: &DbStateView,
+        table_handle: Address,
+        key_type: &TypeTag,
+        key: serde_json::Value,
+        ledger_info: &LedgerInfo,
+    ) -> Result<StateKey, BasicErrorWith404> {
+        table_item_key(
+            &state_view.as_converter(
+                self.context.db.clone(),
+                self.context.table_info_reader.clone(),
+            ),
+            table_handle,
+            key_type,
+            key,
+        )
+        .map_err(|err| {
+            BasicErrorWith404::bad_request_with_code(err, AptosErrorCode::InvalidInput, ledger_info)
+        })
+    }
+
+    fn table_item_proof(
+        &self,
+        accept_type: &AcceptType,
+        table_handle: Address,
+        table_item_request: TableItemRequest,
+    ) -> BasicResultWith404<Vec<u8>> {
+        let key_type = table_item_request
+            .key_type
+            .try_into()
+            .context("Failed to parse key_type")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        let key = table_item_request.key;
+
+        let (ledger_info, ledger_version, state_view) = self.context.state_view(None)?;
+        let state_key = self.table_item_state_key(
+            &state_view,
+            table_handle,
+            &key_type,
+            key.clone(),
+            &ledger_info,
+        )?;
+        let (_, mut proof) =
+            self.state_proof(&state_key, ledger_version, None, &ledger_info, || {
+                table_item_not_found(table_handle, &key, ledger_version, &ledger_info)
+            })?;
+        proof.state_key = Some(bcs::to_bytes(&state_key).unwrap());
+
+        match accept_type {
+            AcceptType::Bcs => BasicResponse::try_from_encoded((
+                bcs::to_bytes(&proof).unwrap(),
+                &ledger_info,
+                BasicResponseStatus::Ok,
+            )),
+            _ => Err(api_forbidden(
+                "Get table item proof",
+                "Only BCS is supported as an AcceptType.",
+            )),
+        }
+    }
+
     /// Retrieve table item for a specific ledger version
     pub fn table_item(
         &self,
@@ -728,26 +3600,14 @@ impl StateApi {
             self.context.table_info_reader.clone(),
         );
 
-        // Convert key to lookup version for DB
-        let vm_key = converter
-            .try_into_vm_value(&key_type, key.clone())
-            .map_err(|err| {
-                BasicErrorWith404::bad_request_with_code(
-                    err,
-                    AptosErrorCode::InvalidInput,
-                    &ledger_info,
-                )
-            })?;
-        let raw_key = vm_key.undecorate().simple_serialize().ok_or_else(|| {
-            BasicErrorWith404::bad_request_with_code(
-                "Failed to serialize table key",
-                AptosErrorCode::InvalidInput,
-                &ledger_info,
-            )
-        })?;
-
         // Retrieve value from the state key
-        let state_key = StateKey::table_item(&TableHandle(table_handle.into()), &raw_key);
+        let state_key = self.table_item_state_key(
+            &state_view,
+            table_handle,
+            &key_type,
+            key.clone(),
+            &ledger_info,
+        )?;
         let bytes = state_view
             .get_state_value_bytes(&state_key)
             .context(format!(
@@ -788,6 +3648,103 @@ impl StateApi {
         }
     }
 
+    /// Retrieve a batch of table items, all at the same ledger version
+    pub fn table_items(
+        &self,
+        accept_type: &AcceptType,
+        table_handle: Address,
+        table_item_requests: Vec<TableItemRequest>,
+        ledger_version: Option<U64>,
+    ) -> BasicResultWith404<Vec<TableItemResult>> {
+        // Reject oversized batches before touching the DB
+        let max_batch_size = self.context.max_table_item_batch_size();
+        if table_item_requests.len() > max_batch_size {
+            return Err(BasicErrorWith404::bad_request_with_code_no_info(
+                format!(
+                    "Requested {} table items, at most {} are allowed",
+                    table_item_requests.len(),
+                    max_batch_size
+                ),
+                AptosErrorCode::InvalidInput,
+            ));
+        }
+
+        // Retrieve local state, shared by all items
+        let (ledger_info, ledger_version, state_view) = self
+            .context
+            .state_view(ledger_version.map(|inner| inner.0))?;
+
+        let converter = state_view.as_converter(
+            self.context.db.clone(),
+            self.context.table_info_reader.clone(),
+        );
+        let items = table_item_requests.into_iter().map(|table_item_request| {
+            batch_table_item(
+                &converter,
+                &state_view,
+                table_handle,
+                table_item_request,
+                ledger_version,
+            )
+        });
+
+        match accept_type {
+            AcceptType::Json => {
+                let results: Vec<_> = items
+                    .map(|item| {
+                        let value = item.and_then(|(value_type, bytes)| {
+                            converter
+                                .try_into_move_value(&value_type, &bytes)
+                                .context("Failed to deserialize table item retrieved from DB")
+                                .map_err(|err| {
+                                    AptosError::new_with_error_code(
+                                        err,
+                                        AptosErrorCode::InternalError,
+                                    )
+                                })
+                        });
+                        match value {
+                            Ok(value) => TableItemResult {
+                                value: Some(value),
+                                error: None,
+                            },
+                            Err(error) => TableItemResult {
+                                value: None,
+                                error: Some(error),
+                            },
+                        }
+                    })
+                    .collect();
+                BasicResponse::try_from_json((results, &ledger_info, BasicResponseStatus::Ok))
+            },
+            AcceptType::Bcs => {
+                // The BCS payload is a `Vec<RawTableItemResult>`, not the declared JSON type
+                let results: Vec<RawTableItemResult> = items
+                    .map(|item| match item {
+                        Ok((_, bytes)) => RawTableItemResult {
+                            value: Some(bytes.to_vec()),
+                            error: None,
+                        },
+                        Err(error) => RawTableItemResult {
+                            value: None,
+                            error: Some(error),
+                        },
+                    })
+                    .collect();
+                let bytes = bcs::to_bytes(&results)
+                    .context("Failed to serialize table items")
+                    .map_err(|err| {
+                        BasicErrorWith404::internal_with_code(
+                            err,
+                            AptosErrorCode::InternalError,
+                            &ledger_info,
+                        )
+                    })?;
+                BasicResponse::try_from_encoded((bytes, &ledger_info, BasicResponseStatus::Ok))
+            },
+        }
+    }
+
     /// Retrieve table item for a specific ledger version
     pub fn raw_table_item(
         &self,
@@ -795,6 +3752,7 @@ impl StateApi {
         table_handle: Address,
         table_item_request: RawTableItemRequest,
         ledger_version: Option<U64>,
+        with_key_hash: bool,
     ) -> BasicResultWith404<MoveValue> {
         // Retrieve local state
         let (ledger_info, ledger_version, state_view) = self
@@ -803,8 +3761,8 @@ impl StateApi {
 
         let state_key =
             StateKey::table_item(&TableHandle(table_handle.into()), &table_item_request.key.0);
-        let bytes = state_view
-            .get_state_value_bytes(&state_key)
+        let state_value = state_view
+            .get_state_value(&state_key)
             .context(format!(
                 "Failed when trying to retrieve table item from the DB with key: {}",
                 table_item_request.key,
@@ -827,17 +3785,34 @@ impl StateApi {
                     &ledger_info,
                 )
             })?;
+        let bytes = if with_key_hash {
+            bcs::to_bytes(&StateValueWithKeyHash {
+                value: state_value,
+                key_hash: state_key.hash(),
+            })
+            .context(format!(
+                "Failed serializing table item. key: {}",
+                table_item_request.key
+            ))
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?
+        } else {
+            state_value.bytes().to_vec()
+        };
 
         match accept_type {
             AcceptType::Json => Err(api_forbidden(
                 "Get raw table item",
                 "Please use get table item instead.",
             )),
-            AcceptType::Bcs => BasicResponse::try_from_encoded((
-                bytes.to_vec(),
-                &ledger_info,
-                BasicResponseStatus::Ok,
-            )),
+            AcceptType::Bcs => {
+                BasicResponse::try_from_encoded((bytes, &ledger_info, BasicResponseStatus::Ok))
+            },
         }
     }
 
@@ -847,13 +3822,14 @@ impl StateApi {
         accept_type: &AcceptType,
         request: RawStateValueRequest,
         ledger_version: Option<U64>,
+        with_key_hash: bool,
     ) -> BasicResultWith404<MoveValue> {
         // Retrieve local state
         let (ledger_info, ledger_version, state_view) = self
             .context
             .state_view(ledger_version.map(|inner| inner.0))?;
 
-        let state_key = bcs::from_bytes(&request.key.0)
+        let state_key: StateKey = bcs::from_bytes(&request.key.0)
             .context(format!(
                 "Failed deserializing state value. key: {}",
                 request.key
@@ -886,18 +3862,21 @@ impl StateApi {
                     &ledger_info,
                 )
             })?;
-        let bytes = bcs::to_bytes(&state_value)
-            .context(format!(
-                "Failed serializing state value. key: {}",
-                request.key
-            ))
-            .map_err(|err| {
-                BasicErrorWith404::internal_with_code(
-                    err,
-                    AptosErrorCode::InternalError,
-                    &ledger_info,
-                )
-            })?;
+        let bytes = if with_key_hash {
+            bcs::to_bytes(&StateValueWithKeyHash {
+                value: state_value,
+                key_hash: state_key.hash(),
+            })
+        } else {
+            bcs::to_bytes(&state_value)
+        }
+        .context(format!(
+            "Failed serializing state value. key: {}",
+            request.key
+        ))
+        .map_err(|err| {
+            BasicErrorWith404::internal_with_code(err, AptosErrorCode::InternalError, &ledger_info)
+        })?;
 
         match accept_type {
             AcceptType::Json => Err(api_forbidden(
@@ -910,3 +3889,224 @@ impl StateApi {
         }
     }
 }
+
+/// Query options shared by the account proof endpoints
+struct AccountProofOptions {
+    block_height: Option<u64>,
+    ledger_version: Option<u64>,
+    transaction_hash: Option<HashValue>,
+    include_state_key: bool,
+    value_chunk: Option<u64>,
+    compress_state_proof: bool,
+    trim_ledger_info: bool,
+    include_transaction_proof: bool,
+    expected_ledger_info_hash: Option<HashValue>,
+    resource_type: Option<MoveStructTag>,
+    chain_id: Option<u8>,
+}
+
+/// Trims the ledger info of `proof`. The trimmed proof rebuilds it from the transaction proof, so
+/// failing to is a server error once the options were checked to keep it.
+fn trim_proof(
+    proof: &StateProofPayload,
+    ledger_info: &LedgerInfo,
+) -> Result<TrimmedAccountProofPayload, BasicErrorWith404> {
+    proof.trim().ok_or_else(|| {
+        BasicErrorWith404::internal_with_code(
+            "Can't trim the ledger info of an account proof without its transaction proof",
+            AptosErrorCode::InternalError,
+            ledger_info,
+        )
+    })
+}
+
+/// Renders account proofs as JSON, to be inspected by hand.
+fn proof_views(
+    proofs: Vec<StateProofPayload>,
+    ledger_info: &LedgerInfo,
+) -> Result<Vec<StateProofView>, BasicErrorWith404> {
+    proofs
+        .into_iter()
+        .map(StateProofView::try_from)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| {
+            BasicErrorWith404::internal_with_code(err, AptosErrorCode::InternalError, ledger_info)
+        })
+}
+
+/// Deserializes the `bytes` of a resource found under `tag`. Failing to is still a server error,
+/// but with its own code, as it usually means the layout of the type changed rather than a fault
+/// of the node.
+pub(crate) fn try_into_resource<S: StateView>(
+    converter: &MoveConverter<S>,
+    tag: &StructTag,
+    bytes: &[u8],
+    ledger_info: &LedgerInfo,
+) -> Result<MoveResource, BasicErrorWith404> {
+    converter
+        .try_into_resource(tag, bytes)
+        .context("Failed to deserialize resource data retrieved from DB")
+        .map_err(|err| {
+            BasicErrorWith404::internal_with_code(
+                err,
+                AptosErrorCode::ResourceDeserializationError,
+                ledger_info,
+            )
+        })
+}
+
+/// Latest state checkpoint at or before `ledger_version`, provable by its ledger info. The latest
+/// checkpoint may have been committed after the ledger info was read, the latest snapshot before
+/// the ledger info is taken instead then.
+pub(crate) fn latest_checkpoint_at(
+    db: &dyn DbReader,
+    ledger_version: Version,
+) -> aptos_storage_interface::Result<Option<Version>> {
+    match db.get_latest_state_checkpoint_version()? {
+        Some(version) if version <= ledger_version => Ok(Some(version)),
+        _ => Ok(db
+            .get_state_snapshot_before(ledger_version + 1)?
+            .map(|(version, _)| version)),
+    }
+}
+
+/// Computes the state key of a table item, converting its key to the lookup version for DB
+fn table_item_key<S: StateView>(
+    converter: &MoveConverter<S>,
+    table_handle: Address,
+    key_type: &TypeTag,
+    key: serde_json::Value,
+) -> anyhow::Result<StateKey> {
+    let vm_key = converter.try_into_vm_value(key_type, key)?;
+    let raw_key = vm_key
+        .undecorate()
+        .simple_serialize()
+        .ok_or_else(|| format_err!("Failed to serialize table key"))?;
+    Ok(StateKey::table_item(
+        &TableHandle(table_handle.into()),
+        &raw_key,
+    ))
+}
+
+/// Reads one item of a batch table item request, returning its value type and BCS encoded value,
+/// or the error specific to the item.
+fn batch_table_item(
+    converter: &MoveConverter<DbStateView>,
+    state_view: &DbStateView,
+    table_handle: Address,
+    table_item_request: TableItemRequest,
+    ledger_version: u64,
+) -> Result<(TypeTag, bytes::Bytes), AptosError> {
+    let invalid_input = |err| AptosError::new_with_error_code(err, AptosErrorCode::InvalidInput);
+    table_item_request
+        .verify()
+        .context("'table_item_request' invalid")
+        .map_err(invalid_input)?;
+    let key_type = table_item_request
+        .key_type
+        .try_into()
+        .context("Failed to parse key_type")
+        .map_err(invalid_input)?;
+    let value_type = table_item_request
+        .value_type
+        .try_into()
+        .context("Failed to parse value_type")
+        .map_err(invalid_input)?;
+    let key = table_item_request.key;
+    let state_key =
+        table_item_key(converter, table_handle, &key_type, key.clone()).map_err(invalid_input)?;
+
+    let bytes = state_view
+        .get_state_value_bytes(&state_key)
+        .context(format!(
+            "Failed when trying to retrieve table item from the DB with key: {}",
+            key
+        ))
+        .map_err(|err| AptosError::new_with_error_code(err, AptosErrorCode::InternalError))?
+        .ok_or_else(|| {
+            AptosError::new_with_error_code(
+                format!(
+                    "Table Item not found by Table handle({}), Table key({}) and Ledger version({})",
+                    table_handle, key, ledger_version
+                ),
+                AptosErrorCode::TableItemNotFound,
+            )
+        })?;
+    Ok((value_type, bytes))
+}
+
+/// Checks that the node serves the chain the client expects, if it told which.
+fn check_chain_id(chain_id: Option<u8>, ledger_info: &LedgerInfo) -> Result<(), BasicErrorWith404> {
+    match chain_id {
+        Some(chain_id) if chain_id != ledger_info.chain_id => {
+            Err(BasicErrorWith404::bad_request_with_code(
+                format!(
+                    "Expected chain ID {}, but the node serves chain ID {}",
+                    chain_id, ledger_info.chain_id
+                ),
+                AptosErrorCode::InvalidInput,
+                ledger_info,
+            ))
+        },
+        _ => Ok(()),
+    }
+}
+
+/// Checks that `epoch_state` holds the validators that signed `ledger_info`. The genesis ledger
+/// info is the exception, as it isn't signed it comes with the validators of epoch 1.
+fn check_signing_epoch_state(
+    ledger_info: &aptos_types::ledger_info::LedgerInfo,
+    epoch_state: &aptos_types::epoch_state::EpochState,
+) -> anyhow::Result<()> {
+    let expected_epoch = if ledger_info.epoch() == 0 {
+        1
+    } else {
+        ledger_info.epoch()
+    };
+    anyhow::ensure!(
+        epoch_state.epoch == expected_epoch,
+        "Validator set of epoch {} attached to a LedgerInfo of epoch {}",
+        epoch_state.epoch,
+        ledger_info.epoch()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::check_signing_epoch_state;
+    use aptos_crypto::HashValue;
+    use aptos_types::{
+        block_info::BlockInfo, epoch_state::EpochState, ledger_info::LedgerInfo,
+        validator_verifier::ValidatorVerifier,
+    };
+
+    fn ledger_info(epoch: u64, next_epoch_state: Option<EpochState>) -> LedgerInfo {
+        LedgerInfo::new(
+            BlockInfo::new(
+                epoch,
+                0,
+                HashValue::zero(),
+                HashValue::zero(),
+                0,
+                0,
+                next_epoch_state,
+            ),
+            HashValue::zero(),
+        )
+    }
+
+    #[test]
+    fn test_check_signing_epoch_state() {
+        let epoch_state = |epoch| EpochState::new(epoch, ValidatorVerifier::new(vec![]));
+
+        assert!(check_signing_epoch_state(&ledger_info(2, None), &epoch_state(2)).is_ok());
+        assert!(
+            check_signing_epoch_state(&ledger_info(0, Some(epoch_state(1))), &epoch_state(1))
+                .is_ok()
+        );
+
+        // A stale validator set, left over from the previous epoch
+        assert!(check_signing_epoch_state(&ledger_info(2, None), &epoch_state(1)).is_err());
+    }
+}
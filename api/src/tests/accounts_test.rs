@@ -4,8 +4,11 @@
 
 use super::new_test_context;
 use aptos_api_test_context::{current_function_name, find_value};
-use aptos_api_types::{MoveModuleBytecode, MoveResource, MoveStructTag, StateKeyWrapper};
+use aptos_api_types::{
+    MoveModuleBytecode, MoveResource, MoveStructTag, RawAccountResourcesAtVersion, StateKeyWrapper,
+};
 use aptos_cached_packages::aptos_stdlib;
+use aptos_types::account_config::AccountResource;
 use serde_json::json;
 use std::str::FromStr;
 
@@ -125,6 +128,60 @@ async fn test_get_account_resources_by_ledger_version() {
     assert_eq!(root_account["data"]["sequence_number"], "0");
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_resources_by_type() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account).await;
+    context.commit_block(&vec![txn.clone()]).await;
+    let root = context.root_account().await.address().to_hex_literal();
+    let resource_types = json!([
+        "0x1::account::Account",
+        "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>"
+    ]);
+
+    let resp = context
+        .post(&account_resources(&root), resource_types.clone())
+        .await;
+    let version = context.get_latest_ledger_info().version();
+    assert_eq!(resp["version"], version.to_string());
+    assert_eq!(resp["resources"][0]["type"], "0x1::account::Account");
+    assert_eq!(resp["resources"][0]["data"]["sequence_number"], "1");
+    assert_eq!(
+        resp["resources"][1]["type"],
+        "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>"
+    );
+
+    let resp = context
+        .post(
+            &account_resources_with_ledger_version(&root, 0),
+            resource_types.clone(),
+        )
+        .await;
+    assert_eq!(resp["version"], "0");
+    assert_eq!(resp["resources"][0]["data"]["sequence_number"], "0");
+
+    let req = warp::test::request()
+        .method("POST")
+        .header("Accept", "application/x-bcs")
+        .path(&format!("/v1{}", account_resources(&root)))
+        .json(&resource_types);
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 200);
+    let snapshot: RawAccountResourcesAtVersion = bcs::from_bytes(resp.body()).unwrap();
+    assert_eq!(snapshot.version, version);
+    assert_eq!(snapshot.resources.len(), 2);
+    let account_resource: AccountResource = bcs::from_bytes(&snapshot.resources[0]).unwrap();
+    assert_eq!(account_resource.sequence_number(), 1);
+
+    // One missing resource fails the whole snapshot
+    let resp = context
+        .expect_status_code(404)
+        .post(&account_resources("0x0"), resource_types)
+        .await;
+    assert_eq!(resp["error_code"], "resource_not_found");
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_get_account_resources_by_too_large_ledger_version() {
     let mut context = new_test_context(current_function_name!());
@@ -2,8 +2,9 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use super::new_test_context;
+use super::{new_test_context, new_test_context_with_config, new_test_context_with_indexer};
 use aptos_api_test_context::current_function_name;
+use aptos_config::config::NodeConfig;
 use serde_json::json;
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
@@ -13,6 +14,27 @@ async fn test_get_index() {
     context.check_golden_output(resp);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_ledger_info_summary() {
+    let context = new_test_context(current_function_name!());
+    let resp = context.get("/ledger_info").await;
+
+    let ledger_info = context.get_latest_ledger_info();
+    assert_eq!(resp, serde_json::to_value(&ledger_info).unwrap());
+    for field in [
+        "chain_id",
+        "epoch",
+        "ledger_version",
+        "oldest_ledger_version",
+        "block_height",
+        "oldest_block_height",
+        "ledger_timestamp",
+    ] {
+        assert!(!resp[field].is_null(), "missing {}", field);
+    }
+    assert_eq!(resp.as_object().unwrap().len(), 7);
+}
+
 // TODO: Un-ignore this pending https://github.com/poem-web/poem/issues/343.
 #[ignore]
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
@@ -43,6 +65,77 @@ async fn test_health_check() {
     assert_eq!(resp.status(), 200)
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_health_check_verbose() {
+    let mut context = new_test_context(current_function_name!());
+    context.create_account().await;
+    let version = context.get_latest_ledger_info().version();
+
+    let resp = context.get("/-/healthy").await;
+    assert_eq!(resp, json!({"message": "aptos-node:ok"}));
+
+    let resp = context.get("/-/healthy?verbose=true").await;
+    assert_eq!(
+        resp,
+        json!({
+            "message": "aptos-node:ok",
+            "oldest_ledger_version": "0",
+            "first_viable_version": "0",
+            "oldest_block_height": "0",
+            "latest_state_checkpoint": version.to_string(),
+        })
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_ledger_summary() {
+    let mut context = new_test_context(current_function_name!());
+    context.create_account().await;
+    let ledger_info = context.get_latest_ledger_info();
+    let version = ledger_info.version().to_string();
+
+    let resp = context.get("/ledger/summary").await;
+    assert_eq!(resp["ledger_version"], json!(version));
+    assert_eq!(resp["epoch"], json!(ledger_info.epoch.to_string()));
+    assert_eq!(resp["oldest_ledger_version"], json!("0"));
+    assert_eq!(resp["first_viable_version"], json!("0"));
+    assert_eq!(resp["latest_state_checkpoint"], json!(version));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_proof_status() {
+    let mut context = new_test_context_with_indexer(current_function_name!());
+    context.create_account().await;
+
+    let resp = context.get("/-/proof_status").await;
+    assert_eq!(resp, json!({"available": true, "reasons": []}));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_proof_status_degraded() {
+    let mut node_config = NodeConfig::default();
+    node_config.api.bcs_output_enabled = false;
+    let context = new_test_context_with_config(current_function_name!(), node_config);
+
+    let resp = context.expect_status_code(503).get("/-/proof_status").await;
+    assert_eq!(
+        resp,
+        json!({"available": false, "reasons": ["bcs_output_disabled", "table_info_reader_missing"]})
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_proof_status_table_info_reader_missing() {
+    // The context has neither a table info reader nor the DB indexer
+    let context = new_test_context(current_function_name!());
+
+    let resp = context.expect_status_code(503).get("/-/proof_status").await;
+    assert_eq!(
+        resp,
+        json!({"available": false, "reasons": ["table_info_reader_missing"]})
+    );
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_openapi_spec() {
     let context = new_test_context(current_function_name!());
@@ -31,3 +31,7 @@ fn new_test_context(test_name: String) -> TestContext {
 fn new_test_context_with_config(test_name: String, node_config: NodeConfig) -> TestContext {
     super_new_test_context(test_name, node_config, false)
 }
+
+fn new_test_context_with_indexer(test_name: String) -> TestContext {
+    super_new_test_context(test_name, NodeConfig::default(), true)
+}
@@ -1,14 +1,43 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use super::new_test_context;
+use super::{new_test_context, new_test_context_with_config};
+use crate::{
+    response::BasicErrorWith404,
+    state::{latest_checkpoint_at, try_into_resource},
+};
 use aptos_api_test_context::{current_function_name, TestContext};
+use aptos_api_types::{
+    epoch_change::EpochChangeSummaryPage,
+    ledger_info::EpochState as EpochStateView,
+    proof_payload::{
+        AccountResourcesProofPayload, AccountStateDeltaPayload, BootstrapBundle,
+        EpochChangeProofPage, EpochChangeProofPayload, EventProofPayload, ProofCheckReport,
+        ResourceWithProof, StateProofPayload, StateProofView, TransactionProofPayload,
+        TrimmedAccountProofPayload,
+    },
+    proof_schema::PROOF_SCHEMA_VERSION,
+    waypoint::Waypoint as WaypointView,
+    AptosErrorCode, AsConverter, RawTableItemResult, StateValueWithKeyHash,
+};
+use aptos_config::config::NodeConfig;
+use aptos_crypto::{hash::CryptoHash, HashValue};
 use aptos_sdk::{transaction_builder::aptos_stdlib::aptos_token_stdlib, types::LocalAccount};
 use aptos_storage_interface::DbReader;
-use move_core_types::account_address::AccountAddress;
+use aptos_types::{
+    account_config::{AccountResource, ChainIdResource},
+    epoch_change::EpochChangeProof,
+    event::EventKey,
+    state_store::{state_key::StateKey, state_value::StateValue, table::TableHandle, TStateView},
+    transaction::Version,
+    trusted_state::TrustedState,
+    waypoint::Waypoint,
+};
+use flate2::read::GzDecoder;
+use move_core_types::{account_address::AccountAddress, ident_str, move_resource::MoveStructType};
 use serde::Serialize;
 use serde_json::{json, Value};
-use std::path::PathBuf;
+use std::{io::Read, path::PathBuf};
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_get_account_resource() {
@@ -22,12 +51,1892 @@ async fn test_get_account_resource() {
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_get_account_proof() {
     let mut context = new_test_context(current_function_name!());
+    let resp = context.get(&get_account_proof("0xA550C18")).await;
+    let views: Vec<StateProofView> = serde_json::from_value(resp).unwrap();
+    assert_eq!(views.len(), 1);
+    let view = &views[0];
+
+    // The account resource is proven at the latest version by default
+    let state_key = StateKey::resource(
+        &AccountAddress::from_hex_literal("0xA550C18").unwrap(),
+        &AccountResource::struct_tag(),
+    )
+    .unwrap();
+    let version = context.get_latest_ledger_info().version();
+    let state_value = context
+        .db
+        .get_state_value_by_version(&state_key, version)
+        .unwrap()
+        .unwrap();
+    assert_eq!(view.transaction_index.0, version);
+    assert_eq!(view.element_key, state_key.hash().into());
+    assert_eq!(view.element_hash, state_value.hash().into());
+    assert!(view.transaction_proof.is_some());
+    assert!(view.transaction.is_some());
+    assert!(view.state_key.is_none());
+    assert!(view.value_chunk.is_none());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_proof_with_auth_token() {
+    let mut node_config = NodeConfig::default();
+    node_config.api.proof_auth_token = Some("secret".to_string());
+    let mut context = new_test_context_with_config(current_function_name!(), node_config);
+    context.create_account().await;
+    let version = context.get_latest_ledger_info().version();
+
+    let request = |path: &str, authorization: Option<&str>| {
+        let req = warp::test::request()
+            .method("GET")
+            .header("Accept", "application/x-bcs")
+            .path(&format!("/v1{}", path));
+        match authorization {
+            Some(authorization) => req.header("Authorization", authorization),
+            None => req,
+        }
+    };
+
+    // Proofs served outside of a `proof` path segment are protected too
+    for path in [
+        get_account_proof("0xA550C18"),
+        "/bootstrap".to_string(),
+        format!(
+            "{}?ledger_version={}&with_proof=true",
+            get_account_resource("0x1", "0x1::chain_id::ChainId"),
+            version
+        ),
+    ] {
+        let resp = context.reply(request(&path, None)).await;
+        assert_eq!(resp.status(), 401);
+        let resp = context.reply(request(&path, Some("Bearer wrong"))).await;
+        assert_eq!(resp.status(), 401);
+        let resp = context.reply(request(&path, Some("Bearer secre"))).await;
+        assert_eq!(resp.status(), 401);
+        let resp = context.reply(request(&path, Some("Bearer secret"))).await;
+        assert_eq!(resp.status(), 200);
+    }
+
+    // So are the epoch states and waypoints clients anchor their proofs to
+    for path in [
+        "/epoch/state",
+        "/epoch/waypoint",
+        "/epoch/progress",
+        "/proof/schema",
+    ] {
+        let req = warp::test::request()
+            .method("GET")
+            .path(&format!("/v1{}", path));
+        assert_eq!(context.reply(req).await.status(), 401);
+        let req = warp::test::request()
+            .method("GET")
+            .header("Authorization", "Bearer secret")
+            .path(&format!("/v1{}", path));
+        assert_eq!(context.reply(req).await.status(), 200);
+    }
+
+    // Everything that isn't a proof stays open
+    let healthy = warp::test::request().method("GET").path("/v1/-/healthy");
+    assert_eq!(context.reply(healthy).await.status(), 200);
+    let resource = warp::test::request().method("GET").path(&format!(
+        "/v1{}",
+        get_account_resource("0xA550C18", "0x1::account::Account")
+    ));
+    assert_eq!(context.reply(resource).await.status(), 200);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_proof_with_gzip() {
+    let mut context = new_test_context(current_function_name!());
+    context.create_account().await;
+    let version = context.get_latest_ledger_info().version();
+    let request = |path: &str, accept_encoding: Option<&str>| {
+        let req = warp::test::request()
+            .method("GET")
+            .header("Accept", "application/x-bcs")
+            .path(&format!("/v1{}", path));
+        match accept_encoding {
+            Some(accept_encoding) => req.header("Accept-Encoding", accept_encoding),
+            None => req,
+        }
+    };
+
+    for path in [
+        get_account_proof("0x1"),
+        "/epoch/proof".to_string(),
+        "/bootstrap".to_string(),
+        format!(
+            "{}?ledger_version={}&with_proof=true",
+            get_account_resource("0x1", "0x1::chain_id::ChainId"),
+            version
+        ),
+    ] {
+        let plain = context.reply(request(&path, None)).await;
+        assert_eq!(plain.status(), 200);
+        assert!(plain.headers().get("Content-Encoding").is_none());
+        assert_eq!(plain.headers()["Vary"], "Accept-Encoding");
+
+        let resp = context.reply(request(&path, Some("gzip;q=0"))).await;
+        assert!(resp.headers().get("Content-Encoding").is_none());
+        assert_eq!(resp.body(), plain.body());
+
+        let resp = context.reply(request(&path, Some("br, gzip"))).await;
+        assert_eq!(resp.status(), 200);
+        assert_eq!(resp.headers()["Content-Encoding"], "gzip");
+        let mut body = vec![];
+        GzDecoder::new(resp.body().as_ref())
+            .read_to_end(&mut body)
+            .unwrap();
+        assert_eq!(body, plain.body().as_ref());
+    }
+
+    // Only the BCS proofs are compressed
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("GET")
+                .header("Accept-Encoding", "gzip")
+                .path(&format!(
+                    "/v1{}",
+                    get_account_resource("0xA550C18", "0x1::account::Account")
+                )),
+        )
+        .await;
+    assert_eq!(resp.status(), 200);
+    assert!(resp.headers().get("Content-Encoding").is_none());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_proof_without_transaction_proof() {
+    let context = new_test_context(current_function_name!());
+    let proof_with = |query: &str| {
+        warp::test::request()
+            .method("GET")
+            .header("Accept", "application/x-bcs")
+            .path(&format!("/v1{}?{}", get_account_proof("0x1"), query))
+    };
+
+    let resp = context.reply(proof_with("")).await;
+    assert_eq!(resp.status(), 200);
+    let full: StateProofPayload = bcs::from_bytes(resp.body()).unwrap();
+    let state_root = full
+        .transaction
+        .as_ref()
+        .unwrap()
+        .state_checkpoint_hash()
+        .unwrap();
+
+    let resp = context
+        .reply(proof_with("include_transaction_proof=false"))
+        .await;
+    assert_eq!(resp.status(), 200);
+    let state_only: StateProofPayload = bcs::from_bytes(resp.body()).unwrap();
+    assert_eq!(state_only.transaction_proof, None);
+    assert_eq!(state_only.transaction, None);
+    assert_eq!(state_only.element_hash, full.element_hash);
+    assert!(resp.body().len() < bcs::to_bytes(&full).unwrap().len());
+    state_only.verify_with_state_root(state_root).unwrap();
+
+    let resp = context
+        .reply(proof_with(
+            "include_transaction_proof=false&trim_ledger_info=true",
+        ))
+        .await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_proof_of_resource_type() {
+    let context = new_test_context(current_function_name!());
+    let proof_of = |address: &str, resource_type: &str| {
+        warp::test::request()
+            .method("GET")
+            .header("Accept", "application/x-bcs")
+            .path(&format!(
+                "/v1{}?resource_type={}",
+                get_account_proof(address),
+                resource_type
+            ))
+    };
+
+    let resp = context
+        .reply(proof_of("0x1", "0x1::chain_id::ChainId"))
+        .await;
+    assert_eq!(resp.status(), 200);
+    let payload: StateProofPayload = bcs::from_bytes(resp.body()).unwrap();
+    let state_key =
+        StateKey::resource(&AccountAddress::ONE, &ChainIdResource::struct_tag()).unwrap();
+    assert_eq!(payload.element_key, state_key.hash());
+    payload
+        .full_state_proof()
+        .unwrap()
+        .verify_by_hash(
+            payload
+                .transaction
+                .as_ref()
+                .unwrap()
+                .state_checkpoint_hash()
+                .unwrap(),
+            payload.element_key,
+            Some(payload.element_hash),
+        )
+        .unwrap();
+
+    let resp = context
+        .reply(proof_of("0xA550C18", "0x1::chain_id::ChainId"))
+        .await;
+    assert_eq!(resp.status(), 404);
+    let resp = context.reply(proof_of("0x1", "0x1::chain_id")).await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_batch_account_proof() {
+    let mut context = new_test_context(current_function_name!());
+    context.create_account().await;
+    let version = context.get_latest_ledger_info().version();
+    let prove = |requests: Value| {
+        warp::test::request()
+            .method("POST")
+            .header("Accept", "application/x-bcs")
+            .path("/v1/accounts/proof/batch")
+            .json(&requests)
+    };
+
+    let resp = context
+        .reply(prove(json!([
+            {"address": "0x1"},
+            {"address": "0xA550C18"},
+            {"address": "0x1", "resource_type": "0x1::chain_id::ChainId"},
+        ])))
+        .await;
+    assert_eq!(resp.status(), 200);
+    let proofs: Vec<StateProofPayload> = bcs::from_bytes(resp.body()).unwrap();
+
+    let state_keys = [
+        StateKey::resource(&AccountAddress::ONE, &AccountResource::struct_tag()).unwrap(),
+        StateKey::resource(
+            &AccountAddress::from_hex_literal("0xA550C18").unwrap(),
+            &AccountResource::struct_tag(),
+        )
+        .unwrap(),
+        StateKey::resource(&AccountAddress::ONE, &ChainIdResource::struct_tag()).unwrap(),
+    ];
+    assert_eq!(proofs.len(), state_keys.len());
+    for (proof, state_key) in proofs.iter().zip(&state_keys) {
+        assert_eq!(proof.element_key, state_key.hash());
+        assert_eq!(proof.transaction_index, version);
+        assert_eq!(proof.ledger_info_v0, proofs[0].ledger_info_v0);
+        assert_eq!(proof.validator_verifier, proofs[0].validator_verifier);
+        proof
+            .full_state_proof()
+            .unwrap()
+            .verify_by_hash(
+                proof
+                    .transaction
+                    .as_ref()
+                    .unwrap()
+                    .state_checkpoint_hash()
+                    .unwrap(),
+                proof.element_key,
+                Some(proof.element_hash),
+            )
+            .unwrap();
+    }
+
+    let too_many = vec![json!({"address": "0x1"}); 101];
+    let resp = context.reply(prove(json!(too_many))).await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_proof_at_reconfiguration() {
+    // Nothing is committed after genesis, so the latest version is the last one of epoch 0
+    let context = new_test_context(current_function_name!());
+    let genesis_li = context
+        .db
+        .get_epoch_ending_ledger_infos(0, 1)
+        .unwrap()
+        .ledger_info_with_sigs
+        .remove(0);
+    assert_eq!(context.get_latest_ledger_info().version(), 0);
+
+    let req = warp::test::request()
+        .method("GET")
+        .header("Accept", "application/x-bcs")
+        .path(&format!("/v1{}", get_account_proof("0x1")));
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 200);
+    let payload: StateProofPayload = bcs::from_bytes(resp.body()).unwrap();
+    assert!(payload.ledger_info_v0.ledger_info().ends_epoch());
+
+    let waypoint = Waypoint::new_epoch_boundary(genesis_li.ledger_info()).unwrap();
+    let (version, _) = payload.verify_with_waypoint(&waypoint).unwrap();
+    assert_eq!(version, 0);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_proof_at_ledger_version() {
+    let mut context = new_test_context(current_function_name!());
+    context.create_account().await;
+    let ledger_version = context.get_latest_ledger_info().version();
+    let proof_with = |query: String| {
+        warp::test::request()
+            .method("GET")
+            .header("Accept", "application/x-bcs")
+            .path(&format!("/v1{}?{}", get_account_proof("0x1"), query))
+    };
+
+    let resp = context
+        .reply(proof_with(format!("ledger_version={}", ledger_version)))
+        .await;
+    assert_eq!(resp.status(), 200);
+    let payload: StateProofPayload = bcs::from_bytes(resp.body()).unwrap();
+    assert_eq!(payload.transaction_index, ledger_version);
+
+    // Not the last version of a block
+    let resp = context
+        .reply(proof_with(format!("ledger_version={}", ledger_version - 1)))
+        .await;
+    assert_eq!(resp.status(), 400);
+
+    let resp = context
+        .reply(proof_with(format!("ledger_version={}", ledger_version + 1)))
+        .await;
+    assert_eq!(resp.status(), 404);
+
+    let resp = context
+        .reply(proof_with(format!(
+            "ledger_version={}&block_height=1",
+            ledger_version
+        )))
+        .await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_proof_at_pruned_version() {
+    let mut context = new_test_context(current_function_name!());
+    context.create_account().await;
+    let ledger_version = context.get_latest_ledger_info().version();
+    context
+        .db
+        .set_ledger_min_readable_version_for_test(ledger_version)
+        .unwrap();
+
+    let req = warp::test::request()
+        .method("GET")
+        .header("Accept", "application/x-bcs")
+        .path(&format!("/v1{}?ledger_version=0", get_account_proof("0x1")));
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 410);
+
+    let req = warp::test::request()
+        .method("POST")
+        .header("Accept", "application/x-bcs")
+        .path(&format!(
+            "/v1{}/proof",
+            get_account_resource("0x1", "0x1::account::Account")
+        ))
+        .json(&vec!["0".to_string()]);
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 410);
+
+    // The latest version is still in the window
+    let req = warp::test::request()
+        .method("GET")
+        .header("Accept", "application/x-bcs")
+        .path(&format!("/v1{}", get_account_proof("0x1")));
+    assert_eq!(context.reply(req).await.status(), 200);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_proof_json() {
+    let mut context = new_test_context(current_function_name!());
+    let req = warp::test::request()
+        .method("GET")
+        .header("Accept", "application/x-bcs")
+        .path(&format!("/v1{}", get_account_proof("0x1")));
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 200);
+    let payload: StateProofPayload = bcs::from_bytes(resp.body()).unwrap();
+
+    let resp = context.get(&get_account_proof("0x1")).await;
+    let views: Vec<StateProofView> = serde_json::from_value(resp).unwrap();
+    assert_eq!(views, vec![StateProofView::try_from(payload).unwrap()]);
+
+    let resp = context
+        .expect_status_code(400)
+        .get(&format!(
+            "{}?compress_state_proof=true",
+            get_account_proof("0x1")
+        ))
+        .await;
+    assert_eq!(resp["error_code"], "invalid_input");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_verify_account_proof() {
+    let mut context = new_test_context(current_function_name!());
+    context.create_account().await;
+    let resp = context
+        .get(&format!("{}/verify", get_account_proof("0x1")))
+        .await;
+    let report: ProofCheckReport = serde_json::from_value(resp).unwrap();
+    assert!(report.state_proof.passed);
+    assert!(report.transaction_proof.passed);
+    // Blocks committed by the test context aren't signed
+    assert!(!report.signatures.passed);
+    assert!(report.signatures.error.is_some());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_proof_across_epoch_boundary() {
+    // Genesis is the only block of epoch 0, the blocks committed afterwards are in epoch 1
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account).await;
+    context.commit_block(&vec![txn]).await;
+
+    let proof_at = |block_height: u64| {
+        warp::test::request()
+            .method("GET")
+            .header("Accept", "application/x-bcs")
+            .path(&format!(
+                "/v1{}?block_height={}",
+                get_account_proof("0x1"),
+                block_height
+            ))
+    };
+
+    // The genesis block is proven against the ledger info ending epoch 0
+    let resp = context.reply(proof_at(0)).await;
+    assert_eq!(resp.status(), 200);
+    let payload: StateProofPayload = bcs::from_bytes(resp.body()).unwrap();
+    assert_eq!(payload.ledger_info_v0.ledger_info().epoch(), 0);
+    let genesis_li = context
+        .db
+        .get_epoch_ending_ledger_infos(0, 1)
+        .unwrap()
+        .ledger_info_with_sigs
+        .remove(0);
+    let waypoint = Waypoint::new_epoch_boundary(genesis_li.ledger_info()).unwrap();
+    let (version, _) = payload.verify_with_waypoint(&waypoint).unwrap();
+    assert_eq!(version, 0);
+
+    // The next block is proven against the latest ledger info, with the validators of epoch 1
+    let resp = context.reply(proof_at(1)).await;
+    assert_eq!(resp.status(), 200);
+    let payload: StateProofPayload = bcs::from_bytes(resp.body()).unwrap();
+    assert_eq!(payload.ledger_info_v0.ledger_info().epoch(), 1);
+    assert_eq!(
+        payload.validator_verifier,
+        context.db.get_latest_epoch_state().unwrap().verifier
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_resource_proofs() {
+    // Every account created commits a block, bumping the sequence number of the root account
+    let mut context = new_test_context(current_function_name!());
+    let mut versions = vec![];
+    for _ in 0..3 {
+        context.create_account().await;
+        versions.push(context.get_latest_ledger_info().version());
+    }
+
+    let req = warp::test::request()
+        .method("POST")
+        .header("Accept", "application/x-bcs")
+        .path(&format!(
+            "/v1{}/proof",
+            get_account_resource("0xA550C18", "0x1::account::Account")
+        ))
+        .json(&versions.iter().map(u64::to_string).collect::<Vec<_>>());
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 200);
+    let proofs: Vec<StateProofPayload> = bcs::from_bytes(resp.body()).unwrap();
+
+    assert_eq!(proofs.len(), 3);
+    let latest_epoch_state = context.db.get_latest_epoch_state().unwrap();
+    for (proof, version) in proofs.iter().zip(&versions) {
+        assert_eq!(proof.transaction_index, *version);
+        assert_eq!(proof.validator_verifier, latest_epoch_state.verifier);
+        proof
+            .full_state_proof()
+            .unwrap()
+            .verify_by_hash(
+                proof
+                    .transaction
+                    .as_ref()
+                    .unwrap()
+                    .state_checkpoint_hash()
+                    .unwrap(),
+                proof.element_key,
+                Some(proof.element_hash),
+            )
+            .unwrap();
+    }
+    assert_ne!(proofs[0].element_hash, proofs[1].element_hash);
+    assert_ne!(proofs[1].element_hash, proofs[2].element_hash);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_resource_proofs_at_reserved_address() {
+    let mut context = new_test_context(current_function_name!());
+    context.create_account().await;
+    let version = context.get_latest_ledger_info().version();
+    let prove = |address: &str| {
+        warp::test::request()
+            .method("POST")
+            .header("Accept", "application/x-bcs")
+            .path(&format!(
+                "/v1{}/proof",
+                get_account_resource(address, "0x1::chain_id::ChainId")
+            ))
+            .json(&[version.to_string()])
+    };
+
+    let resp = context.reply(prove("0x1")).await;
+    assert_eq!(resp.status(), 200);
+    let proofs: Vec<StateProofPayload> = bcs::from_bytes(resp.body()).unwrap();
+    let proof = &proofs[0];
+
+    // The short address is zero-padded to derive the key of the on-chain config
+    let state_key =
+        StateKey::resource(&AccountAddress::ONE, &ChainIdResource::struct_tag()).unwrap();
+    assert_eq!(proof.element_key, state_key.hash());
+    let state_value = context
+        .db
+        .get_state_value_by_version(&state_key, version)
+        .unwrap()
+        .unwrap();
+    assert_eq!(proof.element_hash, state_value.hash());
+    proof
+        .full_state_proof()
+        .unwrap()
+        .verify_by_hash(
+            proof
+                .transaction
+                .as_ref()
+                .unwrap()
+                .state_checkpoint_hash()
+                .unwrap(),
+            proof.element_key,
+            Some(proof.element_hash),
+        )
+        .unwrap();
+
+    let padded = format!("0x{}", "0".repeat(63) + "1");
+    let resp = context.reply(prove(&padded)).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(
+        bcs::from_bytes::<Vec<StateProofPayload>>(resp.body()).unwrap(),
+        proofs
+    );
+
+    // Nothing is stored at the zero address
+    let resp = context.reply(prove("0x0")).await;
+    assert_eq!(resp.status(), 404);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_resource_with_proof() {
+    let mut context = new_test_context(current_function_name!());
+    context.create_account().await;
+    let version = context.get_latest_ledger_info().version();
+    let get = |struct_tag: &str| {
+        warp::test::request()
+            .method("GET")
+            .header("Accept", "application/x-bcs")
+            .path(&format!(
+                "/v1{}?ledger_version={}&with_proof=true",
+                get_account_resource("0x1", struct_tag),
+                version
+            ))
+    };
+
+    let resp = context.reply(get("0x1::chain_id::ChainId")).await;
+    assert_eq!(resp.status(), 200);
+    let resource: ResourceWithProof = bcs::from_bytes(resp.body()).unwrap();
+    assert_eq!(resource.version, version);
+
+    // The resource bytes are the same as without the proof
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("GET")
+                .header("Accept", "application/x-bcs")
+                .path(&format!(
+                    "/v1{}",
+                    get_account_resource_with_version("0x1", "0x1::chain_id::ChainId", version)
+                )),
+        )
+        .await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resource.resource_bytes(), resp.body().as_ref());
+
+    // Verified against the state root proven by the account proof at the same version
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("GET")
+                .header("Accept", "application/x-bcs")
+                .path(&format!(
+                    "/v1{}?ledger_version={}",
+                    get_account_proof("0x1"),
+                    version
+                )),
+        )
+        .await;
+    assert_eq!(resp.status(), 200);
+    let payload: StateProofPayload = bcs::from_bytes(resp.body()).unwrap();
+    let state_root = payload
+        .transaction
+        .as_ref()
+        .unwrap()
+        .state_checkpoint_hash()
+        .unwrap();
+    let state_key =
+        StateKey::resource(&AccountAddress::ONE, &ChainIdResource::struct_tag()).unwrap();
+    resource.verify(&state_key, state_root).unwrap();
+
+    let resp = context.reply(get("0x1::missing::Missing")).await;
+    assert_eq!(resp.status(), 404);
+
+    context
+        .expect_status_code(403)
+        .get(&format!(
+            "{}?with_proof=true",
+            get_account_resource("0x1", "0x1::chain_id::ChainId")
+        ))
+        .await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_state_checkpoint_proof() {
+    let mut context = new_test_context(current_function_name!());
+    context.create_account().await;
+    let checkpoint = context
+        .db
+        .get_latest_state_checkpoint_version()
+        .unwrap()
+        .unwrap();
+    let get = |query: &str| {
+        warp::test::request()
+            .header("Accept", "application/x-bcs")
+            .path(&format!("/v1/state/checkpoint/proof?address=0x1{}", query))
+    };
+
+    let resp = context.reply(get("")).await;
+    assert_eq!(resp.status(), 200);
+    let proof: StateProofPayload = bcs::from_bytes(resp.body()).unwrap();
+    assert_eq!(proof.transaction_index, checkpoint);
+    assert_eq!(
+        proof.element_key,
+        StateKey::resource(&AccountAddress::ONE, &AccountResource::struct_tag())
+            .unwrap()
+            .hash()
+    );
+
+    // Genesis is the only snapshot before version 1
+    let resp = context.reply(get("&before_version=1")).await;
+    assert_eq!(resp.status(), 200);
+    let proof: StateProofPayload = bcs::from_bytes(resp.body()).unwrap();
+    assert_eq!(proof.transaction_index, 0);
+
+    let resp = context.reply(get("&before_version=0")).await;
+    assert_eq!(resp.status(), 404);
+}
+
+/// A DB with only a latest state checkpoint and the state snapshots committed
+struct MockCheckpointDb {
+    checkpoint: Version,
+    snapshots: Vec<Version>,
+}
+
+impl DbReader for MockCheckpointDb {
+    fn get_latest_state_checkpoint_version(
+        &self,
+    ) -> aptos_storage_interface::Result<Option<Version>> {
+        Ok(Some(self.checkpoint))
+    }
+
+    fn get_state_snapshot_before(
+        &self,
+        next_version: Version,
+    ) -> aptos_storage_interface::Result<Option<(Version, HashValue)>> {
+        Ok(self
+            .snapshots
+            .iter()
+            .rev()
+            .find(|version| **version < next_version)
+            .map(|version| (*version, HashValue::zero())))
+    }
+}
+
+#[test]
+fn test_latest_checkpoint_at() {
+    let db = MockCheckpointDb {
+        checkpoint: 10,
+        snapshots: vec![0, 5, 10],
+    };
+    assert_eq!(latest_checkpoint_at(&db, 10).unwrap(), Some(10));
+    assert_eq!(latest_checkpoint_at(&db, 12).unwrap(), Some(10));
+
+    // The latest checkpoint was committed after the ledger info
+    assert_eq!(latest_checkpoint_at(&db, 7).unwrap(), Some(5));
+    assert_eq!(latest_checkpoint_at(&db, 5).unwrap(), Some(5));
+
+    let db = MockCheckpointDb {
+        checkpoint: 10,
+        snapshots: vec![10],
+    };
+    assert_eq!(latest_checkpoint_at(&db, 7).unwrap(), None);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_resource_deserialization_error() {
+    let context = new_test_context(current_function_name!());
+    let ledger_info = context.get_latest_ledger_info();
+    let state_view = context.latest_state_view();
+    let converter = state_view.as_converter(context.db.clone(), None);
+
+    let chain_id = state_view
+        .get_state_value_bytes(
+            &StateKey::resource(&AccountAddress::ONE, &ChainIdResource::struct_tag()).unwrap(),
+        )
+        .unwrap()
+        .unwrap();
+    assert!(try_into_resource(
+        &converter,
+        &ChainIdResource::struct_tag(),
+        &chain_id,
+        &ledger_info
+    )
+    .is_ok());
+
+    // The bytes of the chain ID are too short for an account resource
+    let err = try_into_resource(
+        &converter,
+        &AccountResource::struct_tag(),
+        &chain_id,
+        &ledger_info,
+    )
+    .unwrap_err();
+    match err {
+        BasicErrorWith404::Internal(error, ..) => assert!(matches!(
+            error.0.error_code,
+            AptosErrorCode::ResourceDeserializationError
+        )),
+        err => panic!("Expected an internal error, got {:?}", err),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_raw_state_value_with_key_hash() {
+    let mut context = new_test_context(current_function_name!());
+    let state_key =
+        StateKey::resource(&AccountAddress::ONE, &ChainIdResource::struct_tag()).unwrap();
+    let get = |query: &str| {
+        warp::test::request()
+            .method("POST")
+            .header("Accept", "application/x-bcs")
+            .path(&format!("/v1/experimental/state_values/raw{}", query))
+            .json(&json!({
+                "key": format!("0x{}", hex::encode(bcs::to_bytes(&state_key).unwrap())),
+            }))
+    };
+
+    let resp = context.reply(get("")).await;
+    assert_eq!(resp.status(), 200);
+    let state_value: StateValue = bcs::from_bytes(resp.body()).unwrap();
+
+    let resp = context.reply(get("?with_key_hash=true")).await;
+    assert_eq!(resp.status(), 200);
+    let with_key_hash: StateValueWithKeyHash = bcs::from_bytes(resp.body()).unwrap();
+    assert_eq!(with_key_hash.value, state_value);
+    assert_eq!(with_key_hash.key_hash, state_key.hash());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_table_items_at_pruned_version() {
+    let mut context = new_test_context(current_function_name!());
+    context.create_account().await;
+    let ledger_version = context.get_latest_ledger_info().version();
+    context
+        .db
+        .set_ledger_min_readable_version_for_test(ledger_version)
+        .unwrap();
+
+    let state_key =
+        StateKey::resource(&AccountAddress::ONE, &ChainIdResource::struct_tag()).unwrap();
+    let requests = [
+        (
+            format!("{}?ledger_version=0", get_table_item(AccountAddress::ONE)),
+            json!({"key_type": "u8", "value_type": "u8", "key": 1}),
+        ),
+        (
+            format!("/tables/{}/raw_item?ledger_version=0", AccountAddress::ONE),
+            json!({"key": "0x01"}),
+        ),
+        (
+            "/experimental/state_values/raw?ledger_version=0".to_string(),
+            json!({"key": format!("0x{}", hex::encode(bcs::to_bytes(&state_key).unwrap()))}),
+        ),
+    ];
+    for (path, body) in requests {
+        // Pruned history is gone, not missing. Errors are JSON whatever the accept type
+        let resp = context
+            .reply(
+                warp::test::request()
+                    .method("POST")
+                    .header("Accept", "application/x-bcs")
+                    .path(&format!("/v1{}", path))
+                    .json(&body),
+            )
+            .await;
+        assert_eq!(resp.status(), 410, "{}", path);
+        let error: Value = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(error["error_code"], "version_pruned", "{}", path);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_state_root() {
+    let mut context = new_test_context(current_function_name!());
+    context.create_account().await;
+    let version = context.get_latest_ledger_info().version();
+    let expected = context.get_transactions(version, 1)[0]
+        .info
+        .state_checkpoint_hash()
+        .unwrap();
+
+    let resp = context.get(&format!("/state/{}/root", version)).await;
+    assert_eq!(resp, json!(expected.to_hex_literal()));
+
+    let req = warp::test::request()
+        .method("GET")
+        .header("Accept", "application/x-bcs")
+        .path(&format!("/v1/state/{}/root", version));
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(bcs::from_bytes::<HashValue>(resp.body()).unwrap(), expected);
+
+    // The user transaction isn't the last of its block
+    let resp = context
+        .expect_status_code(400)
+        .get(&format!("/state/{}/root", version - 1))
+        .await;
+    assert_eq!(resp["error_code"], "invalid_input");
+    context
+        .expect_status_code(404)
+        .get(&format!("/state/{}/root", version + 1))
+        .await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_resource_proofs_too_many_versions() {
+    let context = new_test_context(current_function_name!());
+    let versions = vec!["0".to_string(); 11];
+    let req = warp::test::request()
+        .method("POST")
+        .header("Accept", "application/x-bcs")
+        .path(&format!(
+            "/v1{}/proof",
+            get_account_resource("0xA550C18", "0x1::account::Account")
+        ))
+        .json(&versions);
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_proof_by_transaction_hash() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account).await;
+    context.commit_block(&vec![txn.clone()]).await;
+
+    let proof_after = |hash: HashValue| {
+        warp::test::request()
+            .method("GET")
+            .header("Accept", "application/x-bcs")
+            .path(&format!(
+                "/v1{}?transaction_hash={}",
+                get_account_proof("0xA550C18"),
+                hash.to_hex_literal()
+            ))
+    };
+
+    // The account is proven at the end of the block of the transaction
+    let resp = context.reply(proof_after(txn.committed_hash())).await;
+    assert_eq!(resp.status(), 200);
+    let payload: StateProofPayload = bcs::from_bytes(resp.body()).unwrap();
+    assert_eq!(
+        payload.transaction_index,
+        context.get_latest_ledger_info().version()
+    );
+
+    let resp = context.reply(proof_after(HashValue::zero())).await;
+    assert_eq!(resp.status(), 404);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_proof_with_resources() {
+    let mut node_config = NodeConfig::default();
+    node_config.api.account_proof_resources = vec![
+        "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>".to_string(),
+        "0x1::coin::CoinInfo<0x1::aptos_coin::AptosCoin>".to_string(),
+    ];
+    let mut context = new_test_context_with_config(current_function_name!(), node_config);
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("GET")
+                .header("Accept", "application/x-bcs")
+                .path(&format!("/v1{}", get_account_proof_bundle("0xA550C18"))),
+        )
+        .await;
+    assert_eq!(resp.status(), 200);
+    let proofs: Vec<StateProofPayload> = bcs::from_bytes(resp.body()).unwrap();
+
+    // The account has a coin store but no coin info, which is only published under 0x1
+    let state_keys = proofs
+        .iter()
+        .map(|proof| bcs::from_bytes(proof.state_key.as_ref().unwrap()).unwrap())
+        .collect::<Vec<StateKey>>();
+    let address = AccountAddress::from_hex_literal("0xA550C18").unwrap();
+    assert_eq!(state_keys, vec![
+        StateKey::resource(&address, &AccountResource::struct_tag()).unwrap(),
+        StateKey::resource(
+            &address,
+            &"0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>"
+                .parse()
+                .unwrap()
+        )
+        .unwrap(),
+    ]);
+
+    // Ledger infos aren't signed in tests, so only the state proofs are verified
+    for proof in &proofs {
+        assert_eq!(proof.ledger_info_v0, proofs[0].ledger_info_v0);
+        assert_eq!(proof.transaction_index, proofs[0].transaction_index);
+        proof
+            .full_state_proof()
+            .unwrap()
+            .verify_by_hash(
+                proof
+                    .transaction
+                    .as_ref()
+                    .unwrap()
+                    .state_checkpoint_hash()
+                    .unwrap(),
+                proof.element_key,
+                Some(proof.element_hash),
+            )
+            .unwrap();
+    }
+
+    // Trimmed proofs are bundled the same way
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("GET")
+                .header("Accept", "application/x-bcs")
+                .path(&format!(
+                    "/v1{}?trim_ledger_info=true",
+                    get_account_proof_bundle("0xA550C18")
+                )),
+        )
+        .await;
+    assert_eq!(resp.status(), 200);
+    let trimmed: Vec<TrimmedAccountProofPayload> = bcs::from_bytes(resp.body()).unwrap();
+    assert_eq!(
+        trimmed
+            .iter()
+            .map(|proof| proof.element_key)
+            .collect::<Vec<_>>(),
+        proofs
+            .iter()
+            .map(|proof| proof.element_key)
+            .collect::<Vec<_>>()
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_proof_with_expected_ledger_info_hash() {
+    let mut context = new_test_context(current_function_name!());
+    context.create_account().await;
+    let proof_anchored_to = |hash: HashValue| {
+        warp::test::request()
+            .method("GET")
+            .header("Accept", "application/x-bcs")
+            .path(&format!(
+                "/v1{}?expected_ledger_info_hash={}",
+                get_account_proof("0xA550C18"),
+                hash.to_hex_literal()
+            ))
+    };
+
+    let latest_li = context.db.get_latest_ledger_info().unwrap();
+    let resp = context
+        .reply(proof_anchored_to(latest_li.ledger_info().hash()))
+        .await;
+    assert_eq!(resp.status(), 200);
+    let payload: StateProofPayload = bcs::from_bytes(resp.body()).unwrap();
+    assert_eq!(payload.ledger_info_v0, latest_li);
+
+    // The ledger info the client expected is no longer the latest
+    context.create_account().await;
+    let resp = context
+        .reply(proof_anchored_to(latest_li.ledger_info().hash()))
+        .await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_transaction_proof() {
+    let mut context = new_test_context(current_function_name!());
+    context.create_account().await;
+    let ledger_version = context.get_latest_ledger_info().version();
+    let prove = |version: u64| {
+        warp::test::request()
+            .header("Accept", "application/x-bcs")
+            .path(&format!("/v1/transactions/{}/proof", version))
+    };
+
+    // The user transaction of the block
+    let version = ledger_version - 1;
+    let resp = context.reply(prove(version)).await;
+    assert_eq!(resp.status(), 200);
+    let proof: TransactionProofPayload = bcs::from_bytes(resp.body()).unwrap();
+    assert_eq!(proof.transaction_index, version);
+    assert_eq!(proof.ledger_info_v0.ledger_info().version(), ledger_version);
+    assert_eq!(
+        proof.transaction,
+        context.get_transactions(version, 1)[0].info
+    );
+    proof
+        .transaction_proof
+        .verify(
+            proof
+                .ledger_info_v0
+                .ledger_info()
+                .transaction_accumulator_hash(),
+            proof.transaction.hash(),
+            version,
+        )
+        .unwrap();
+
+    let resp = context.reply(prove(ledger_version + 1)).await;
+    assert_eq!(resp.status(), 404);
+
+    context
+        .db
+        .set_ledger_min_readable_version_for_test(ledger_version)
+        .unwrap();
+    let resp = context.reply(prove(0)).await;
+    assert_eq!(resp.status(), 410);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_event_proof() {
+    let mut context = new_test_context(current_function_name!());
+    context.create_account().await;
+    let ledger_version = context.get_latest_ledger_info().version();
+    let prove = |query: &str| {
+        warp::test::request()
+            .header("Accept", "application/x-bcs")
+            .path(&format!("/v1/accounts/0x1/events/2/proof?{}", query))
+    };
+
+    // The new epoch event of genesis
+    let resp = context.reply(prove("sequence_number=0")).await;
+    assert_eq!(resp.status(), 200);
+    let proof: EventProofPayload = bcs::from_bytes(resp.body()).unwrap();
+    assert_eq!(
+        proof.event.v1().unwrap().key(),
+        &EventKey::new(2, AccountAddress::ONE)
+    );
+    assert_eq!(proof.event.v1().unwrap().sequence_number(), 0);
+    assert_eq!(proof.transaction_index, 0);
+    assert_eq!(proof.ledger_info_v0.ledger_info().version(), ledger_version);
+    assert_eq!(proof.transaction, context.get_transactions(0, 1)[0].info);
+    proof
+        .event_proof
+        .verify(
+            proof.transaction.event_root_hash(),
+            proof.event.hash(),
+            proof.event_index,
+        )
+        .unwrap();
+    proof
+        .transaction_proof
+        .verify(
+            proof
+                .ledger_info_v0
+                .ledger_info()
+                .transaction_accumulator_hash(),
+            proof.transaction.hash(),
+            0,
+        )
+        .unwrap();
+
+    let resp = context
+        .reply(prove("sequence_number=0&ledger_version=0"))
+        .await;
+    assert_eq!(resp.status(), 200);
+    let genesis_proof: EventProofPayload = bcs::from_bytes(resp.body()).unwrap();
+    assert_eq!(genesis_proof.event, proof.event);
+    assert_eq!(genesis_proof.ledger_info_v0.ledger_info().version(), 0);
+
+    let resp = context.reply(prove("sequence_number=100")).await;
+    assert_eq!(resp.status(), 404);
+
+    context
+        .db
+        .set_ledger_min_readable_version_for_test(ledger_version)
+        .unwrap();
+    let resp = context
+        .reply(prove("sequence_number=0&ledger_version=0"))
+        .await;
+    assert_eq!(resp.status(), 410);
+
+    // The latest version is still served, but not the transaction that emitted the event
+    let resp = context.reply(prove("sequence_number=0")).await;
+    assert_eq!(resp.status(), 410);
+    let error: Value = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(error["error_code"], "version_pruned");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_proof_schema() {
+    let mut context = new_test_context(current_function_name!());
+    let resp = context.get("/proof/schema").await;
+    assert_eq!(resp["version"], json!(PROOF_SCHEMA_VERSION));
+    assert_eq!(resp["types"][0]["name"], json!("AccountProofPayload"));
+    assert_eq!(
+        resp["types"][0]["fields"][0],
+        json!({"name": "state_proof", "type": "SparseMerkleProof"})
+    );
+
+    let resp = context
+        .reply(
+            warp::test::request()
+                .header("Accept", "application/x-bcs")
+                .path("/v1/proof/schema"),
+        )
+        .await;
+    assert_eq!(resp.status(), 403);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_resources_proof() {
+    let mut context = new_test_context(current_function_name!());
+    context.create_account().await;
+    let version = context.get_latest_ledger_info().version();
+    let prove = |query: String| {
+        warp::test::request()
+            .header("Accept", "application/x-bcs")
+            .path(&format!("/v1/accounts/0xA550C18/resources/proof?{}", query))
+    };
+
+    // All resources in one page
+    let resp = context.reply(prove("limit=100".to_string())).await;
+    assert_eq!(resp.status(), 200);
+    assert!(!resp.headers().contains_key("X-Aptos-Cursor"));
+    let all: AccountResourcesProofPayload = bcs::from_bytes(resp.body()).unwrap();
+    assert_eq!(all.version, version);
+    assert!(all.resources.len() > 2);
+
+    // The same resources, two at a time, following the cursor
+    let mut paged = vec![];
+    let mut query = "limit=2".to_string();
+    loop {
+        let resp = context.reply(prove(query)).await;
+        assert_eq!(resp.status(), 200);
+        let page: AccountResourcesProofPayload = bcs::from_bytes(resp.body()).unwrap();
+        assert!(page.resources.len() <= 2);
+        paged.extend(page.resources);
+        match resp.headers().get("X-Aptos-Cursor") {
+            Some(cursor) => query = format!("limit=2&start={}", cursor.to_str().unwrap()),
+            None => break,
+        }
+    }
+    assert_eq!(paged, all.resources);
+
+    for resource in &all.resources {
+        let proof = &resource.proof;
+        assert_eq!(
+            proof.state_key().unwrap().unwrap().hash(),
+            proof.element_key
+        );
+        assert_eq!(proof.ledger_info_v0, all.resources[0].proof.ledger_info_v0);
+        proof.verify_state_value(&resource.state_value).unwrap();
+        proof
+            .full_state_proof()
+            .unwrap()
+            .verify_by_hash(
+                proof
+                    .transaction
+                    .as_ref()
+                    .unwrap()
+                    .state_checkpoint_hash()
+                    .unwrap(),
+                proof.element_key,
+                Some(proof.element_hash),
+            )
+            .unwrap();
+    }
+
+    let resp = context.reply(prove("limit=0".to_string())).await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_state_delta() {
+    let mut context = new_test_context(current_function_name!());
+    let before_account = context.get_latest_ledger_info().version();
+    let account = context.create_account().await;
+    let from_version = context.get_latest_ledger_info().version();
+    // Only the root account, which sends the transaction, changes
+    context.create_account().await;
+    let to_version = context.get_latest_ledger_info().version();
+
+    let delta_of = |address: AccountAddress| {
+        warp::test::request()
+            .method("GET")
+            .header("Accept", "application/x-bcs")
+            .path(&format!(
+                "/v1/accounts/{}/proof/delta?from_version={}&to_version={}",
+                address.to_hex_literal(),
+                from_version,
+                to_version
+            ))
+    };
+
+    let resp = context.reply(delta_of(account.address())).await;
+    assert_eq!(resp.status(), 200);
+    let delta: AccountStateDeltaPayload = bcs::from_bytes(resp.body()).unwrap();
+    assert!(!delta.more);
+    assert!(!delta.resources.is_empty());
+    assert_eq!(delta.from.transaction_index, from_version);
+    assert_eq!(delta.to.transaction_index, to_version);
+    // Both versions are in the latest epoch, so they share the latest ledger info
+    assert_eq!(delta.from.ledger_info_v0, delta.to.ledger_info_v0);
+    let from_state_root = delta.from.state_root().unwrap();
+    let to_state_root = delta.to.state_root().unwrap();
+    for resource in &delta.resources {
+        assert!(!resource.changed);
+        assert!(resource.from_value_hash.is_some());
+        resource.verify(from_state_root, to_state_root).unwrap();
+    }
+
+    let root = AccountAddress::from_hex_literal("0xA550C18").unwrap();
+    let resp = context.reply(delta_of(root)).await;
+    assert_eq!(resp.status(), 200);
+    let delta: AccountStateDeltaPayload = bcs::from_bytes(resp.body()).unwrap();
+    let account_key =
+        bcs::to_bytes(&StateKey::resource(&root, &AccountResource::struct_tag()).unwrap()).unwrap();
+    let account_resource = delta
+        .resources
+        .iter()
+        .find(|resource| resource.state_key == account_key)
+        .unwrap();
+    assert!(account_resource.changed);
+    account_resource
+        .verify(
+            delta.from.state_root().unwrap(),
+            delta.to.state_root().unwrap(),
+        )
+        .unwrap();
+
+    // The account didn't exist yet, its resources are proven missing
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("GET")
+                .header("Accept", "application/x-bcs")
+                .path(&format!(
+                    "/v1/accounts/{}/proof/delta?from_version={}&to_version={}",
+                    account.address().to_hex_literal(),
+                    before_account,
+                    to_version
+                )),
+        )
+        .await;
+    assert_eq!(resp.status(), 200);
+    let delta: AccountStateDeltaPayload = bcs::from_bytes(resp.body()).unwrap();
+    assert!(!delta.resources.is_empty());
+    let from_state_root = delta.from.state_root().unwrap();
+    let to_state_root = delta.to.state_root().unwrap();
+    for resource in &delta.resources {
+        assert!(resource.changed);
+        assert!(resource.from_value_hash.is_none());
+        resource.verify(from_state_root, to_state_root).unwrap();
+    }
+
+    // A pruned version is reported as such, before the state at it is read
+    context
+        .db
+        .set_ledger_min_readable_version_for_test(before_account + 1)
+        .unwrap();
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("GET")
+                .header("Accept", "application/x-bcs")
+                .path(&format!(
+                    "/v1/accounts/{}/proof/delta?from_version={}&to_version={}",
+                    account.address().to_hex_literal(),
+                    before_account,
+                    to_version
+                )),
+        )
+        .await;
+    assert_eq!(resp.status(), 410);
+
+    // The versions are out of order
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("GET")
+                .header("Accept", "application/x-bcs")
+                .path(&format!(
+                    "/v1/accounts/0xA550C18/proof/delta?from_version={}&to_version={}",
+                    to_version, from_version
+                )),
+        )
+        .await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_proof_size_breakdown() {
+    let mut context = new_test_context(current_function_name!());
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("GET")
+                .header("Accept", "application/x-bcs")
+                .path(&format!(
+                    "/v1{}?include_state_key=true",
+                    get_account_proof("0xA550C18")
+                )),
+        )
+        .await;
+    assert_eq!(resp.status(), 200);
+
+    let breakdown = context
+        .get(&format!(
+            "{}?include_state_key=true&breakdown=true",
+            get_account_proof("0xA550C18")
+        ))
+        .await;
+    let size = |component: &str| {
+        breakdown[component]
+            .as_str()
+            .unwrap()
+            .parse::<u64>()
+            .unwrap()
+    };
+    assert_eq!(size("total"), resp.body().len() as u64);
+    assert_eq!(
+        size("state_proof")
+            + size("accumulator_proof")
+            + size("ledger_info")
+            + size("validator_set")
+            + size("other"),
+        size("total")
+    );
+
+    // The breakdown is of an untrimmed proof
+    context
+        .expect_status_code(400)
+        .get(&format!(
+            "{}?breakdown=true&trim_ledger_info=true",
+            get_account_proof("0xA550C18")
+        ))
+        .await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_epoch_change_proof_between_waypoints() {
+    let context = new_test_context(current_function_name!());
+    let genesis_li = context
+        .db
+        .get_epoch_ending_ledger_infos(0, 1)
+        .unwrap()
+        .ledger_info_with_sigs
+        .remove(0);
+    let genesis_waypoint = Waypoint::new_epoch_boundary(genesis_li.ledger_info()).unwrap();
+    let proof_between = |from: &Waypoint, to: &Waypoint| {
+        warp::test::request()
+            .method("GET")
+            .header("Accept", "application/x-bcs")
+            .path(&format!("/v1/epoch/proof/between?from={}&to={}", from, to))
+    };
+
+    // No epoch changed between a waypoint and itself
     let resp = context
-        .get(&get_account_proof("0xA550C18"))
+        .reply(proof_between(&genesis_waypoint, &genesis_waypoint))
         .await;
-    println!("{}", &resp);
-    panic!("a");
-    context.check_golden_output(resp);
+    assert_eq!(resp.status(), 200);
+    let proof: EpochChangeProof = bcs::from_bytes(resp.body()).unwrap();
+    assert!(proof.ledger_info_with_sigs.is_empty());
+
+    // A waypoint at genesis, but not derived from the genesis ledger info
+    let wrong_waypoint: Waypoint = format!("0:{}", HashValue::zero().to_hex()).parse().unwrap();
+    let resp = context
+        .reply(proof_between(&genesis_waypoint, &wrong_waypoint))
+        .await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_epoch_change_proof_between_distinct_waypoints() {
+    let mut context = new_test_context(current_function_name!());
+    context.create_account().await;
+    context.end_epoch().await;
+    // The epoch-ending ledger info can only be proven once the next epoch has started
+    context.create_account().await;
+
+    let epoch_ending_lis = context
+        .db
+        .get_epoch_ending_ledger_infos(0, 2)
+        .unwrap()
+        .ledger_info_with_sigs;
+    assert_eq!(epoch_ending_lis.len(), 2);
+    let waypoints: Vec<_> = epoch_ending_lis
+        .iter()
+        .map(|li| Waypoint::new_epoch_boundary(li.ledger_info()).unwrap())
+        .collect();
+
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("GET")
+                .header("Accept", "application/x-bcs")
+                .path(&format!(
+                    "/v1/epoch/proof/between?from={}&to={}",
+                    waypoints[0], waypoints[1]
+                )),
+        )
+        .await;
+    assert_eq!(resp.status(), 200);
+    let proof: EpochChangeProof = bcs::from_bytes(resp.body()).unwrap();
+    // The chain starts right after `from` and ends at `to`
+    assert!(!proof.more);
+    assert_eq!(proof.ledger_info_with_sigs, epoch_ending_lis[1..]);
+    let last_li = proof.ledger_info_with_sigs.last().unwrap().ledger_info();
+    assert_eq!(last_li.epoch(), 1);
+    assert_eq!(Waypoint::new_epoch_boundary(last_li).unwrap(), waypoints[1]);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_epoch_change_proof_between_reversed_waypoints() {
+    let mut context = new_test_context(current_function_name!());
+    context.create_account().await;
+    let genesis_li = context
+        .db
+        .get_epoch_ending_ledger_infos(0, 1)
+        .unwrap()
+        .ledger_info_with_sigs
+        .remove(0);
+    let genesis_waypoint = Waypoint::new_epoch_boundary(genesis_li.ledger_info()).unwrap();
+    let latest_li = context.db.get_latest_ledger_info().unwrap();
+    let latest_waypoint = Waypoint::new_any(latest_li.ledger_info());
+
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("GET")
+                .header("Accept", "application/x-bcs")
+                .path(&format!(
+                    "/v1/epoch/proof/between?from={}&to={}",
+                    latest_waypoint, genesis_waypoint
+                )),
+        )
+        .await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_epoch_state() {
+    let mut context = new_test_context(current_function_name!());
+    let latest_epoch_state = context.db.get_latest_epoch_state().unwrap();
+    let expected = EpochStateView::from(latest_epoch_state.clone());
+
+    let resp = context.get("/epoch/state").await;
+    assert_eq!(
+        serde_json::from_value::<EpochStateView>(resp).unwrap(),
+        expected
+    );
+
+    // Requesting the latest epoch explicitly
+    let resp = context
+        .get(&format!(
+            "/epoch/state?epoch_number={}",
+            latest_epoch_state.epoch
+        ))
+        .await;
+    assert_eq!(
+        serde_json::from_value::<EpochStateView>(resp).unwrap(),
+        expected
+    );
+
+    for epoch_number in [0, latest_epoch_state.epoch + 1] {
+        let resp = context
+            .expect_status_code(400)
+            .get(&format!("/epoch/state?epoch_number={}", epoch_number))
+            .await;
+        assert_eq!(resp["error_code"], "invalid_input");
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_epoch_waypoint() {
+    let mut context = new_test_context(current_function_name!());
+    let genesis_li = context
+        .db
+        .get_epoch_ending_ledger_infos(0, 1)
+        .unwrap()
+        .ledger_info_with_sigs
+        .remove(0);
+    let genesis_waypoint = Waypoint::new_epoch_boundary(genesis_li.ledger_info()).unwrap();
+
+    let resp = context.get("/epoch/waypoint?epoch_number=1").await;
+    let waypoint: WaypointView = serde_json::from_value(resp).unwrap();
+    assert_eq!(waypoint.to_string(), genesis_waypoint.to_string());
+    assert_eq!(
+        waypoint.to_string().parse::<Waypoint>().unwrap(),
+        genesis_waypoint
+    );
+
+    let resp = context
+        .expect_status_code(400)
+        .get("/epoch/waypoint?epoch_number=0")
+        .await;
+    assert_eq!(resp["error_code"], "invalid_input");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_derive_waypoint() {
+    let mut node_config = NodeConfig::default();
+    node_config.api.developer_tools_enabled = true;
+    let context = new_test_context_with_config(current_function_name!(), node_config);
+    let genesis_li = context
+        .db
+        .get_epoch_ending_ledger_infos(0, 1)
+        .unwrap()
+        .ledger_info_with_sigs
+        .remove(0);
+
+    let req = warp::test::request()
+        .method("POST")
+        .path("/v1/waypoint/derive")
+        .header("Content-Type", "application/x-bcs")
+        .body(bcs::to_bytes(&genesis_li).unwrap());
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 200);
+    let waypoint: WaypointView = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(waypoint, Waypoint::new_any(genesis_li.ledger_info()).into());
+
+    let req = warp::test::request()
+        .method("POST")
+        .path("/v1/waypoint/derive")
+        .header("Content-Type", "application/x-bcs")
+        .body(vec![1, 2, 3]);
+    assert_eq!(context.reply(req).await.status(), 400);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_derive_waypoint_disabled() {
+    let context = new_test_context(current_function_name!());
+    let req = warp::test::request()
+        .method("POST")
+        .path("/v1/waypoint/derive")
+        .header("Content-Type", "application/x-bcs")
+        .body(vec![]);
+    assert_eq!(context.reply(req).await.status(), 403);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_epoch_change_proof_beyond_latest_epoch() {
+    let mut context = new_test_context(current_function_name!());
+    let latest_epoch = context.db.get_latest_epoch_state().unwrap().epoch;
+    let resp = context
+        .expect_status_code(400)
+        .get(&format!("/epoch/proof?epoch_number={}", latest_epoch + 5))
+        .await;
+    assert_eq!(resp["error_code"], "invalid_input");
+    assert!(resp["message"]
+        .as_str()
+        .unwrap()
+        .contains(&latest_epoch.to_string()));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_epoch_change_proof_before_epoch_two() {
+    let mut context = new_test_context(current_function_name!());
+    for epoch_number in [0, 1] {
+        let resp = context
+            .expect_status_code(400)
+            .get(&format!("/epoch/proof?epoch_number={}", epoch_number))
+            .await;
+        assert_eq!(resp["error_code"], "invalid_input");
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_epoch_change_proof_from_epoch() {
+    let mut context = new_test_context(current_function_name!());
+    let latest_epoch = context.db.get_latest_epoch_state().unwrap().epoch;
+    let genesis_li = context
+        .db
+        .get_epoch_ending_ledger_infos(latest_epoch - 1, latest_epoch)
+        .unwrap()
+        .ledger_info_with_sigs
+        .pop()
+        .unwrap();
+
+    // Trusting the latest epoch already, there is no epoch change to prove
+    let req = warp::test::request()
+        .header("Accept", "application/x-bcs")
+        .path(&format!("/v1/epoch/proof?from_epoch={}", latest_epoch));
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 200);
+    let payload: EpochChangeProofPayload = bcs::from_bytes(resp.body()).unwrap();
+    assert!(payload.epoch_change_proof.ledger_info_with_sigs.is_empty());
+    assert!(!payload.epoch_change_proof.more);
+    assert!(payload.next_epoch_state_present);
+    assert_eq!(
+        payload.trusted_state.waypoint(),
+        Waypoint::new_any(genesis_li.ledger_info())
+    );
+    match payload.trusted_state {
+        TrustedState::EpochState { epoch_state, .. } => {
+            assert_eq!(epoch_state, context.db.get_latest_epoch_state().unwrap())
+        },
+        TrustedState::EpochWaypoint(_) => panic!("Expected a trusted epoch state"),
+    }
+
+    for query in [
+        "from_epoch=0".to_string(),
+        format!("from_epoch={}", latest_epoch + 1),
+        format!("from_epoch={}&epoch_number={}", latest_epoch, latest_epoch),
+    ] {
+        let resp = context
+            .expect_status_code(400)
+            .get(&format!("/epoch/proof?{}", query))
+            .await;
+        assert_eq!(resp["error_code"], "invalid_input");
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_proof_chain_id() {
+    let mut context = new_test_context(current_function_name!());
+    let chain_id = context.get_latest_ledger_info().chain_id;
+
+    for path in [
+        get_account_proof("0x1"),
+        "/epoch/proof".to_string(),
+        "/bootstrap".to_string(),
+        format!(
+            "{}?ledger_version={}&with_proof=true",
+            get_account_resource("0x1", "0x1::chain_id::ChainId"),
+            version
+        ),
+    ] {
+        let req = warp::test::request()
+            .header("Accept", "application/x-bcs")
+            .path(&format!("/v1{}?chain_id={}", path, chain_id));
+        let resp = context.reply(req).await;
+        assert_eq!(resp.status(), 200);
+
+        // Proofs of another chain must not be trusted
+        let req = warp::test::request()
+            .header("Accept", "application/x-bcs")
+            .path(&format!(
+                "/v1{}?chain_id={}",
+                path,
+                chain_id.wrapping_add(1)
+            ));
+        let resp = context.reply(req).await;
+        assert_eq!(resp.status(), 400);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_bootstrap_bundle() {
+    let mut context = new_test_context(current_function_name!());
+    context.create_account().await;
+    let version = context.get_latest_ledger_info().version();
+
+    let req = warp::test::request()
+        .header("Accept", "application/x-bcs")
+        .path("/v1/bootstrap");
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 200);
+    let bundle: BootstrapBundle = bcs::from_bytes(resp.body()).unwrap();
+    assert_eq!(bundle.latest_ledger_info.ledger_info().version(), version);
+
+    // Still in the first epoch after genesis, the trusted state is already the latest epoch
+    // state. Ledger infos aren't signed in tests, so the bundle can't be ratcheted through.
+    assert!(bundle.epoch_change_proof.ledger_info_with_sigs.is_empty());
+    match bundle.trusted_state {
+        TrustedState::EpochState { epoch_state, .. } => {
+            assert_eq!(
+                epoch_state.epoch,
+                bundle.latest_ledger_info.ledger_info().epoch()
+            );
+            assert_eq!(epoch_state, context.db.get_latest_epoch_state().unwrap());
+        },
+        TrustedState::EpochWaypoint(_) => panic!("Expected a trusted epoch state"),
+    }
+
+    let resp = context
+        .reply(warp::test::request().path("/v1/bootstrap"))
+        .await;
+    assert_eq!(resp.status(), 403);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_epoch_change_proof_range_out_of_range() {
+    let mut context = new_test_context(current_function_name!());
+    let latest_epoch = context.db.get_latest_epoch_state().unwrap().epoch;
+    for (start_epoch, end_epoch) in [
+        // No ledger info ends the epoch before genesis
+        (0, latest_epoch),
+        // Empty range
+        (latest_epoch, latest_epoch),
+        // The latest epoch hasn't ended yet
+        (1, latest_epoch + 1),
+    ] {
+        let resp = context
+            .expect_status_code(400)
+            .get(&format!(
+                "/epoch/proof/range?start_epoch={}&end_epoch={}",
+                start_epoch, end_epoch
+            ))
+            .await;
+        assert_eq!(resp["error_code"], "invalid_input");
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_stream_epoch_change_proof() {
+    let mut context = new_test_context(current_function_name!());
+    let latest_epoch = context.db.get_latest_epoch_state().unwrap().epoch;
+    let expected = context
+        .db
+        .get_epoch_ending_ledger_infos(0, latest_epoch)
+        .unwrap();
+    let stream = |start_epoch: u64, chunk_size: u64| {
+        warp::test::request()
+            .header("Accept", "application/x-bcs")
+            .path(&format!(
+                "/v1/epoch/proof/stream?start_epoch={}&chunk_size={}",
+                start_epoch, chunk_size
+            ))
+    };
+
+    // Follow the cursor until the last page
+    let mut ledger_infos = vec![];
+    let mut next_epoch = Some(0);
+    while let Some(start_epoch) = next_epoch {
+        let resp = context.reply(stream(start_epoch, 1)).await;
+        assert_eq!(resp.status(), 200);
+        let page: EpochChangeProofPage = bcs::from_bytes(resp.body()).unwrap();
+        assert!(page.epoch_change_proof.ledger_info_with_sigs.len() <= 1);
+        assert_eq!(page.epoch_change_proof.more, page.next_epoch.is_some());
+        ledger_infos.extend(page.epoch_change_proof.ledger_info_with_sigs);
+        next_epoch = page.next_epoch;
+    }
+    assert_eq!(ledger_infos, expected.ledger_info_with_sigs);
+
+    // Already at the latest epoch, there is nothing left to sync
+    let resp = context.reply(stream(latest_epoch, 1)).await;
+    assert_eq!(resp.status(), 200);
+    let page: EpochChangeProofPage = bcs::from_bytes(resp.body()).unwrap();
+    assert_eq!(
+        page.epoch_change_proof,
+        EpochChangeProof::new(vec![], false)
+    );
+    assert_eq!(page.next_epoch, None);
+
+    for (start_epoch, chunk_size) in [(latest_epoch + 1, 1), (0, 0)] {
+        let resp = context.reply(stream(start_epoch, chunk_size)).await;
+        assert_eq!(resp.status(), 400);
+    }
+    context
+        .expect_status_code(403)
+        .get("/epoch/proof/stream?start_epoch=0")
+        .await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_epoch_change_proof_top_n_requires_summary() {
+    let mut context = new_test_context(current_function_name!());
+    let resp = context
+        .expect_status_code(400)
+        .get("/epoch/proof?top_n=10")
+        .await;
+    assert_eq!(resp["error_code"], "invalid_input");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_epoch_change_summary_full_span() {
+    let mut context = new_test_context(current_function_name!());
+    // One epoch more than the span, so the first request spans as many epochs as allowed, which
+    // is more epoch-ending ledger infos than the DB returns at once
+    let max_span = context.context.max_epoch_change_proof_span() as u64;
+    for _ in 0..=max_span {
+        context.end_epoch().await;
+    }
+    let latest_epoch = context.db.get_latest_epoch_state().unwrap().epoch;
+    assert_eq!(latest_epoch, max_span + 2);
+
+    // Follow `next_epoch` until every epoch change is summarized
+    let mut epochs = vec![];
+    let mut next_epoch = Some(1);
+    while let Some(from_epoch) = next_epoch {
+        let resp = context
+            .reply(
+                warp::test::request()
+                    .header("Accept", "application/x-bcs")
+                    .path(&format!(
+                        "/v1/epoch/proof?summary=true&from_epoch={}",
+                        from_epoch
+                    )),
+            )
+            .await;
+        assert_eq!(resp.status(), 200);
+        let page: EpochChangeSummaryPage = bcs::from_bytes(resp.body()).unwrap();
+        assert_eq!(page.more, page.next_epoch.is_some());
+        if from_epoch == 1 {
+            assert!(page.more);
+        }
+        epochs.extend(page.summaries.iter().map(|summary| summary.epoch.0));
+        next_epoch = page.next_epoch;
+    }
+    assert_eq!(epochs, (2..=latest_epoch).collect::<Vec<_>>());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_epoch_progress() {
+    let context = new_test_context(current_function_name!());
+    let resp = context.get("/epoch/progress").await;
+
+    let ledger_info = context.get_latest_ledger_info();
+    assert_eq!(resp["epoch"], ledger_info.epoch.to_string());
+    assert_eq!(
+        resp["current_version"],
+        ledger_info.ledger_version.to_string()
+    );
+    let start_version: u64 = resp["start_version"].as_str().unwrap().parse().unwrap();
+    assert!(start_version <= ledger_info.version());
 }
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
@@ -132,6 +2041,50 @@ async fn test_get_account_module_not_found() {
     context.check_golden_output(resp);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_module_proof() {
+    let context = new_test_context(current_function_name!());
+    let version = context.get_latest_ledger_info().version();
+    let prove = |name: &str| {
+        warp::test::request()
+            .method("GET")
+            .header("Accept", "application/x-bcs")
+            .path(&format!("/v1{}/proof", get_account_module("0x1", name)))
+    };
+
+    let resp = context.reply(prove("guid")).await;
+    assert_eq!(resp.status(), 200);
+    let payload: StateProofPayload = bcs::from_bytes(resp.body()).unwrap();
+    let state_key = StateKey::module(&AccountAddress::ONE, ident_str!("guid"));
+    assert_eq!(payload.state_key().unwrap(), Some(state_key.clone()));
+    assert_eq!(payload.element_key, state_key.hash());
+    let bytecode = context
+        .db
+        .get_state_value_by_version(&state_key, version)
+        .unwrap()
+        .unwrap();
+    assert_eq!(payload.element_hash, bytecode.hash());
+    payload
+        .full_state_proof()
+        .unwrap()
+        .verify_by_hash(
+            payload
+                .transaction
+                .as_ref()
+                .unwrap()
+                .state_checkpoint_hash()
+                .unwrap(),
+            payload.element_key,
+            Some(payload.element_hash),
+        )
+        .unwrap();
+
+    let resp = context.reply(prove("NoNoNo")).await;
+    assert_eq!(resp.status(), 404);
+    let error: Value = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(error["error_code"], "module_not_found");
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_merkle_leaves_with_nft_transfer() {
     let mut context = new_test_context(current_function_name!());
@@ -308,6 +2261,177 @@ async fn test_get_table_item() {
     assert_table_item(ctx, &nested_table, "u8", "u8", 2, 3).await;
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_table_items() {
+    let mut context = new_test_context(current_function_name!());
+    let ctx = &mut context;
+    let mut acc = ctx.root_account().await;
+    make_test_tables(ctx, &mut acc).await;
+    let tt = ctx
+        .api_get_account_resource(
+            acc.address(),
+            &acc.address().to_hex_literal(),
+            "TableTestData",
+            "TestTables",
+        )
+        .await["data"]
+        .to_owned();
+    let handle: AccountAddress = tt["u64_table"]["handle"].as_str().unwrap().parse().unwrap();
+    let path = format!("/tables/{}/items/batch", handle);
+    let item = |key: Value| json!({"key_type": "u64", "value_type": "u64", "key": key});
+    let items = json!([item(json!("1")), item(json!("2")), item(json!("NaN"))]);
+
+    // A missing or malformed item fails on its own
+    let resp = ctx.post(&path, items.clone()).await;
+    assert_eq!(resp[0]["value"], "1");
+    assert_eq!(resp[0]["error"], Value::Null);
+    assert_eq!(resp[1]["value"], Value::Null);
+    assert_eq!(resp[1]["error"]["error_code"], "table_item_not_found");
+    assert_eq!(resp[2]["error"]["error_code"], "invalid_input");
+
+    let resp = ctx
+        .reply(
+            warp::test::request()
+                .method("POST")
+                .header("Accept", "application/x-bcs")
+                .path(&format!("/v1{}", path))
+                .json(&items),
+        )
+        .await;
+    assert_eq!(resp.status(), 200);
+    let results: Vec<RawTableItemResult> = bcs::from_bytes(resp.body()).unwrap();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].value, Some(bcs::to_bytes(&1u64).unwrap()));
+    assert_eq!(
+        results[1].error.as_ref().unwrap().error_code,
+        AptosErrorCode::TableItemNotFound
+    );
+
+    let too_many = vec![item(json!("1")); ctx.context.max_table_item_batch_size() + 1];
+    let resp = ctx
+        .expect_status_code(400)
+        .post(&path, json!(&too_many))
+        .await;
+    assert_eq!(resp["error_code"], "invalid_input");
+
+    // The batch size is checked before the ledger version is looked up
+    let resp = ctx
+        .expect_status_code(400)
+        .post(
+            &format!("{}?ledger_version={}", path, u64::MAX),
+            json!(too_many),
+        )
+        .await;
+    assert_eq!(resp["error_code"], "invalid_input");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_raw_table_item_with_key_hash() {
+    let mut context = new_test_context(current_function_name!());
+    let ctx = &mut context;
+    let mut acc = ctx.root_account().await;
+    make_test_tables(ctx, &mut acc).await;
+    let tt = ctx
+        .api_get_account_resource(
+            acc.address(),
+            &acc.address().to_hex_literal(),
+            "TableTestData",
+            "TestTables",
+        )
+        .await["data"]
+        .to_owned();
+    let handle: AccountAddress = tt["u64_table"]["handle"].as_str().unwrap().parse().unwrap();
+    let key = bcs::to_bytes(&1u64).unwrap();
+    let get = |query: &str| {
+        warp::test::request()
+            .method("POST")
+            .header("Accept", "application/x-bcs")
+            .path(&format!("/v1/tables/{}/raw_item{}", handle, query))
+            .json(&json!({ "key": format!("0x{}", hex::encode(&key)) }))
+    };
+
+    let resp = ctx.reply(get("")).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.body().as_ref(), bcs::to_bytes(&1u64).unwrap());
+
+    let resp = ctx.reply(get("?with_key_hash=true")).await;
+    assert_eq!(resp.status(), 200);
+    let with_key_hash: StateValueWithKeyHash = bcs::from_bytes(resp.body()).unwrap();
+    assert_eq!(
+        with_key_hash.value.bytes().as_ref(),
+        bcs::to_bytes(&1u64).unwrap()
+    );
+    assert_eq!(
+        with_key_hash.key_hash,
+        StateKey::table_item(&TableHandle(handle), &key).hash()
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_table_item_proof() {
+    let mut context = new_test_context(current_function_name!());
+    let ctx = &mut context;
+    let mut acc = ctx.root_account().await;
+    make_test_tables(ctx, &mut acc).await;
+    let tt = ctx
+        .api_get_account_resource(
+            acc.address(),
+            &acc.address().to_hex_literal(),
+            "TableTestData",
+            "TestTables",
+        )
+        .await["data"]
+        .to_owned();
+    let handle: AccountAddress = tt["u64_table"]["handle"].as_str().unwrap().parse().unwrap();
+    let version = ctx.get_latest_ledger_info().version();
+
+    let prove = |key: &str| {
+        warp::test::request()
+            .method("POST")
+            .header("Accept", "application/x-bcs")
+            .path(&format!("/v1{}/proof", get_table_item(handle)))
+            .json(&json!({
+                "key_type": "u64",
+                "value_type": "u64",
+                "key": key,
+            }))
+    };
+
+    let resp = context.reply(prove("1")).await;
+    assert_eq!(resp.status(), 200);
+    let payload: StateProofPayload = bcs::from_bytes(resp.body()).unwrap();
+
+    // The key is serialized the same way the table stores it
+    let state_key = StateKey::table_item(&TableHandle(handle), &bcs::to_bytes(&1u64).unwrap());
+    assert_eq!(payload.state_key().unwrap(), Some(state_key.clone()));
+    assert_eq!(payload.element_key, state_key.hash());
+    let state_value = context
+        .db
+        .get_state_value_by_version(&state_key, version)
+        .unwrap()
+        .unwrap();
+    assert_eq!(payload.element_hash, state_value.hash());
+    payload
+        .full_state_proof()
+        .unwrap()
+        .verify_by_hash(
+            payload
+                .transaction
+                .as_ref()
+                .unwrap()
+                .state_checkpoint_hash()
+                .unwrap(),
+            payload.element_key,
+            Some(payload.element_hash),
+        )
+        .unwrap();
+
+    let resp = context.reply(prove("999")).await;
+    assert_eq!(resp.status(), 404);
+    let error: Value = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(error["error_code"], "table_item_not_found");
+}
+
 fn get_account_resource(address: &str, struct_tag: &str) -> String {
     format!("/accounts/{}/resource/{}", address, struct_tag)
 }
@@ -316,6 +2440,10 @@ fn get_account_proof(address: &str) -> String {
     format!("/accounts/{}/proof", address)
 }
 
+fn get_account_proof_bundle(address: &str) -> String {
+    format!("/accounts/{}/proof/bundle", address)
+}
+
 fn get_account_resource_with_version(address: &str, struct_tag: &str, version: u64) -> String {
     format!(
         "/accounts/{}/resource/{}?ledger_version={}",
@@ -39,6 +39,7 @@ use aptos_types::{
     block_info::BlockInfo,
     block_metadata::BlockMetadata,
     chain_id::ChainId,
+    epoch_state::EpochState,
     ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
     transaction::{
         signature_verified_transaction::into_signature_verified_block, Transaction,
@@ -598,6 +599,31 @@ impl TestContext {
     }
 
     pub async fn commit_block(&mut self, signed_txns: &[SignedTransaction]) {
+        self.commit_block_with_next_epoch_state(signed_txns, None)
+            .await
+    }
+
+    /// Commits an empty block whose ledger info ends the current epoch, with the same validators
+    /// in the next one. Only the ledger history moves to the next epoch, the on-chain
+    /// configuration and the epochs of the block events stay the same.
+    pub async fn end_epoch(&mut self) {
+        let epoch = self
+            .context
+            .get_latest_ledger_info_with_signatures()
+            .unwrap()
+            .ledger_info()
+            .next_block_epoch();
+        let verifier = self.context.db.get_latest_epoch_state().unwrap().verifier;
+        let next_epoch_state = EpochState::new(epoch + 1, verifier);
+        self.commit_block_with_next_epoch_state(&[], Some(next_epoch_state))
+            .await
+    }
+
+    async fn commit_block_with_next_epoch_state(
+        &mut self,
+        signed_txns: &[SignedTransaction],
+        next_epoch_state: Option<EpochState>,
+    ) {
         let metadata = self.new_block_metadata();
         let timestamp = metadata.timestamp_usecs();
         let txns: Vec<Transaction> = std::iter::once(Transaction::BlockMetadata(metadata.clone()))
@@ -634,7 +660,12 @@ impl TestContext {
             .commit_blocks(
                 vec![metadata.id()],
                 // StateCheckpoint/BlockEpilogue is added on top of the input transactions.
-                self.new_ledger_info(&metadata, result.root_hash(), txns.len() + 1),
+                self.new_ledger_info(
+                    &metadata,
+                    result.root_hash(),
+                    txns.len() + 1,
+                    next_epoch_state,
+                ),
             )
             .unwrap();
 
@@ -1002,6 +1033,7 @@ impl TestContext {
         metadata: &BlockMetadata,
         root_hash: HashValue,
         block_size: usize,
+        next_epoch_state: Option<EpochState>,
     ) -> LedgerInfoWithSignatures {
         let parent = self
             .context
@@ -1017,7 +1049,7 @@ impl TestContext {
                 root_hash,
                 version,
                 metadata.timestamp_usecs(),
-                None,
+                next_epoch_state,
             ),
             HashValue::zero(),
         );
@@ -2,7 +2,7 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{HexEncodedBytes, U64};
+use crate::{HexEncodedBytes, MoveResource, U64};
 use aptos_types::account_config::AccountResource;
 use poem_openapi::Object;
 use serde::{Deserialize, Serialize};
@@ -25,3 +25,18 @@ impl From<AccountResource> for AccountData {
         }
     }
 }
+
+/// Resources of an account read at a single ledger version, in the order they were requested
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct AccountResourcesAtVersion {
+    /// Ledger version all the resources were read at
+    pub version: U64,
+    pub resources: Vec<MoveResource>,
+}
+
+/// BCS form of [`AccountResourcesAtVersion`], with the BCS encoded resources
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RawAccountResourcesAtVersion {
+    pub version: u64,
+    pub resources: Vec<Vec<u8>>,
+}
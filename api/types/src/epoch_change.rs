@@ -1,4 +1,9 @@
-use crate::ledger_info::LedgerInfoWithSignatures;
+use crate::{
+    ledger_info::{LedgerInfoWithSignatures, ValidatorConsensusInfo},
+    HashValue, U64,
+};
+use aptos_crypto::hash::CryptoHash;
+use aptos_types::validator_verifier::ValidatorVerifier as InternValidatorVerifier;
 use poem_openapi::Object as PoemObject;
 use serde::{Deserialize, Serialize};
 
@@ -20,3 +25,296 @@ impl From<aptos_types::epoch_change::EpochChangeProof> for EpochChangeProof {
         }
     }
 }
+
+/// The changes to the validator set between two consecutive epochs
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize, PoemObject)]
+pub struct ValidatorSetDiff {
+    /// Validators in the new set that were not in the old one
+    pub added: Vec<ValidatorConsensusInfo>,
+    /// Validators in the old set that are not in the new one
+    pub removed: Vec<ValidatorConsensusInfo>,
+    /// Validators in both sets whose consensus key or voting power changed, as they appear in the
+    /// new set
+    pub updated: Vec<ValidatorConsensusInfo>,
+    /// Validators left out of the lists above when the diff is truncated
+    pub omitted: Option<OmittedValidators>,
+}
+
+/// Aggregate of the validators left out of a truncated list
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize, PoemObject)]
+pub struct OmittedValidators {
+    /// Number of validators left out
+    pub count: U64,
+    /// Total voting power of the validators left out
+    pub voting_power: U64,
+}
+
+impl ValidatorSetDiff {
+    pub fn new(old: &InternValidatorVerifier, new: &InternValidatorVerifier) -> Self {
+        let mut diff = Self::default();
+        for info in new.validator_infos() {
+            match old
+                .validator_infos()
+                .iter()
+                .find(|old_info| old_info.address == info.address)
+            {
+                None => diff.added.push(info.clone().into()),
+                Some(old_info) if old_info != info => diff.updated.push(info.clone().into()),
+                Some(_) => {},
+            }
+        }
+        for info in old.validator_infos() {
+            if new.get_voting_power(&info.address).is_none() {
+                diff.removed.push(info.clone().into());
+            }
+        }
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.updated.is_empty()
+    }
+
+    /// Keeps only the `top_n` validators with the most voting power in each list, sorting them by
+    /// decreasing voting power, and aggregates the others into `omitted`.
+    pub fn truncate(&mut self, top_n: usize) {
+        let mut omitted = self.omitted.take().unwrap_or_default();
+        for validators in [&mut self.added, &mut self.removed, &mut self.updated] {
+            validators.sort_by(|a, b| b.voting_power.0.cmp(&a.voting_power.0));
+            for validator in validators.split_off(top_n.min(validators.len())) {
+                omitted.count.0 += 1;
+                omitted.voting_power.0 = omitted
+                    .voting_power
+                    .0
+                    .saturating_add(validator.voting_power.0);
+            }
+        }
+        self.omitted = (omitted.count.0 > 0).then_some(omitted);
+    }
+}
+
+/// A lightweight view of an epoch change, for monitoring the validator set without ratcheting
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, PoemObject)]
+pub struct EpochChangeSummary {
+    /// The epoch the validator set applies to
+    pub epoch: U64,
+    /// Hash of the signed ledger info that ended the previous epoch
+    pub ledger_info_hash: HashValue,
+    /// Validator set changes relative to the previous epoch
+    pub diff: ValidatorSetDiff,
+}
+
+impl EpochChangeSummary {
+    /// Summarizes every consecutive pair of epoch-ending ledger infos, skipping the first one
+    /// which only establishes the starting validator set.
+    pub fn from_ledger_infos(
+        ledger_info_with_sigs: &[aptos_types::ledger_info::LedgerInfoWithSignatures],
+    ) -> anyhow::Result<Vec<Self>> {
+        let epoch_states = ledger_info_with_sigs
+            .iter()
+            .map(|li| {
+                li.ledger_info().next_epoch_state().ok_or_else(|| {
+                    anyhow::format_err!(
+                        "LedgerInfo ending epoch {} doesn't carry a next EpochState",
+                        li.ledger_info().epoch()
+                    )
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        Ok(epoch_states
+            .windows(2)
+            .zip(ledger_info_with_sigs.iter().skip(1))
+            .map(|(pair, li)| Self {
+                epoch: pair[1].epoch.into(),
+                ledger_info_hash: li.ledger_info().hash().into(),
+                diff: ValidatorSetDiff::new(&pair[0].verifier, &pair[1].verifier),
+            })
+            .collect())
+    }
+}
+
+/// Summaries of a range of epoch changes, cut short if the range spans more epochs than the node
+/// serves at once
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct EpochChangeSummaryPage {
+    pub summaries: Vec<EpochChangeSummary>,
+    /// Whether epoch changes of the requested range were left out
+    pub more: bool,
+    /// Epoch to request the rest of the range from, if `more` is set
+    pub next_epoch: Option<u64>,
+}
+
+/// How far the chain has progressed through the current epoch
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, PoemObject)]
+pub struct EpochProgress {
+    /// The current epoch
+    pub epoch: U64,
+    /// The first version of the current epoch
+    pub start_version: U64,
+    /// The latest committed version
+    pub current_version: U64,
+    /// Best-effort estimate of the versions left before the epoch ends
+    ///
+    /// This extrapolates the rate of versions committed so far in the epoch to the rest of the
+    /// epoch interval. It is only a hint: epochs can end early, e.g. on a governance
+    /// reconfiguration, or late, and the rate of transactions varies.
+    pub estimated_versions_remaining: Option<U64>,
+}
+
+impl EpochProgress {
+    /// Timestamps and the epoch interval are in microseconds. Without an epoch interval, or before
+    /// any time has passed in the epoch, no estimate is made.
+    pub fn new(
+        epoch: u64,
+        start_version: u64,
+        current_version: u64,
+        epoch_start_usecs: u64,
+        current_usecs: u64,
+        epoch_interval_usecs: Option<u64>,
+    ) -> Self {
+        let elapsed_usecs = current_usecs.saturating_sub(epoch_start_usecs);
+        let estimated_versions_remaining =
+            epoch_interval_usecs
+                .filter(|_| elapsed_usecs > 0)
+                .map(|interval_usecs| {
+                    let remaining_usecs = interval_usecs.saturating_sub(elapsed_usecs) as u128;
+                    let versions = current_version.saturating_sub(start_version) as u128 + 1;
+                    let estimate = versions * remaining_usecs / elapsed_usecs as u128;
+                    U64::from(u64::try_from(estimate).unwrap_or(u64::MAX))
+                });
+        Self {
+            epoch: epoch.into(),
+            start_version: start_version.into(),
+            current_version: current_version.into(),
+            estimated_versions_remaining,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EpochChangeSummary, EpochProgress, OmittedValidators, ValidatorSetDiff};
+    use crate::{
+        ledger_info::ValidatorConsensusInfo as ValidatorConsensusInfoView,
+        test_utils::TestEpochChain, HashValue, U64,
+    };
+    use aptos_crypto::hash::CryptoHash;
+    use aptos_types::validator_verifier::{ValidatorConsensusInfo, ValidatorVerifier};
+
+    #[test]
+    fn test_summary_matches_full_proof() {
+        let chain = TestEpochChain::new(1, 3);
+        let ledger_infos = &chain.proof.ledger_info_with_sigs;
+
+        let summaries = EpochChangeSummary::from_ledger_infos(ledger_infos).unwrap();
+        assert_eq!(summaries.len(), ledger_infos.len() - 1);
+        for (summary, pair) in summaries.iter().zip(ledger_infos.windows(2)) {
+            let old = pair[0].ledger_info().next_epoch_state().unwrap();
+            let new = pair[1].ledger_info().next_epoch_state().unwrap();
+            assert_eq!(summary.epoch, U64::from(new.epoch));
+            assert_eq!(
+                summary.ledger_info_hash,
+                HashValue::from(pair[1].ledger_info().hash())
+            );
+            assert_eq!(
+                summary.diff,
+                ValidatorSetDiff::new(&old.verifier, &new.verifier)
+            );
+            // Every test epoch is run by a single, fresh validator
+            assert_eq!(summary.diff.added.len(), 1);
+            assert_eq!(summary.diff.removed.len(), 1);
+            assert!(summary.diff.updated.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_validator_set_diff() {
+        let (signer, verifier) = crate::test_utils::validator(1);
+        assert!(ValidatorSetDiff::new(&verifier, &verifier).is_empty());
+
+        let reweighted = ValidatorVerifier::new(vec![ValidatorConsensusInfo::new(
+            signer.author(),
+            signer.public_key(),
+            10,
+        )]);
+        let diff = ValidatorSetDiff::new(&verifier, &reweighted);
+        assert!(diff.added.is_empty());
+        assert!(diff.removed.is_empty());
+        assert_eq!(diff.updated, vec![ValidatorConsensusInfoView::from(
+            reweighted.validator_infos()[0].clone()
+        )]);
+    }
+
+    #[test]
+    fn test_validator_set_diff_truncate() {
+        let validator = |seed: u64, voting_power| {
+            let (signer, _) = crate::test_utils::validator(seed);
+            ValidatorConsensusInfo::new(signer.author(), signer.public_key(), voting_power)
+        };
+        let old = ValidatorVerifier::new(vec![validator(1, 5), validator(2, 7)]);
+        let new = ValidatorVerifier::new(vec![
+            validator(3, 1),
+            validator(4, 30),
+            validator(5, 20),
+            validator(6, 10),
+        ]);
+        let mut diff = ValidatorSetDiff::new(&old, &new);
+        assert_eq!(diff.added.len(), 4);
+        assert_eq!(diff.removed.len(), 2);
+
+        diff.truncate(2);
+        let voting_powers = |validators: &[ValidatorConsensusInfoView]| {
+            validators
+                .iter()
+                .map(|validator| validator.voting_power.0)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(voting_powers(&diff.added), vec![30, 20]);
+        assert_eq!(voting_powers(&diff.removed), vec![7, 5]);
+        assert_eq!(
+            diff.omitted,
+            Some(OmittedValidators {
+                count: 2u64.into(),
+                voting_power: 11u64.into(),
+            })
+        );
+
+        // Truncating again only aggregates the newly omitted validators
+        diff.truncate(1);
+        assert_eq!(voting_powers(&diff.added), vec![30]);
+        assert_eq!(voting_powers(&diff.removed), vec![7]);
+        assert_eq!(
+            diff.omitted,
+            Some(OmittedValidators {
+                count: 4u64.into(),
+                voting_power: 36u64.into(),
+            })
+        );
+
+        // Nothing is omitted from lists already short enough
+        let mut untouched = ValidatorSetDiff::new(&old, &new);
+        untouched.truncate(4);
+        assert_eq!(untouched.omitted, None);
+    }
+
+    #[test]
+    fn test_epoch_progress_estimate() {
+        // 100 versions in the first quarter of a 4 second epoch
+        let progress = EpochProgress::new(5, 1_000, 1_099, 0, 1_000_000, Some(4_000_000));
+        assert_eq!(
+            progress.estimated_versions_remaining,
+            Some(U64::from(300u64))
+        );
+
+        // Past the interval, the epoch is expected to end any moment
+        let progress = EpochProgress::new(5, 1_000, 1_099, 0, 5_000_000, Some(4_000_000));
+        assert_eq!(progress.estimated_versions_remaining, Some(U64::from(0u64)));
+
+        // No estimate without an interval, or before any time has passed
+        let progress = EpochProgress::new(5, 1_000, 1_099, 0, 1_000_000, None);
+        assert_eq!(progress.estimated_versions_remaining, None);
+        let progress = EpochProgress::new(5, 1_000, 1_000, 1_000_000, 1_000_000, Some(4_000_000));
+        assert_eq!(progress.estimated_versions_remaining, None);
+    }
+}
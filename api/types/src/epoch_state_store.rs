@@ -0,0 +1,313 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_crypto::HashValue;
+use aptos_types::epoch_state::EpochState;
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+
+/// Default number of epochs kept by an `InMemoryEpochStateStore`
+pub const DEFAULT_EPOCH_STATE_STORE_CAPACITY: usize = 100;
+
+/// A cache of epoch states a client has already verified.
+///
+/// While ratcheting through an `EpochChangeProof`, the verifier consults the store before checking
+/// the signatures of an epoch-ending ledger info, and populates it with every newly verified
+/// `EpochState`. A long-running client can then skip re-verifying epochs it already trusts.
+pub trait EpochStateStore {
+    /// Returns the verified epoch state for `epoch`, if present
+    fn get(&self, epoch: u64) -> Option<EpochState>;
+
+    /// Records a verified epoch state
+    fn insert(&mut self, epoch_state: EpochState);
+
+    /// Whether exactly this epoch state has already been verified
+    fn contains(&self, epoch_state: &EpochState) -> bool {
+        self.get(epoch_state.epoch).as_ref() == Some(epoch_state)
+    }
+}
+
+/// An `EpochStateStore` kept in memory, holding at most `capacity` epochs.
+///
+/// Ratcheting only ever moves forward, so once the store is full the oldest epoch is evicted.
+#[derive(Clone, Debug)]
+pub struct InMemoryEpochStateStore {
+    capacity: usize,
+    epoch_states: BTreeMap<u64, EpochState>,
+}
+
+impl InMemoryEpochStateStore {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            epoch_states: BTreeMap::new(),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.epoch_states.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.epoch_states.is_empty()
+    }
+
+    /// The most recent verified epoch state, if any
+    pub fn latest(&self) -> Option<&EpochState> {
+        self.epoch_states.values().next_back()
+    }
+}
+
+impl Default for InMemoryEpochStateStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_EPOCH_STATE_STORE_CAPACITY)
+    }
+}
+
+impl EpochStateStore for InMemoryEpochStateStore {
+    fn get(&self, epoch: u64) -> Option<EpochState> {
+        self.epoch_states.get(&epoch).cloned()
+    }
+
+    fn insert(&mut self, epoch_state: EpochState) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.epoch_states.insert(epoch_state.epoch, epoch_state);
+        while self.epoch_states.len() > self.capacity {
+            self.epoch_states.pop_first();
+        }
+    }
+}
+
+/// Error loading or saving a `FileEpochStateStore`
+#[derive(Debug, Error)]
+pub enum EpochStateStoreError {
+    #[error("Failed to access epoch state store file {path}: {error}")]
+    Io { path: PathBuf, error: io::Error },
+    #[error("Epoch state store file {path} is corrupted: {reason}")]
+    Corrupted { path: PathBuf, reason: String },
+}
+
+/// An `EpochStateStore` persisted to a file, so verified epochs survive a client restart.
+///
+/// The file holds the SHA3-256 checksum of the serialized epoch states followed by the BCS-encoded
+/// epoch states themselves. The checksum only detects truncated or corrupted files: the file must
+/// be protected like any other trusted client state, as whoever can write it decides which
+/// validator sets are trusted.
+///
+/// Inserts are kept in memory until [`FileEpochStateStore::save`] is called.
+#[derive(Clone, Debug)]
+pub struct FileEpochStateStore {
+    path: PathBuf,
+    inner: InMemoryEpochStateStore,
+}
+
+impl FileEpochStateStore {
+    /// Loads the store persisted at `path`, or starts an empty one if the file doesn't exist yet.
+    pub fn open(path: impl Into<PathBuf>, capacity: usize) -> Result<Self, EpochStateStoreError> {
+        let path = path.into();
+        let mut inner = InMemoryEpochStateStore::new(capacity);
+        let bytes = match fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                return Ok(Self { path, inner })
+            },
+            Err(error) => return Err(EpochStateStoreError::Io { path, error }),
+        };
+
+        if bytes.len() < HashValue::LENGTH {
+            return Err(EpochStateStoreError::Corrupted {
+                path,
+                reason: "file is shorter than its checksum".to_string(),
+            });
+        }
+        let (checksum, serialized) = bytes.split_at(HashValue::LENGTH);
+        if checksum != HashValue::sha3_256_of(serialized).as_slice() {
+            return Err(EpochStateStoreError::Corrupted {
+                path,
+                reason: "checksum mismatch".to_string(),
+            });
+        }
+        let epoch_states: Vec<EpochState> =
+            bcs::from_bytes(serialized).map_err(|error| EpochStateStoreError::Corrupted {
+                path: path.clone(),
+                reason: error.to_string(),
+            })?;
+        for epoch_state in epoch_states {
+            inner.insert(epoch_state);
+        }
+        Ok(Self { path, inner })
+    }
+
+    /// Writes the store to its file, replacing the previous contents atomically.
+    pub fn save(&self) -> Result<(), EpochStateStoreError> {
+        let epoch_states: Vec<&EpochState> = self.inner.epoch_states.values().collect();
+        let serialized = bcs::to_bytes(&epoch_states).expect("Epoch states are serializable");
+        let mut bytes = HashValue::sha3_256_of(&serialized).to_vec();
+        bytes.extend(serialized);
+
+        // Write to a sibling file first, so a crash mid-write never leaves a truncated store
+        let tmp_path = self.path.with_extension("tmp");
+        fs::write(&tmp_path, bytes)
+            .and_then(|()| fs::rename(&tmp_path, &self.path))
+            .map_err(|error| EpochStateStoreError::Io {
+                path: self.path.clone(),
+                error,
+            })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// The most recent verified epoch state, if any
+    pub fn latest(&self) -> Option<&EpochState> {
+        self.inner.latest()
+    }
+}
+
+impl EpochStateStore for FileEpochStateStore {
+    fn get(&self, epoch: u64) -> Option<EpochState> {
+        self.inner.get(epoch)
+    }
+
+    fn insert(&mut self, epoch_state: EpochState) {
+        self.inner.insert(epoch_state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        EpochStateStore, EpochStateStoreError, FileEpochStateStore, InMemoryEpochStateStore,
+        DEFAULT_EPOCH_STATE_STORE_CAPACITY,
+    };
+    use crate::{
+        test_utils::TestEpochChain,
+        verify::{ratchet_epoch_state, VerifyError},
+    };
+    use aptos_temppath::TempPath;
+    use aptos_types::{
+        aggregate_signature::AggregateSignature, epoch_change::EpochChangeProof,
+        epoch_state::EpochState, ledger_info::LedgerInfoWithSignatures,
+        validator_verifier::ValidatorVerifier,
+    };
+    use std::fs;
+
+    fn epoch_state(epoch: u64) -> EpochState {
+        EpochState::new(epoch, ValidatorVerifier::new(vec![]))
+    }
+
+    #[test]
+    fn test_insert_and_get() {
+        let mut store = InMemoryEpochStateStore::new(2);
+        assert!(store.is_empty());
+
+        store.insert(epoch_state(1));
+        assert_eq!(store.get(1), Some(epoch_state(1)));
+        assert!(store.contains(&epoch_state(1)));
+        assert_eq!(store.get(2), None);
+    }
+
+    #[test]
+    fn test_evicts_oldest_epoch() {
+        let mut store = InMemoryEpochStateStore::new(2);
+        for epoch in 1..=3 {
+            store.insert(epoch_state(epoch));
+        }
+
+        assert_eq!(store.len(), 2);
+        assert_eq!(store.get(1), None);
+        assert_eq!(store.latest(), Some(&epoch_state(3)));
+    }
+
+    #[test]
+    fn test_zero_capacity_stores_nothing() {
+        let mut store = InMemoryEpochStateStore::new(0);
+        store.insert(epoch_state(1));
+        assert!(store.is_empty());
+    }
+
+    #[test]
+    fn test_file_store_survives_restart() {
+        let path = TempPath::new();
+        let chain = TestEpochChain::new(1, 3);
+
+        let mut store =
+            FileEpochStateStore::open(path.path(), DEFAULT_EPOCH_STATE_STORE_CAPACITY).unwrap();
+        assert!(store.is_empty());
+        ratchet_epoch_state(&chain.trusted, &chain.proof, &mut store).unwrap();
+        store.save().unwrap();
+        drop(store);
+
+        // After the restart every epoch change is cached, so the signatures are never looked at
+        let mut store =
+            FileEpochStateStore::open(path.path(), DEFAULT_EPOCH_STATE_STORE_CAPACITY).unwrap();
+        assert_eq!(store.len(), 3);
+        assert_eq!(store.latest(), Some(&chain.latest));
+        let unsigned = EpochChangeProof::new(
+            chain
+                .proof
+                .ledger_info_with_sigs
+                .iter()
+                .map(|li| {
+                    LedgerInfoWithSignatures::new(
+                        li.ledger_info().clone(),
+                        AggregateSignature::empty(),
+                    )
+                })
+                .collect(),
+            chain.proof.more,
+        );
+        assert_eq!(
+            ratchet_epoch_state(&chain.trusted, &unsigned, &mut store).unwrap(),
+            chain.latest
+        );
+
+        // A fresh store still checks them
+        let mut empty_store = InMemoryEpochStateStore::default();
+        assert!(matches!(
+            ratchet_epoch_state(&chain.trusted, &unsigned, &mut empty_store),
+            Err(VerifyError::BitmaskTooShort { epoch: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_file_store_rejects_corrupted_file() {
+        let path = TempPath::new();
+        let mut store = FileEpochStateStore::open(path.path(), 2).unwrap();
+        store.insert(epoch_state(1));
+        store.save().unwrap();
+
+        let mut bytes = fs::read(path.path()).unwrap();
+        *bytes.last_mut().unwrap() ^= 1;
+        fs::write(path.path(), &bytes).unwrap();
+        assert!(matches!(
+            FileEpochStateStore::open(path.path(), 2),
+            Err(EpochStateStoreError::Corrupted { .. })
+        ));
+
+        fs::write(path.path(), &bytes[..4]).unwrap();
+        assert!(matches!(
+            FileEpochStateStore::open(path.path(), 2),
+            Err(EpochStateStoreError::Corrupted { .. })
+        ));
+    }
+}
@@ -81,6 +81,8 @@ pub enum AptosErrorCode {
     BlockNotFound = 108,
     ///  StateValue not found at the requested version
     StateValueNotFound = 109,
+    /// Event not found at the requested version
+    EventNotFound = 110,
 
     /// Ledger version is pruned
     VersionPruned = 200,
@@ -110,6 +112,12 @@ pub enum AptosErrorCode {
     BcsNotSupported = 602,
     /// API Disabled
     ApiDisabled = 603,
+    /// The request is missing valid credentials for a protected API
+    Unauthorized = 604,
+    /// A resource was found, but couldn't be deserialized as the requested type
+    ///
+    /// Usually means the layout of the type changed since the resource was written
+    ResourceDeserializationError = 605,
 }
 
 impl AptosErrorCode {
@@ -2,8 +2,10 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::HashValue;
-use crate::U64;
+use crate::{Address, HashValue, HexEncodedBytes, U64};
+use anyhow::Context;
+use aptos_bitvec::BitVec;
+use aptos_crypto::{bls12381, hash::CryptoHash};
 use aptos_types::chain_id::ChainId;
 use poem_openapi::{Enum, Object as PoemObject};
 use serde::{Deserialize, Serialize};
@@ -56,6 +58,14 @@ impl LedgerInfo {
     pub fn timestamp(&self) -> u64 {
         self.ledger_timestamp.into()
     }
+
+    pub fn block_height(&self) -> u64 {
+        self.block_height.into()
+    }
+
+    pub fn oldest_block_height(&self) -> u64 {
+        self.oldest_block_height.into()
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, PoemObject)]
@@ -90,6 +100,38 @@ pub struct LedgerInfoWithV0 {
     signatures: AggregateSignature,
 }
 
+impl LedgerInfoWithV0 {
+    pub fn ledger_info(&self) -> &CompleteLedgerInfo {
+        &self.ledger_info
+    }
+
+    /// Number of validators whose signatures are aggregated
+    pub fn num_signers(&self) -> usize {
+        BitVec::from(self.signatures.validator_bitmask.clone()).count_ones() as usize
+    }
+
+    /// Summed voting power of the signers, found by their index in `verifier`. Fails if a signer
+    /// isn't one of its validators.
+    pub fn signers_voting_power(&self, verifier: &ValidatorVerifier) -> anyhow::Result<u128> {
+        BitVec::from(self.signatures.validator_bitmask.clone())
+            .iter_ones()
+            .map(|index| {
+                verifier
+                    .validator_infos
+                    .get(index)
+                    .map(|info| info.voting_power.0 as u128)
+                    .ok_or_else(|| anyhow::anyhow!("Signer {} is not a validator", index))
+            })
+            .sum()
+    }
+
+    /// Whether the signers hold more than two thirds of the voting power of `verifier`, as
+    /// required for the ledger info to be valid. The signatures themselves aren't verified.
+    pub fn has_quorum(&self, verifier: &ValidatorVerifier) -> anyhow::Result<bool> {
+        Ok(self.signers_voting_power(verifier)? >= verifier.quorum_voting_power())
+    }
+}
+
 impl From<aptos_types::ledger_info::LedgerInfoWithV0> for LedgerInfoWithV0 {
     fn from(value: aptos_types::ledger_info::LedgerInfoWithV0) -> Self {
         Self {
@@ -108,6 +150,15 @@ pub struct CompleteLedgerInfo {
     consensus_data_hash: HashValue,
 }
 
+impl CompleteLedgerInfo {
+    /// Hash of the ledger info the validators sign, to verify the aggregate signature against.
+    /// Fails if a validator of the next epoch state has an invalid public key.
+    pub fn signing_hash(&self) -> anyhow::Result<HashValue> {
+        let ledger_info = aptos_types::ledger_info::LedgerInfo::try_from(self.clone())?;
+        Ok(CryptoHash::hash(&ledger_info).into())
+    }
+}
+
 impl From<aptos_types::ledger_info::LedgerInfo> for CompleteLedgerInfo {
     fn from(value: aptos_types::ledger_info::LedgerInfo) -> Self {
         Self {
@@ -117,6 +168,18 @@ impl From<aptos_types::ledger_info::LedgerInfo> for CompleteLedgerInfo {
     }
 }
 
+/// Rebuilds the ledger info the view was built from, to hash it the way the validators did
+impl TryFrom<CompleteLedgerInfo> for aptos_types::ledger_info::LedgerInfo {
+    type Error = anyhow::Error;
+
+    fn try_from(value: CompleteLedgerInfo) -> anyhow::Result<Self> {
+        Ok(Self::new(
+            value.commit_info.try_into()?,
+            value.consensus_data_hash.into(),
+        ))
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, PoemObject)]
 pub struct BlockInfo {
     /// The epoch to which the block belongs.
@@ -149,6 +212,22 @@ impl From<aptos_types::block_info::BlockInfo> for BlockInfo {
     }
 }
 
+impl TryFrom<BlockInfo> for aptos_types::block_info::BlockInfo {
+    type Error = anyhow::Error;
+
+    fn try_from(value: BlockInfo) -> anyhow::Result<Self> {
+        Ok(Self::new(
+            value.epoch.0,
+            value.round.0,
+            value.id.into(),
+            value.executed_state_id.into(),
+            value.version.0,
+            value.timestamp_usecs.0,
+            value.next_epoch_state.map(TryInto::try_into).transpose()?,
+        ))
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, PoemObject)]
 pub struct EpochState {
     pub epoch: U64,
@@ -170,6 +249,14 @@ impl From<aptos_types::epoch_state::EpochState> for EpochState {
     }
 }
 
+impl TryFrom<EpochState> for aptos_types::epoch_state::EpochState {
+    type Error = anyhow::Error;
+
+    fn try_from(value: EpochState) -> anyhow::Result<Self> {
+        Ok(Self::new(value.epoch.0, value.verifier.try_into()?))
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, PoemObject)]
 pub struct ValidatorVerifier {
     /// A vector of each validator's on-chain account address to its pubkeys and voting power.
@@ -188,23 +275,129 @@ impl From<aptos_types::validator_verifier::ValidatorVerifier> for ValidatorVerif
     }
 }
 
+impl ValidatorVerifier {
+    pub fn total_voting_power(&self) -> u128 {
+        self.validator_infos
+            .iter()
+            .map(|info| info.voting_power.0 as u128)
+            .sum()
+    }
+
+    /// Voting power needed for a quorum, computed the same way as
+    /// [`aptos_types::validator_verifier::ValidatorVerifier::new`]
+    pub fn quorum_voting_power(&self) -> u128 {
+        if self.validator_infos.is_empty() {
+            0
+        } else {
+            self.total_voting_power() * 2 / 3 + 1
+        }
+    }
+}
+
+/// Rebuilds the verifier the view was built from, to check signatures against it
+impl TryFrom<ValidatorVerifier> for aptos_types::validator_verifier::ValidatorVerifier {
+    type Error = anyhow::Error;
+
+    fn try_from(value: ValidatorVerifier) -> anyhow::Result<Self> {
+        Ok(Self::new(
+            value
+                .validator_infos
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<anyhow::Result<_>>()?,
+        ))
+    }
+}
+
+/// The address and public key are hex encoded in JSON, and plain bytes in BCS
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, PoemObject)]
 pub struct ValidatorConsensusInfo {
-    pub address: Vec<u8>,
-    pub public_key: Vec<u8>,
+    #[serde(with = "address_bytes")]
+    pub address: Address,
+    #[serde(with = "hex_encoded_bytes")]
+    pub public_key: HexEncodedBytes,
     pub voting_power: U64,
 }
 
 impl From<aptos_types::validator_verifier::ValidatorConsensusInfo> for ValidatorConsensusInfo {
     fn from(value: aptos_types::validator_verifier::ValidatorConsensusInfo) -> Self {
         Self {
-            address: value.address.to_vec(),
-            public_key: value.public_key().to_bytes().to_vec(),
+            address: value.address.into(),
+            public_key: value.public_key().to_bytes().to_vec().into(),
             voting_power: value.voting_power.into(),
         }
     }
 }
 
+impl TryFrom<ValidatorConsensusInfo> for aptos_types::validator_verifier::ValidatorConsensusInfo {
+    type Error = anyhow::Error;
+
+    fn try_from(value: ValidatorConsensusInfo) -> anyhow::Result<Self> {
+        let public_key = bls12381::PublicKey::try_from(value.public_key.inner())
+            .with_context(|| format!("Invalid public key of validator {}", value.address))?;
+        Ok(Self::new(
+            *value.address.inner(),
+            public_key,
+            value.voting_power.0,
+        ))
+    }
+}
+
+/// Serializes an [`Address`] as a string in human readable formats, and as the bytes of the
+/// address otherwise, the same way as a `Vec<u8>`
+mod address_bytes {
+    use crate::Address;
+    use aptos_types::account_address::AccountAddress;
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(address: &Address, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            address.serialize(serializer)
+        } else {
+            address.inner().to_vec().serialize(serializer)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Address, D::Error> {
+        if deserializer.is_human_readable() {
+            Address::deserialize(deserializer)
+        } else {
+            let bytes = Vec::<u8>::deserialize(deserializer)?;
+            AccountAddress::from_bytes(bytes)
+                .map(Address::from)
+                .map_err(D::Error::custom)
+        }
+    }
+}
+
+/// Serializes [`HexEncodedBytes`] as a string in human readable formats, and as a `Vec<u8>`
+/// otherwise
+mod hex_encoded_bytes {
+    use crate::HexEncodedBytes;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        bytes: &HexEncodedBytes,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            bytes.serialize(serializer)
+        } else {
+            bytes.0.serialize(serializer)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<HexEncodedBytes, D::Error> {
+        if deserializer.is_human_readable() {
+            HexEncodedBytes::deserialize(deserializer)
+        } else {
+            Vec::<u8>::deserialize(deserializer).map(HexEncodedBytes)
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, PoemObject)]
 pub struct AggregateSignature {
     validator_bitmask: Vec<u8>,
@@ -213,10 +406,235 @@ pub struct AggregateSignature {
 
 impl From<aptos_types::aggregate_signature::AggregateSignature> for AggregateSignature {
     fn from(sig: aptos_types::aggregate_signature::AggregateSignature) -> Self {
-        dbg!(&sig);
         Self {
             validator_bitmask: sig.get_signers_bitvec().clone().into(),
             sig: sig.sig().clone().map(|sig| sig.to_bytes().to_vec()),
         }
     }
 }
+
+impl TryFrom<AggregateSignature> for aptos_types::aggregate_signature::AggregateSignature {
+    type Error = anyhow::Error;
+
+    fn try_from(value: AggregateSignature) -> anyhow::Result<Self> {
+        let sig = value
+            .sig
+            .map(|sig| bls12381::Signature::try_from(sig.as_slice()))
+            .transpose()
+            .context("Invalid aggregate signature")?;
+        Ok(Self::new(BitVec::from(value.validator_bitmask), sig))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        AggregateSignature, CompleteLedgerInfo, LedgerInfo, LedgerInfoWithV0,
+        ValidatorConsensusInfo, ValidatorVerifier,
+    };
+    use crate::{
+        test_utils::{ledger_info, sign, validator},
+        HexEncodedBytes, U64,
+    };
+    use aptos_crypto::hash::CryptoHash;
+    use aptos_types::{
+        aggregate_signature::PartialSignatures, chain_id::ChainId, epoch_state::EpochState,
+    };
+    use serde::Serialize;
+    use serde_json::json;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_ledger_info_accessors() {
+        let (signer, verifier) = validator(1);
+        let signed = sign(&signer, &verifier, ledger_info(1, 10, None));
+        let ledger_info = LedgerInfo::new(&ChainId::test(), &signed, 3, 2, 5);
+        assert_eq!(ledger_info.epoch(), 1);
+        assert_eq!(ledger_info.version(), 10);
+        assert_eq!(ledger_info.oldest_version(), 3);
+        assert_eq!(ledger_info.block_height(), 5);
+        assert_eq!(ledger_info.oldest_block_height(), 2);
+        assert_eq!(
+            ledger_info.timestamp(),
+            signed.ledger_info().timestamp_usecs()
+        );
+    }
+
+    #[test]
+    fn test_aggregate_signature_from() {
+        let (signer, verifier) = validator(1);
+        let signed = sign(&signer, &verifier, ledger_info(1, 1, None));
+        let signatures = signed.signatures().clone();
+        assert_eq!(
+            AggregateSignature::from(signatures.clone()),
+            AggregateSignature {
+                validator_bitmask: signatures.get_signers_bitvec().clone().into(),
+                sig: signatures.sig().as_ref().map(|sig| sig.to_bytes().to_vec()),
+            }
+        );
+
+        assert_eq!(
+            AggregateSignature::from(aptos_types::aggregate_signature::AggregateSignature::empty()),
+            AggregateSignature {
+                validator_bitmask: vec![],
+                sig: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_validator_consensus_info_serde() {
+        let (signer, _) = validator(1);
+        let public_key = signer.public_key().to_bytes().to_vec();
+        let info = ValidatorConsensusInfo::from(
+            aptos_types::validator_verifier::ValidatorConsensusInfo::new(
+                signer.author(),
+                signer.public_key(),
+                7,
+            ),
+        );
+
+        let json = serde_json::to_value(&info).unwrap();
+        assert_eq!(
+            json,
+            json!({
+                "address": signer.author().to_hex_literal(),
+                "public_key": format!("0x{}", hex::encode(&public_key)),
+                "voting_power": "7",
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<ValidatorConsensusInfo>(json).unwrap(),
+            info
+        );
+
+        // The BCS layout is the one of the byte vectors the fields used to be
+        #[derive(Serialize)]
+        struct BytesValidatorConsensusInfo {
+            address: Vec<u8>,
+            public_key: Vec<u8>,
+            voting_power: U64,
+        }
+        let bytes = bcs::to_bytes(&info).unwrap();
+        assert_eq!(
+            bytes,
+            bcs::to_bytes(&BytesValidatorConsensusInfo {
+                address: signer.author().to_vec(),
+                public_key,
+                voting_power: 7.into(),
+            })
+            .unwrap()
+        );
+        assert_eq!(
+            bcs::from_bytes::<ValidatorConsensusInfo>(&bytes).unwrap(),
+            info
+        );
+    }
+
+    #[test]
+    fn test_validator_verifier_round_trip() {
+        let (signer, verifier) = validator(1);
+        let signed = sign(&signer, &verifier, ledger_info(1, 1, None));
+
+        // Only what is shipped in payloads is used to verify the signatures
+        let view: ValidatorVerifier = serde_json::from_value(
+            serde_json::to_value(ValidatorVerifier::from(verifier.clone())).unwrap(),
+        )
+        .unwrap();
+        let signatures: AggregateSignature = bcs::from_bytes(
+            &bcs::to_bytes(&AggregateSignature::from(signed.signatures().clone())).unwrap(),
+        )
+        .unwrap();
+        let rebuilt = aptos_types::validator_verifier::ValidatorVerifier::try_from(view).unwrap();
+        assert_eq!(rebuilt, verifier);
+        let signatures =
+            aptos_types::aggregate_signature::AggregateSignature::try_from(signatures).unwrap();
+        assert_eq!(&signatures, signed.signatures());
+        rebuilt
+            .verify_multi_signatures(signed.ledger_info(), &signatures)
+            .unwrap();
+
+        let mut invalid = ValidatorConsensusInfo::from(verifier.validator_infos()[0].clone());
+        invalid.public_key = HexEncodedBytes(vec![0; 3]);
+        assert!(
+            aptos_types::validator_verifier::ValidatorConsensusInfo::try_from(invalid).is_err()
+        );
+    }
+
+    #[test]
+    fn test_signing_hash() {
+        let (_, verifier) = validator(2);
+        for ledger_info in [
+            ledger_info(1, 10, None),
+            ledger_info(1, 10, Some(EpochState::new(2, verifier))),
+        ] {
+            // Only what is shipped in payloads is used to recompute the hash
+            let view: CompleteLedgerInfo = serde_json::from_value(
+                serde_json::to_value(CompleteLedgerInfo::from(ledger_info.clone())).unwrap(),
+            )
+            .unwrap();
+            assert_eq!(view.signing_hash().unwrap(), ledger_info.hash().into());
+        }
+
+        let other = CompleteLedgerInfo::from(ledger_info(1, 11, None));
+        assert_ne!(
+            other.signing_hash().unwrap(),
+            ledger_info(1, 10, None).hash().into()
+        );
+    }
+
+    #[test]
+    fn test_signers_voting_power() {
+        let signers = [validator(1).0, validator(2).0, validator(3).0];
+        let verifier = aptos_types::validator_verifier::ValidatorVerifier::new(
+            signers
+                .iter()
+                .zip([1, 2, 3])
+                .map(|(signer, voting_power)| {
+                    aptos_types::validator_verifier::ValidatorConsensusInfo::new(
+                        signer.author(),
+                        signer.public_key(),
+                        voting_power,
+                    )
+                })
+                .collect(),
+        );
+        let view = ValidatorVerifier::from(verifier.clone());
+        assert_eq!(view.total_voting_power(), 6);
+        assert_eq!(view.quorum_voting_power(), verifier.quorum_voting_power());
+
+        let signed_by = |signers: &[aptos_types::validator_signer::ValidatorSigner]| {
+            let ledger_info = ledger_info(1, 1, None);
+            let partial_signatures = PartialSignatures::new(
+                signers
+                    .iter()
+                    .map(|signer| (signer.author(), signer.sign(&ledger_info).unwrap()))
+                    .collect::<BTreeMap<_, _>>(),
+            );
+            let signed = aptos_types::ledger_info::LedgerInfoWithSignatures::new(
+                ledger_info,
+                verifier.aggregate_signatures(&partial_signatures).unwrap(),
+            );
+            match signed {
+                aptos_types::ledger_info::LedgerInfoWithSignatures::V0(v0) => {
+                    LedgerInfoWithV0::from(v0)
+                },
+            }
+        };
+
+        let quorum = signed_by(&signers[1..]);
+        assert_eq!(quorum.num_signers(), 2);
+        assert_eq!(quorum.signers_voting_power(&view).unwrap(), 5);
+        assert!(quorum.has_quorum(&view).unwrap());
+
+        let minority = signed_by(&signers[..2]);
+        assert_eq!(minority.num_signers(), 2);
+        assert_eq!(minority.signers_voting_power(&view).unwrap(), 3);
+        assert!(!minority.has_quorum(&view).unwrap());
+
+        // Signers beyond the validator set
+        let (_, single) = validator(1);
+        let single = ValidatorVerifier::from(single);
+        assert!(quorum.signers_voting_power(&single).is_err());
+    }
+}
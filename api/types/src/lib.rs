@@ -11,23 +11,31 @@ mod bytecode;
 mod convert;
 mod derives;
 pub mod epoch_change;
+pub mod epoch_state_store;
 mod error;
 mod hash;
 mod headers;
 mod index;
 pub mod ledger_info;
+pub mod light_client;
 pub mod mime_types;
 mod move_types;
+pub mod proof_payload;
+pub mod proof_schema;
+pub mod self_test;
 pub mod sparse_merkle_proof;
 mod state;
 mod table;
+#[cfg(test)]
+mod test_utils;
 pub mod transaction;
 pub mod trusted_state;
+pub mod verify;
 mod view;
-pub(crate) mod waypoint;
+pub mod waypoint;
 mod wrappers;
 
-pub use account::AccountData;
+pub use account::{AccountData, AccountResourcesAtVersion, RawAccountResourcesAtVersion};
 pub use address::Address;
 pub use block::{BcsBlock, Block};
 pub use bytecode::Bytecode;
@@ -45,9 +53,9 @@ pub use move_types::{
     ResourceGroup, MAX_RECURSIVE_TYPES_ALLOWED, U128, U256, U64,
 };
 use serde::{Deserialize, Deserializer};
-pub use state::RawStateValueRequest;
+pub use state::{AccountProofRequest, RawStateValueRequest, StateValueWithKeyHash};
 use std::str::FromStr;
-pub use table::{RawTableItemRequest, TableItemRequest};
+pub use table::{RawTableItemRequest, RawTableItemResult, TableItemRequest, TableItemResult};
 pub use transaction::{
     AccountSignature, BlockMetadataTransaction, DeleteModule, DeleteResource, DeleteTableItem,
     DirectWriteSet, Ed25519Signature, EncodeSubmissionRequest, EntryFunctionPayload, Event,
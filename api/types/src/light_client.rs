@@ -0,0 +1,66 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Everything a light client needs to verify the proofs served by the API, in one place
+
+pub use crate::{
+    epoch_state_store::{
+        EpochStateStore, EpochStateStoreError, FileEpochStateStore, InMemoryEpochStateStore,
+    },
+    proof_payload::{
+        verify_account_proof_for, verify_batch_account_proof, verify_encoded_batch_account_proof,
+        verify_resource_proof_for, AccountProofPayload, AccountResourcesProofPayload,
+        AccountStateDeltaPayload, BootstrapBundle, CandidateEpoch, CanonicalAccountProof,
+        EpochChangeProofPage, EpochChangeProofPayload, EventProofPayload, ProofEncodingError,
+        ProvenResource, ResourceDelta, ResourceWithProof, StateAnchor, StateProofPayload,
+        StateValueChunk, TransactionProofPayload, TrimmedAccountProofPayload, TrimmedLedgerInfo,
+    },
+    self_test::{run_self_test, SelfTestError},
+    sparse_merkle_proof::CompressedSiblings,
+    verify::{
+        ratchet_epoch_state, ratchet_epoch_state_with_lineage, verify_epoch_span,
+        verify_ledger_info, LedgerInfoVerifier, TrustLineage, TrustLink, VerifyError,
+    },
+};
+pub use aptos_types::{
+    epoch_change::EpochChangeProof, epoch_state::EpochState, ledger_info::LedgerInfoWithSignatures,
+    proof::SparseMerkleProof, trusted_state::TrustedState, waypoint::Waypoint,
+};
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        light_client::{
+            ratchet_epoch_state, verify_ledger_info, EpochChangeProof, EpochChangeProofPayload,
+            EpochState, EpochStateStore, InMemoryEpochStateStore, LedgerInfoWithSignatures,
+            SparseMerkleProof, StateProofPayload, StateValueChunk, TrustedState, VerifyError,
+            Waypoint,
+        },
+        test_utils::{account_proof_payload, TestEpochChain},
+    };
+
+    #[test]
+    fn test_public_paths() {
+        let chain = TestEpochChain::new(1, 1);
+        let ledger_info: &LedgerInfoWithSignatures = &chain.proof.ledger_info_with_sigs[0];
+        let payload = EpochChangeProofPayload {
+            epoch_change_proof: EpochChangeProof::new(vec![ledger_info.clone()], false),
+            trusted_state: TrustedState::EpochState {
+                waypoint: Waypoint::new_any(ledger_info.ledger_info()),
+                epoch_state: chain.trusted.clone(),
+            },
+            next_epoch_state_present: true,
+        };
+        verify_ledger_info(&chain.trusted, ledger_info).unwrap();
+
+        let mut store: Box<dyn EpochStateStore> = Box::<InMemoryEpochStateStore>::default();
+        let latest: Result<EpochState, VerifyError> =
+            ratchet_epoch_state(&chain.trusted, &payload.epoch_change_proof, store.as_mut());
+        assert_eq!(latest, Ok(chain.latest));
+
+        let (epoch_state, account_proof): (_, StateProofPayload) = account_proof_payload();
+        let _: &SparseMerkleProof = &account_proof.state_proof;
+        let _: Vec<StateValueChunk> = vec![];
+        account_proof.verify(&epoch_state).unwrap();
+    }
+}
@@ -0,0 +1,2354 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! BCS payloads returned by the proof endpoints, along with their client-side verification
+
+use crate::{
+    sparse_merkle_proof::CompressedSiblings,
+    verify::{verify_ledger_info, LedgerInfoVerifier, VerifyError},
+    HexEncodedBytes, LedgerInfo, U64,
+};
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_types::{
+    account_address::AccountAddress,
+    account_config::AccountResource,
+    aggregate_signature::AggregateSignature,
+    block_info::{BlockInfo, Round},
+    contract_event::ContractEvent,
+    epoch_change::EpochChangeProof,
+    epoch_state::EpochState,
+    ledger_info::LedgerInfoWithSignatures,
+    proof::{
+        EventAccumulatorProof, SparseMerkleProof, TransactionAccumulatorInternalNode,
+        TransactionAccumulatorProof,
+    },
+    state_store::{state_key::StateKey, state_value::StateValue},
+    transaction::{TransactionInfo, Version},
+    trusted_state::TrustedState,
+    validator_verifier::ValidatorVerifier,
+    waypoint::Waypoint,
+};
+use move_core_types::{language_storage::StructTag, move_resource::MoveStructType};
+use poem_openapi::{Object as PoemObject, Union};
+use serde::{
+    de::{DeserializeSeed, SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize,
+};
+use std::fmt;
+use thiserror::Error;
+
+/// Prefix of [`StateProofPayload::to_hex`] strings, once decoded
+pub const ACCOUNT_PROOF_MAGIC: [u8; 4] = *b"APRF";
+/// Version of the encoding produced by [`StateProofPayload::to_hex`]
+pub const ACCOUNT_PROOF_ENCODING_VERSION: u8 = 2;
+
+/// Errors possible while decoding an [`StateProofPayload::to_hex`] string
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ProofEncodingError {
+    #[error("Invalid hex: {0}")]
+    InvalidHex(String),
+    #[error("Not an encoded account proof, the magic prefix doesn't match")]
+    InvalidMagic,
+    #[error("Unsupported account proof encoding version {0}")]
+    UnsupportedVersion(u8),
+    #[error("Invalid BCS: {0}")]
+    InvalidBcs(String),
+}
+
+/// Epoch state a proof was verified with by [`StateProofPayload::verify_at_epoch_boundary`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CandidateEpoch {
+    Current,
+    Next,
+}
+
+/// Proof of a state value at a transaction, anchored to a signed ledger info
+///
+/// The value can be any state item: an account resource, a module, a table item...
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StateProofPayload {
+    /// Proof for the state value inclusion
+    pub state_proof: SparseMerkleProof,
+    /// State value leaf key
+    pub element_key: HashValue,
+    /// Hash of the state value
+    pub element_hash: HashValue,
+    /// Proof for the transaction inclusion, unless requested without it
+    pub transaction_proof: Option<TransactionAccumulatorProof>,
+    /// Hashed representation of the transaction, unless requested without its proof
+    pub transaction: Option<TransactionInfo>,
+    /// Transaction version.
+    pub transaction_index: u64,
+    /// Signed Ledger info with the transaction
+    pub ledger_info_v0: LedgerInfoWithSignatures,
+    /// ValidatorVerifier valid for the proof. This is only a hint: clients must cross-check it
+    /// against the validators of their trusted epoch state, as [`StateProofPayload::verify`] does.
+    pub validator_verifier: ValidatorVerifier,
+    /// BCS encoded state key, the pre-image of `element_key`, if requested
+    pub state_key: Option<Vec<u8>>,
+    /// Requested chunk of the state value
+    pub value_chunk: Option<StateValueChunk>,
+    /// If requested, the siblings of `state_proof` in compressed form, in which case `state_proof`
+    /// itself comes without siblings
+    pub compressed_siblings: Option<CompressedSiblings>,
+}
+
+/// Former name of [`StateProofPayload`], from when only account resources could be proven
+pub type AccountProofPayload = StateProofPayload;
+
+impl StateProofPayload {
+    /// Verifies the whole proof against the trusted `epoch_state`, returning the version and hash
+    /// of the transaction the account state was proven at.
+    ///
+    /// The `validator_verifier` carried by the payload is not trusted; it must match the verifier
+    /// of `epoch_state`, which is the one signatures are checked with.
+    pub fn verify(&self, epoch_state: &EpochState) -> Result<(Version, HashValue), VerifyError> {
+        verify_ledger_info(epoch_state, &self.ledger_info_v0)?;
+        if self.validator_verifier != epoch_state.verifier {
+            return Err(VerifyError::ValidatorVerifierMismatch {
+                epoch: epoch_state.epoch,
+            });
+        }
+        self.verify_against_ledger_info()
+    }
+
+    /// Same as [`StateProofPayload::verify`], for a client in the window around an epoch change
+    /// that doesn't know yet whether the proof is signed by the validators of the `current` epoch
+    /// or of the `next` one. Reports which of the two verified the proof.
+    ///
+    /// Outside of that window, use [`StateProofPayload::verify`] with the single trusted epoch
+    /// state: `next` must itself be trusted, e.g. ratcheted to through an epoch change proof.
+    pub fn verify_at_epoch_boundary(
+        &self,
+        current: &EpochState,
+        next: &EpochState,
+    ) -> Result<(CandidateEpoch, Version, HashValue), VerifyError> {
+        if next.epoch != current.epoch + 1 {
+            return Err(VerifyError::NonIncrementingEpoch {
+                epoch: current.epoch,
+                next_epoch: next.epoch,
+            });
+        }
+        // Ledger infos carry their epoch, so only one of the candidates can verify the proof
+        let epoch = self.ledger_info_v0.ledger_info().epoch();
+        let (candidate, epoch_state) = if epoch == next.epoch {
+            (CandidateEpoch::Next, next)
+        } else {
+            (CandidateEpoch::Current, current)
+        };
+        let (version, transaction_hash) = self.verify(epoch_state)?;
+        Ok((candidate, version, transaction_hash))
+    }
+
+    /// Same as [`StateProofPayload::verify`], also rejecting ledger infos older than
+    /// `trusted_version`, the version of the latest ledger info the client already trusts, as they
+    /// may come from a replayed stale proof. Proofs of past state are still accepted if
+    /// `historical` is set.
+    pub fn verify_not_stale(
+        &self,
+        epoch_state: &EpochState,
+        trusted_version: Option<Version>,
+        historical: bool,
+    ) -> Result<(Version, HashValue), VerifyError> {
+        let version = self.ledger_info_v0.ledger_info().version();
+        match trusted_version {
+            Some(trusted_version) if version < trusted_version && !historical => {
+                Err(VerifyError::StaleLedgerInfo {
+                    version,
+                    trusted_version,
+                })
+            },
+            _ => self.verify(epoch_state),
+        }
+    }
+
+    /// Same as [`StateProofPayload::verify`], for a ledger info trusted through `waypoint`
+    /// rather than signatures, such as the genesis ledger info.
+    pub fn verify_with_waypoint(
+        &self,
+        waypoint: &Waypoint,
+    ) -> Result<(Version, HashValue), VerifyError> {
+        waypoint
+            .verify(self.ledger_info_v0.ledger_info())
+            .map_err(|error| VerifyError::WaypointMismatch {
+                error: error.to_string(),
+            })?;
+        self.verify_against_ledger_info()
+    }
+
+    /// Same as [`StateProofPayload::verify`], for a `trusted_ledger_info` already authenticated
+    /// by a trusted third party, such as a component publishing signed ledger infos.
+    ///
+    /// Its signatures aren't checked again: the proof only has to be anchored to it, with the state
+    /// and accumulator proofs chaining into it.
+    pub fn verify_with_trusted_ledger_info(
+        &self,
+        trusted_ledger_info: &LedgerInfoWithSignatures,
+    ) -> Result<(Version, HashValue), VerifyError> {
+        // Signatures may be aggregated differently, only the signed ledger info has to match
+        let expected = trusted_ledger_info.ledger_info().hash();
+        let actual = self.ledger_info_v0.ledger_info().hash();
+        if actual != expected {
+            return Err(VerifyError::LedgerInfoMismatch { expected, actual });
+        }
+        self.verify_against_ledger_info()
+    }
+
+    /// Checks only the signatures of `ledger_info_v0` against the trusted `epoch_state`, returning
+    /// the authenticated ledger info.
+    ///
+    /// The state and accumulator proofs are skipped, so nothing is proven about the account: this
+    /// is the cheapest way for a client to refresh its trust in the chain tip.
+    pub fn verify_ledger_info_only(
+        &self,
+        epoch_state: &EpochState,
+    ) -> Result<&aptos_types::ledger_info::LedgerInfo, VerifyError> {
+        verify_ledger_info(epoch_state, &self.ledger_info_v0)?;
+        Ok(self.ledger_info_v0.ledger_info())
+    }
+
+    /// Reports the BCS encoded size of each component of the proof, which add up to its total.
+    pub fn size_breakdown(&self) -> ProofSizeBreakdown {
+        fn size<T: Serialize + ?Sized>(value: &T) -> U64 {
+            (bcs::serialized_size(value).expect("Proof payloads are serializable") as u64).into()
+        }
+        let other = size(&self.element_key).0
+            + size(&self.element_hash).0
+            + size(&self.transaction).0
+            + size(&self.transaction_index).0
+            + size(&self.state_key).0
+            + size(&self.value_chunk).0;
+        ProofSizeBreakdown {
+            total: size(self),
+            state_proof: (size(&self.state_proof).0 + size(&self.compressed_siblings).0).into(),
+            accumulator_proof: size(&self.transaction_proof),
+            ledger_info: size(&self.ledger_info_v0),
+            validator_set: size(&self.validator_verifier),
+            other: other.into(),
+        }
+    }
+
+    /// Verifies everything but the authenticity of `ledger_info_v0`.
+    pub(crate) fn verify_against_ledger_info(&self) -> Result<(Version, HashValue), VerifyError> {
+        self.state_key()?;
+        self.verify_transaction_proof()?;
+        self.verify_state_proof()?;
+        let (_, transaction) = self.transaction_with_proof()?;
+        Ok((self.transaction_index, transaction.transaction_hash()))
+    }
+
+    /// Verifies only the sparse merkle proof, against a `state_root` the client already trusts,
+    /// for proofs served without their transaction proof.
+    ///
+    /// Nothing else in the payload is checked, the trust in the state value comes from
+    /// `state_root` alone.
+    pub fn verify_with_state_root(&self, state_root: HashValue) -> Result<(), VerifyError> {
+        self.state_key()?;
+        self.verify_state_proof_by_root(state_root)
+    }
+
+    /// The transaction and its accumulator proof, unless the proof was served without them.
+    fn transaction_with_proof(
+        &self,
+    ) -> Result<(&TransactionAccumulatorProof, &TransactionInfo), VerifyError> {
+        match (&self.transaction_proof, &self.transaction) {
+            (Some(transaction_proof), Some(transaction)) => Ok((transaction_proof, transaction)),
+            _ => Err(VerifyError::MissingTransactionProof {
+                version: self.transaction_index,
+            }),
+        }
+    }
+
+    /// Runs each check of [`StateProofPayload::verify`] on its own and reports which passed,
+    /// rather than stopping at the first failure.
+    pub fn check(&self, epoch_state: &EpochState) -> ProofCheckReport {
+        ProofCheckReport {
+            state_proof: self
+                .state_key()
+                .and_then(|_| self.verify_state_proof())
+                .into(),
+            transaction_proof: self.verify_transaction_proof().into(),
+            signatures: verify_ledger_info(epoch_state, &self.ledger_info_v0).into(),
+        }
+    }
+
+    /// Checks the transaction accumulator proof against the ledger info.
+    fn verify_transaction_proof(&self) -> Result<(), VerifyError> {
+        let (transaction_proof, transaction) = self.transaction_with_proof()?;
+        verify_transaction_inclusion(
+            transaction_proof,
+            transaction,
+            self.transaction_index,
+            self.ledger_info_v0.ledger_info(),
+        )
+    }
+
+    /// Checks the sparse merkle proof against the state checkpoint of the transaction.
+    fn verify_state_proof(&self) -> Result<(), VerifyError> {
+        let (_, transaction) = self.transaction_with_proof()?;
+        let state_root =
+            transaction
+                .state_checkpoint_hash()
+                .ok_or(VerifyError::MissingStateCheckpoint {
+                    version: self.transaction_index,
+                })?;
+        self.verify_state_proof_by_root(state_root)
+    }
+
+    /// Checks the sparse merkle proof against `state_root`.
+    fn verify_state_proof_by_root(&self, state_root: HashValue) -> Result<(), VerifyError> {
+        let state_proof = self.full_state_proof()?;
+        if let Some(leaf) = state_proof.leaf() {
+            if leaf.value_hash() != self.element_hash {
+                return Err(VerifyError::LeafValueMismatch {
+                    leaf_value_hash: leaf.value_hash(),
+                    element_hash: self.element_hash,
+                });
+            }
+        }
+        state_proof
+            .verify_by_hash(state_root, self.element_key, Some(self.element_hash))
+            .map_err(|error| VerifyError::InvalidStateProof {
+                error: error.to_string(),
+            })
+    }
+
+    /// The sparse merkle proof of the account, with its siblings decompressed if needed.
+    pub fn full_state_proof(&self) -> Result<SparseMerkleProof, VerifyError> {
+        match &self.compressed_siblings {
+            Some(compressed_siblings) => Ok(SparseMerkleProof::new(
+                self.state_proof.leaf(),
+                compressed_siblings.decompress()?,
+            )),
+            None => Ok(self.state_proof.clone()),
+        }
+    }
+
+    /// Cheap consistency checks of the proof against the `ledger_info` reported alongside it by
+    /// the server. These don't replace [`StateProofPayload::verify`].
+    pub fn sanity_check(&self, ledger_info: &LedgerInfo) -> Result<(), VerifyError> {
+        let signed_version = self.ledger_info_v0.ledger_info().version();
+        if self.transaction_index > signed_version {
+            return Err(VerifyError::VersionAheadOfLedgerInfo {
+                version: self.transaction_index,
+                ledger_version: signed_version,
+            });
+        }
+        if self.transaction_index < ledger_info.oldest_version() {
+            return Err(VerifyError::VersionPruned {
+                version: self.transaction_index,
+                oldest_version: ledger_info.oldest_version(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Decodes the included state key, checking that it hashes to `element_key`.
+    pub fn state_key(&self) -> Result<Option<StateKey>, VerifyError> {
+        let Some(bytes) = &self.state_key else {
+            return Ok(None);
+        };
+        let state_key: StateKey =
+            bcs::from_bytes(bytes).map_err(|error| VerifyError::InvalidStateKey {
+                error: error.to_string(),
+            })?;
+        let actual = state_key.hash();
+        if actual != self.element_key {
+            return Err(VerifyError::StateKeyMismatch {
+                expected: self.element_key,
+                actual,
+            });
+        }
+        Ok(Some(state_key))
+    }
+
+    /// Checks that `state_value` is the value proven by `element_hash`.
+    ///
+    /// This only authenticates the value once [`StateProofPayload::verify`] has succeeded.
+    pub fn verify_state_value(&self, state_value: &StateValue) -> Result<(), VerifyError> {
+        let actual = state_value.hash();
+        if actual != self.element_hash {
+            return Err(VerifyError::StateValueMismatch {
+                expected: self.element_hash,
+                actual,
+            });
+        }
+        Ok(())
+    }
+
+    /// Encodes the payload as a single hex string, tagged with [`ACCOUNT_PROOF_MAGIC`] and
+    /// [`ACCOUNT_PROOF_ENCODING_VERSION`] so it can be recognized when decoded.
+    pub fn to_hex(&self) -> String {
+        let mut bytes = ACCOUNT_PROOF_MAGIC.to_vec();
+        bytes.push(ACCOUNT_PROOF_ENCODING_VERSION);
+        bytes.extend(bcs::to_bytes(self).expect("StateProofPayload serialization can't fail"));
+        hex::encode(bytes)
+    }
+
+    /// Decodes a string produced by [`StateProofPayload::to_hex`]. A `0x` prefix is accepted.
+    pub fn from_hex(encoded: &str) -> Result<Self, ProofEncodingError> {
+        let encoded = encoded.strip_prefix("0x").unwrap_or(encoded);
+        let bytes = hex::decode(encoded)
+            .map_err(|error| ProofEncodingError::InvalidHex(error.to_string()))?;
+        let bytes = bytes
+            .strip_prefix(ACCOUNT_PROOF_MAGIC.as_slice())
+            .ok_or(ProofEncodingError::InvalidMagic)?;
+        match bytes.split_first() {
+            Some((&ACCOUNT_PROOF_ENCODING_VERSION, payload)) => bcs::from_bytes(payload)
+                .map_err(|error| ProofEncodingError::InvalidBcs(error.to_string())),
+            Some((&version, _)) => Err(ProofEncodingError::UnsupportedVersion(version)),
+            None => Err(ProofEncodingError::InvalidBcs(
+                "Missing encoding version".to_string(),
+            )),
+        }
+    }
+}
+
+/// Checks that `transaction` is the one at `version` in the transaction accumulator of
+/// `ledger_info`.
+fn verify_transaction_inclusion(
+    transaction_proof: &TransactionAccumulatorProof,
+    transaction: &TransactionInfo,
+    version: Version,
+    ledger_info: &aptos_types::ledger_info::LedgerInfo,
+) -> Result<(), VerifyError> {
+    // The accumulator has a leaf per version up to the ledger info's, and its proofs go from the
+    // leaf all the way up to the root
+    if version > ledger_info.version() {
+        return Err(VerifyError::VersionAheadOfLedgerInfo {
+            version,
+            ledger_version: ledger_info.version(),
+        });
+    }
+    let expected = (u64::BITS - ledger_info.version().leading_zeros()) as usize;
+    if transaction_proof.siblings().len() != expected {
+        return Err(VerifyError::AccumulatorDepthMismatch {
+            ledger_version: ledger_info.version(),
+            num_siblings: transaction_proof.siblings().len(),
+            expected,
+        });
+    }
+
+    transaction_proof
+        .verify(
+            ledger_info.transaction_accumulator_hash(),
+            transaction.hash(),
+            version,
+        )
+        .map_err(|error| VerifyError::InvalidTransactionProof {
+            version,
+            error: error.to_string(),
+        })
+}
+
+/// Proof that a transaction was committed, anchored to a signed ledger info
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TransactionProofPayload {
+    /// Proof for the transaction inclusion
+    pub transaction_proof: TransactionAccumulatorProof,
+    /// Hashed representation of the transaction
+    pub transaction: TransactionInfo,
+    /// Transaction version.
+    pub transaction_index: Version,
+    /// Signed Ledger info with the transaction
+    pub ledger_info_v0: LedgerInfoWithSignatures,
+}
+
+impl TransactionProofPayload {
+    /// Verifies the proof against the trusted `epoch_state`, returning the version and hash of the
+    /// committed transaction.
+    pub fn verify(&self, epoch_state: &EpochState) -> Result<(Version, HashValue), VerifyError> {
+        verify_ledger_info(epoch_state, &self.ledger_info_v0)?;
+        verify_transaction_inclusion(
+            &self.transaction_proof,
+            &self.transaction,
+            self.transaction_index,
+            self.ledger_info_v0.ledger_info(),
+        )?;
+        Ok((self.transaction_index, self.transaction.transaction_hash()))
+    }
+}
+
+/// Proof of an event, anchored to a signed ledger info through the transaction that emitted it
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct EventProofPayload {
+    pub event: ContractEvent,
+    /// Index of the event among the events emitted by the transaction
+    pub event_index: u64,
+    /// Proof for the event inclusion in the event accumulator of the transaction
+    pub event_proof: EventAccumulatorProof,
+    /// Proof for the transaction inclusion
+    pub transaction_proof: TransactionAccumulatorProof,
+    /// Hashed representation of the transaction
+    pub transaction: TransactionInfo,
+    /// Transaction version.
+    pub transaction_index: Version,
+    /// Signed Ledger info with the transaction
+    pub ledger_info_v0: LedgerInfoWithSignatures,
+}
+
+impl EventProofPayload {
+    /// Verifies the proof against the trusted `epoch_state`, returning the version and hash of the
+    /// transaction that emitted the event.
+    pub fn verify(&self, epoch_state: &EpochState) -> Result<(Version, HashValue), VerifyError> {
+        verify_ledger_info(epoch_state, &self.ledger_info_v0)?;
+        verify_transaction_inclusion(
+            &self.transaction_proof,
+            &self.transaction,
+            self.transaction_index,
+            self.ledger_info_v0.ledger_info(),
+        )?;
+        self.event_proof
+            .verify(
+                self.transaction.event_root_hash(),
+                self.event.hash(),
+                self.event_index,
+            )
+            .map_err(|error| VerifyError::InvalidEventProof {
+                version: self.transaction_index,
+                index: self.event_index,
+                error: error.to_string(),
+            })?;
+        Ok((self.transaction_index, self.transaction.transaction_hash()))
+    }
+}
+
+/// Proofs of the resources of an account at two versions, to tell which ones changed in between
+///
+/// The transaction and signed ledger info each version is proven against are carried once, in
+/// `from` and `to`, and each resource only carries its sparse merkle proofs at both versions.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct AccountStateDeltaPayload {
+    pub from: StateAnchor,
+    pub to: StateAnchor,
+    pub resources: Vec<ResourceDelta>,
+    /// Whether the account has more resources than the ones included
+    pub more: bool,
+}
+
+impl AccountStateDeltaPayload {
+    /// Verifies the proofs of both versions against the trusted epoch states they were signed in,
+    /// which are the same unless the account is compared across an epoch change, then the proofs
+    /// of every resource against the state roots of both versions.
+    pub fn verify(
+        &self,
+        from_epoch_state: &EpochState,
+        to_epoch_state: &EpochState,
+    ) -> Result<(), VerifyError> {
+        let from_state_root = self.from.verify(from_epoch_state)?;
+        let to_state_root = self.to.verify(to_epoch_state)?;
+        for resource in &self.resources {
+            resource.verify(from_state_root, to_state_root)?;
+        }
+        Ok(())
+    }
+}
+
+/// A transaction proven against a signed ledger info, whose state root the proofs of the state at
+/// its version are verified against
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StateAnchor {
+    /// Proof for the transaction inclusion
+    pub transaction_proof: TransactionAccumulatorProof,
+    /// Hashed representation of the transaction
+    pub transaction: TransactionInfo,
+    /// Transaction version.
+    pub transaction_index: Version,
+    /// Signed Ledger info with the transaction
+    pub ledger_info_v0: LedgerInfoWithSignatures,
+    /// ValidatorVerifier valid for the proof. This is only a hint: clients must cross-check it
+    /// against the validators of their trusted epoch state, as [`StateAnchor::verify`] does.
+    pub validator_verifier: ValidatorVerifier,
+}
+
+impl StateAnchor {
+    /// Verifies the transaction against the trusted `epoch_state`, returning the state root at its
+    /// version.
+    pub fn verify(&self, epoch_state: &EpochState) -> Result<HashValue, VerifyError> {
+        verify_ledger_info(epoch_state, &self.ledger_info_v0)?;
+        if self.validator_verifier != epoch_state.verifier {
+            return Err(VerifyError::ValidatorVerifierMismatch {
+                epoch: epoch_state.epoch,
+            });
+        }
+        verify_transaction_inclusion(
+            &self.transaction_proof,
+            &self.transaction,
+            self.transaction_index,
+            self.ledger_info_v0.ledger_info(),
+        )?;
+        self.state_root()
+    }
+
+    /// The state root at the version of the transaction, which must be the last of its block.
+    pub fn state_root(&self) -> Result<HashValue, VerifyError> {
+        self.transaction
+            .state_checkpoint_hash()
+            .ok_or(VerifyError::MissingStateCheckpoint {
+                version: self.transaction_index,
+            })
+    }
+}
+
+/// A resource, or resource group, of an account proven at both versions of an
+/// [`AccountStateDeltaPayload`]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ResourceDelta {
+    /// BCS encoded state key of the resource
+    pub state_key: Vec<u8>,
+    /// Hash of the value of the resource at the `from` version, if it existed then
+    pub from_value_hash: Option<HashValue>,
+    /// Proof of `from_value_hash`, a proof that the resource didn't exist if there is none
+    pub from_proof: SparseMerkleProof,
+    /// Hash of the value of the resource at the `to` version, if it still exists then
+    pub to_value_hash: Option<HashValue>,
+    /// Proof of `to_value_hash`, a proof that the resource doesn't exist if there is none
+    pub to_proof: SparseMerkleProof,
+    /// Whether the value of the resource changed between the two versions, checked against the
+    /// value hashes by [`ResourceDelta::verify`]
+    pub changed: bool,
+}
+
+impl ResourceDelta {
+    /// Verifies the proofs of the resource against the state roots of both versions, as returned
+    /// by [`StateAnchor::verify`], and that `changed` matches them, returning the state key of the
+    /// resource.
+    pub fn verify(
+        &self,
+        from_state_root: HashValue,
+        to_state_root: HashValue,
+    ) -> Result<StateKey, VerifyError> {
+        let state_key: StateKey =
+            bcs::from_bytes(&self.state_key).map_err(|error| VerifyError::InvalidStateKey {
+                error: error.to_string(),
+            })?;
+        let element_key = state_key.hash();
+        for (state_root, value_hash, proof) in [
+            (from_state_root, self.from_value_hash, &self.from_proof),
+            (to_state_root, self.to_value_hash, &self.to_proof),
+        ] {
+            proof
+                .verify_by_hash(state_root, element_key, value_hash)
+                .map_err(|error| VerifyError::InvalidStateProof {
+                    error: error.to_string(),
+                })?;
+        }
+        let changed = self.from_value_hash != self.to_value_hash;
+        if self.changed != changed {
+            return Err(VerifyError::ChangedFlagMismatch {
+                expected: changed,
+                actual: self.changed,
+            });
+        }
+        Ok(state_key)
+    }
+}
+
+/// A page of the resources of an account, each with its proof, all at the same version
+///
+/// The proofs share their ledger info. The cursor to the next page, if any, is returned in the
+/// `X-Aptos-Cursor` header.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct AccountResourcesProofPayload {
+    pub version: Version,
+    pub resources: Vec<ProvenResource>,
+}
+
+/// A resource, or resource group, of an account and the proof of its value
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ProvenResource {
+    pub state_value: StateValue,
+    /// Proof of `state_value`, including its state key
+    pub proof: StateProofPayload,
+}
+
+impl ProvenResource {
+    /// Verifies the proof against `epoch_state` and checks that it proves `state_value`,
+    /// returning the state key of the resource.
+    pub fn verify(&self, epoch_state: &EpochState) -> Result<StateKey, VerifyError> {
+        self.proof.verify(epoch_state)?;
+        self.proof.verify_state_value(&self.state_value)?;
+        self.proof
+            .state_key()?
+            .ok_or_else(|| VerifyError::InvalidStateKey {
+                error: "Missing state key".to_string(),
+            })
+    }
+}
+
+/// A resource of an account with the sparse merkle proof of its state value, for clients that
+/// verify it against a state root they already trust
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ResourceWithProof {
+    /// Version the resource is read and proven at
+    pub version: Version,
+    /// State value of the resource, whose bytes are the BCS encoded resource
+    pub state_value: StateValue,
+    pub state_proof: SparseMerkleProof,
+}
+
+impl ResourceWithProof {
+    /// The BCS encoded resource
+    pub fn resource_bytes(&self) -> &[u8] {
+        self.state_value.bytes()
+    }
+
+    /// Verifies that `state_value` is stored under `state_key` in the state tree of root
+    /// `state_root`.
+    pub fn verify(&self, state_key: &StateKey, state_root: HashValue) -> Result<(), VerifyError> {
+        self.state_proof
+            .verify(state_root, state_key.hash(), Some(&self.state_value))
+            .map_err(|error| VerifyError::InvalidStateProof {
+                error: error.to_string(),
+            })
+    }
+}
+
+/// The parts of an [`StateProofPayload`] two honest nodes serving it at the same version agree on.
+///
+/// It leaves out what nodes may legitimately serve differently: the optional state key, value
+/// chunk and compressed siblings, the validator set hint, the way signatures are aggregated, and
+/// the sibling hashes themselves, which the transaction and ledger info already commit to.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CanonicalAccountProof {
+    pub element_key: HashValue,
+    pub element_hash: HashValue,
+    pub version: Version,
+    /// Hash of the `TransactionInfo`, committing to the root of the state tree at `version`, if
+    /// served
+    pub transaction_info_hash: Option<HashValue>,
+    /// Hash of the signed `LedgerInfo`
+    pub ledger_info_hash: HashValue,
+}
+
+impl StateProofPayload {
+    /// Reduces the payload to its [`CanonicalAccountProof`].
+    pub fn canonicalize(&self) -> CanonicalAccountProof {
+        CanonicalAccountProof {
+            element_key: self.element_key,
+            element_hash: self.element_hash,
+            version: self.transaction_index,
+            transaction_info_hash: self.transaction.as_ref().map(CryptoHash::hash),
+            ledger_info_hash: self.ledger_info_v0.ledger_info().hash(),
+        }
+    }
+
+    /// Whether `other` proves the same value, at the same version, against the same ledger info.
+    ///
+    /// A client fetching the same proof from several nodes can cross-check them with it to detect
+    /// a lying node. Neither payload is verified, which must still be done once.
+    pub fn is_equivalent(&self, other: &Self) -> bool {
+        self.canonicalize() == other.canonicalize()
+    }
+}
+
+/// [`StateProofPayload`] trimmed for clients that only verify the ledger info, without storing
+/// it.
+///
+/// Compared to the full payload, it drops:
+/// - the `validator_verifier` hint, as the client checks signatures with its trusted epoch state
+///   anyway;
+/// - the transaction accumulator root hash of the ledger info, which is recomputed from
+///   `transaction_proof` before checking signatures. A tampered transaction proof thus surfaces as
+///   invalid signatures.
+///
+/// Every other field of the signed ledger info, and its aggregate signature, is kept as is.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TrimmedAccountProofPayload {
+    pub state_proof: SparseMerkleProof,
+    pub element_key: HashValue,
+    pub element_hash: HashValue,
+    pub transaction_proof: TransactionAccumulatorProof,
+    pub transaction: TransactionInfo,
+    pub transaction_index: u64,
+    pub ledger_info: TrimmedLedgerInfo,
+    pub signatures: AggregateSignature,
+    pub state_key: Option<Vec<u8>>,
+    pub value_chunk: Option<StateValueChunk>,
+    pub compressed_siblings: Option<CompressedSiblings>,
+}
+
+/// Fields of a signed ledger info, except the transaction accumulator root hash
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TrimmedLedgerInfo {
+    pub epoch: u64,
+    pub round: Round,
+    pub id: HashValue,
+    pub version: Version,
+    pub timestamp_usecs: u64,
+    pub next_epoch_state: Option<EpochState>,
+    pub consensus_data_hash: HashValue,
+}
+
+impl StateProofPayload {
+    /// Trims the payload, see [`TrimmedAccountProofPayload`].
+    ///
+    /// Returns `None` for a payload served without its transaction proof, as the trimmed ledger
+    /// info can't be rebuilt without it.
+    pub fn trim(&self) -> Option<TrimmedAccountProofPayload> {
+        let ledger_info = self.ledger_info_v0.ledger_info();
+        Some(TrimmedAccountProofPayload {
+            state_proof: self.state_proof.clone(),
+            element_key: self.element_key,
+            element_hash: self.element_hash,
+            transaction_proof: self.transaction_proof.clone()?,
+            transaction: self.transaction.clone()?,
+            transaction_index: self.transaction_index,
+            ledger_info: TrimmedLedgerInfo {
+                epoch: ledger_info.epoch(),
+                round: ledger_info.round(),
+                id: ledger_info.consensus_block_id(),
+                version: ledger_info.version(),
+                timestamp_usecs: ledger_info.timestamp_usecs(),
+                next_epoch_state: ledger_info.next_epoch_state().cloned(),
+                consensus_data_hash: ledger_info.consensus_data_hash(),
+            },
+            signatures: self.ledger_info_v0.signatures().clone(),
+            state_key: self.state_key.clone(),
+            value_chunk: self.value_chunk.clone(),
+            compressed_siblings: self.compressed_siblings.clone(),
+        })
+    }
+}
+
+impl TrimmedAccountProofPayload {
+    /// Verifies the trimmed proof against the trusted `epoch_state`, the same way
+    /// [`StateProofPayload::verify`] does for the full one.
+    pub fn verify(&self, epoch_state: &EpochState) -> Result<(Version, HashValue), VerifyError> {
+        self.clone()
+            .expand(epoch_state.verifier.clone())
+            .verify(epoch_state)
+    }
+
+    /// Rebuilds the full payload, attaching `validator_verifier` to it.
+    pub fn expand(self, validator_verifier: ValidatorVerifier) -> StateProofPayload {
+        // Recompute the accumulator root the transaction proof leads to
+        let accumulator_root = self
+            .transaction_proof
+            .siblings()
+            .iter()
+            .fold(
+                (self.transaction.hash(), self.transaction_index),
+                |(hash, index), sibling| {
+                    (
+                        if index % 2 == 0 {
+                            TransactionAccumulatorInternalNode::new(hash, *sibling).hash()
+                        } else {
+                            TransactionAccumulatorInternalNode::new(*sibling, hash).hash()
+                        },
+                        index / 2,
+                    )
+                },
+            )
+            .0;
+        let ledger_info = aptos_types::ledger_info::LedgerInfo::new(
+            BlockInfo::new(
+                self.ledger_info.epoch,
+                self.ledger_info.round,
+                self.ledger_info.id,
+                accumulator_root,
+                self.ledger_info.version,
+                self.ledger_info.timestamp_usecs,
+                self.ledger_info.next_epoch_state,
+            ),
+            self.ledger_info.consensus_data_hash,
+        );
+
+        StateProofPayload {
+            state_proof: self.state_proof,
+            element_key: self.element_key,
+            element_hash: self.element_hash,
+            transaction_proof: Some(self.transaction_proof),
+            transaction: Some(self.transaction),
+            transaction_index: self.transaction_index,
+            ledger_info_v0: LedgerInfoWithSignatures::new(ledger_info, self.signatures),
+            validator_verifier,
+            state_key: self.state_key,
+            value_chunk: self.value_chunk,
+            compressed_siblings: self.compressed_siblings,
+        }
+    }
+}
+
+/// Verifies the proof of the account at `address` against the trusted `epoch_state`, deriving the
+/// expected leaf key from `address` rather than reading it from the payload, so payloads can carry
+/// only hashes.
+pub fn verify_account_proof_for(
+    address: AccountAddress,
+    payload: &StateProofPayload,
+    epoch_state: &EpochState,
+) -> Result<(Version, HashValue), VerifyError> {
+    verify_resource_proof_for(
+        address,
+        &AccountResource::struct_tag(),
+        payload,
+        epoch_state,
+    )
+}
+
+/// Same as [`verify_account_proof_for`], for the resource `struct_tag` of the account at `address`,
+/// e.g. an on-chain config such as `0x1::chain_id::ChainId`.
+///
+/// Addresses are fixed-length, so a short reserved address like `0x1` derives the same key as its
+/// zero-padded form, exactly as on the node.
+pub fn verify_resource_proof_for(
+    address: AccountAddress,
+    struct_tag: &StructTag,
+    payload: &StateProofPayload,
+    epoch_state: &EpochState,
+) -> Result<(Version, HashValue), VerifyError> {
+    let expected = StateKey::resource(&address, struct_tag)
+        .map_err(|error| VerifyError::InvalidStateKey {
+            error: error.to_string(),
+        })?
+        .hash();
+    if payload.element_key != expected {
+        return Err(VerifyError::StateKeyMismatch {
+            expected,
+            actual: payload.element_key,
+        });
+    }
+    payload.verify(epoch_state)
+}
+
+/// Verifies a batch of account proofs, checking the signatures of their shared ledger info only
+/// once, and returns the result of each proof in order.
+///
+/// Every proof must be anchored to the ledger info of the first one, at the same version, so a
+/// batch can't pass off state from different versions as consistent. A proof that isn't, or
+/// failing to authenticate the shared ledger info, fails the whole batch.
+pub fn verify_batch_account_proof(
+    verifier: &dyn LedgerInfoVerifier,
+    proofs: &[StateProofPayload],
+) -> Result<Vec<Result<(Version, HashValue), VerifyError>>, VerifyError> {
+    let Some(first) = proofs.first() else {
+        return Ok(vec![]);
+    };
+    if let Some(index) = proofs.iter().position(|proof| {
+        proof.ledger_info_v0 != first.ledger_info_v0
+            || proof.transaction_index != first.transaction_index
+    }) {
+        return Err(VerifyError::InconsistentBatch { index });
+    }
+    verifier.verify_ledger_info(&first.ledger_info_v0)?;
+
+    Ok(proofs
+        .iter()
+        .map(StateProofPayload::verify_against_ledger_info)
+        .collect())
+}
+
+/// Same as [`verify_batch_account_proof`], for a BCS encoded batch, verifying each proof as soon
+/// as it's decoded and dropping it before decoding the next one. Only one decoded proof is kept in
+/// memory at a time, along with the shared ledger info, so very large batches can be verified
+/// without decoding them whole.
+///
+/// Results are passed to `on_result` in order. Since nothing is decoded ahead, an inconsistent
+/// proof, or a failure to authenticate the shared ledger info, only fails the proofs concerned.
+/// Returns the number of proofs in the batch.
+pub fn verify_encoded_batch_account_proof(
+    verifier: &dyn LedgerInfoVerifier,
+    bytes: &[u8],
+    on_result: impl FnMut(Result<(Version, HashValue), VerifyError>),
+) -> Result<usize, ProofEncodingError> {
+    bcs::from_bytes_seed(
+        StreamingBatchVerifier {
+            verifier,
+            on_result,
+        },
+        bytes,
+    )
+    .map_err(|error| ProofEncodingError::InvalidBcs(error.to_string()))
+}
+
+/// Decodes a sequence of [`StateProofPayload`], verifying them one at a time
+struct StreamingBatchVerifier<'a, F> {
+    verifier: &'a dyn LedgerInfoVerifier,
+    on_result: F,
+}
+
+impl<'de, F> DeserializeSeed<'de> for StreamingBatchVerifier<'_, F>
+where
+    F: FnMut(Result<(Version, HashValue), VerifyError>),
+{
+    type Value = usize;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<usize, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, F> Visitor<'de> for StreamingBatchVerifier<'_, F>
+where
+    F: FnMut(Result<(Version, HashValue), VerifyError>),
+{
+    type Value = usize;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a sequence of account proofs")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(mut self, mut seq: A) -> Result<usize, A::Error> {
+        // The ledger info and version of the first proof
+        let mut anchor: Option<(LedgerInfoWithSignatures, Version)> = None;
+        let mut authenticated = false;
+        let mut num_proofs = 0;
+        while let Some(proof) = seq.next_element::<StateProofPayload>()? {
+            let (ledger_info, version) = anchor
+                .get_or_insert_with(|| (proof.ledger_info_v0.clone(), proof.transaction_index));
+            let result =
+                if proof.ledger_info_v0 != *ledger_info || proof.transaction_index != *version {
+                    Err(VerifyError::InconsistentBatch { index: num_proofs })
+                } else if authenticated {
+                    proof.verify_against_ledger_info()
+                } else {
+                    self.verifier
+                        .verify_ledger_info(&proof.ledger_info_v0)
+                        .and_then(|()| {
+                            authenticated = true;
+                            proof.verify_against_ledger_info()
+                        })
+                };
+            (self.on_result)(result);
+            num_proofs += 1;
+        }
+        Ok(num_proofs)
+    }
+}
+
+/// Proof of the latest epoch change, along with the state trusted before it
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct EpochChangeProofPayload {
+    pub epoch_change_proof: EpochChangeProof,
+    pub trusted_state: TrustedState,
+    /// Whether the ledger info `trusted_state` is derived from carries the next epoch state, as
+    /// every epoch-ending ledger info should. If not, the epoch boundary is malformed and
+    /// `trusted_state` is only its waypoint.
+    pub next_epoch_state_present: bool,
+}
+
+impl EpochChangeProofPayload {
+    /// Checks that the waypoint embedded in `trusted_state` is the one of `ledger_info`, the
+    /// ledger info ending the epoch before the trusted one. The server leaves it out of
+    /// `epoch_change_proof`, so it has to come from the client, e.g. from an earlier proof.
+    ///
+    /// Catches a valid proof paired with a waypoint of the wrong version or value.
+    pub fn verify_waypoint(
+        &self,
+        ledger_info: &aptos_types::ledger_info::LedgerInfo,
+    ) -> Result<(), VerifyError> {
+        let expected = Waypoint::new_any(ledger_info);
+        let actual = self.trusted_state.waypoint();
+        if actual.version() != expected.version() {
+            return Err(VerifyError::WaypointVersionMismatch {
+                expected: expected.version(),
+                actual: actual.version(),
+            });
+        }
+        if actual.value() != expected.value() {
+            return Err(VerifyError::WaypointValueMismatch {
+                expected: expected.value(),
+                actual: actual.value(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Everything a light client starting from scratch needs to reach the latest ledger info, read
+/// together so the pieces are consistent
+///
+/// `trusted_state` ratchets to `latest_ledger_info` through `epoch_change_proof`, with
+/// [`TrustedState::verify_and_ratchet_inner`].
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct BootstrapBundle {
+    /// State trusted before the last epoch change
+    pub trusted_state: TrustedState,
+    /// Proof of the epoch changes from `trusted_state` to the epoch of `latest_ledger_info`
+    pub epoch_change_proof: EpochChangeProof,
+    pub latest_ledger_info: LedgerInfoWithSignatures,
+}
+
+/// A page of the epoch-ending ledger infos of a range of epochs, for clients syncing epochs
+/// incrementally
+///
+/// Each page ratchets the epoch state trusted after the previous one, the first page the one
+/// trusted at the start of the range.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct EpochChangeProofPage {
+    /// Ledger infos ending the epochs of the page, with `more` set if more pages follow
+    pub epoch_change_proof: EpochChangeProof,
+    /// Epoch to request the next page from, if more pages follow
+    pub next_epoch: Option<u64>,
+}
+
+/// Outcome of each check of a [`StateProofPayload`], see [`StateProofPayload::check`]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, PoemObject)]
+pub struct ProofCheckReport {
+    /// The sparse merkle proof of the state value against the state checkpoint of the transaction
+    pub state_proof: ProofCheck,
+    /// The transaction accumulator proof against the ledger info
+    pub transaction_proof: ProofCheck,
+    /// The signatures of the ledger info against the validators of its epoch
+    pub signatures: ProofCheck,
+}
+
+impl ProofCheckReport {
+    pub fn passed(&self) -> bool {
+        self.state_proof.passed && self.transaction_proof.passed && self.signatures.passed
+    }
+}
+
+/// Outcome of a single check of a proof
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, PoemObject)]
+pub struct ProofCheck {
+    pub passed: bool,
+    /// Why the check failed, if it did
+    pub error: Option<String>,
+}
+
+impl From<Result<(), VerifyError>> for ProofCheck {
+    fn from(result: Result<(), VerifyError>) -> Self {
+        Self {
+            passed: result.is_ok(),
+            error: result.err().map(|error| error.to_string()),
+        }
+    }
+}
+
+/// JSON rendering of a [`StateProofPayload`], to inspect proofs by hand
+///
+/// Proofs are verified from their BCS encoding, this is only meant for debugging.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, PoemObject)]
+pub struct StateProofView {
+    /// Proof for the state value inclusion
+    pub state_proof: crate::sparse_merkle_proof::SparseMerkleProof,
+    /// State value leaf key
+    pub element_key: crate::HashValue,
+    /// Hash of the state value
+    pub element_hash: crate::HashValue,
+    /// Proof for the transaction inclusion, unless requested without it
+    pub transaction_proof: Option<crate::transaction::TransactionAccumulatorProof>,
+    /// Info of the transaction the state value is proven at, unless requested without its proof
+    pub transaction: Option<crate::transaction::TransactionInfoV0>,
+    /// Transaction version
+    pub transaction_index: U64,
+    /// Signed Ledger info with the transaction
+    pub ledger_info_v0: crate::ledger_info::LedgerInfoWithSignatures,
+    /// ValidatorVerifier valid for the proof
+    pub validator_verifier: crate::ledger_info::ValidatorVerifier,
+    /// BCS encoded state key, the pre-image of `element_key`, if requested
+    pub state_key: Option<HexEncodedBytes>,
+    /// Requested chunk of the state value
+    pub value_chunk: Option<StateValueChunkView>,
+}
+
+impl TryFrom<StateProofPayload> for StateProofView {
+    type Error = VerifyError;
+
+    fn try_from(payload: StateProofPayload) -> Result<Self, Self::Error> {
+        Ok(Self {
+            state_proof: payload.state_proof.try_into()?,
+            element_key: payload.element_key.into(),
+            element_hash: payload.element_hash.into(),
+            transaction_proof: payload.transaction_proof.map(Into::into),
+            transaction: payload.transaction.map(Into::into),
+            transaction_index: payload.transaction_index.into(),
+            ledger_info_v0: payload.ledger_info_v0.into(),
+            validator_verifier: payload.validator_verifier.into(),
+            state_key: payload.state_key.map(Into::into),
+            value_chunk: payload.value_chunk.map(Into::into),
+        })
+    }
+}
+
+/// JSON rendering of a [`StateValueChunk`]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, PoemObject)]
+pub struct StateValueChunkView {
+    /// Position of the chunk in the encoded value
+    pub index: U64,
+    /// Number of chunks the encoded value was split into
+    pub num_chunks: U64,
+    /// Hash of the whole encoded value
+    pub content_hash: crate::HashValue,
+    /// Bytes of the encoded value covered by this chunk
+    pub bytes: HexEncodedBytes,
+}
+
+impl From<StateValueChunk> for StateValueChunkView {
+    fn from(chunk: StateValueChunk) -> Self {
+        Self {
+            index: chunk.index.into(),
+            num_chunks: chunk.num_chunks.into(),
+            content_hash: chunk.content_hash.into(),
+            bytes: chunk.bytes.into(),
+        }
+    }
+}
+
+/// Size in bytes of each component of a BCS encoded [`StateProofPayload`]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, PoemObject)]
+pub struct ProofSizeBreakdown {
+    /// Size of the whole proof
+    pub total: U64,
+    /// Size of the sparse merkle proof, compressed siblings included
+    pub state_proof: U64,
+    /// Size of the transaction accumulator proof
+    pub accumulator_proof: U64,
+    /// Size of the signed ledger info
+    pub ledger_info: U64,
+    /// Size of the attached validator set
+    pub validator_set: U64,
+    /// Size of everything else: the transaction info, the state key and value, and their hashes
+    pub other: U64,
+}
+
+/// JSON response of the account proof endpoint: the proofs, or the size breakdown of the proof
+/// if requested
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, Union)]
+#[serde(untagged)]
+pub enum AccountProofView {
+    Proofs(Vec<StateProofView>),
+    SizeBreakdown(ProofSizeBreakdown),
+}
+
+/// A proof too large to be served in a single response
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("The proof takes {size} bytes once encoded, over the limit of {max_size} bytes")]
+pub struct ProofTooLarge {
+    pub size: usize,
+    pub max_size: usize,
+}
+
+/// Returns the size of the BCS encoding of `payload`, computed without serializing it, failing if
+/// it's over `max_size` bytes.
+pub fn check_encoded_size<T: Serialize>(
+    payload: &T,
+    max_size: usize,
+) -> Result<usize, ProofTooLarge> {
+    let size = bcs::serialized_size(payload).expect("Proof payloads are serializable");
+    if size > max_size {
+        return Err(ProofTooLarge { size, max_size });
+    }
+    Ok(size)
+}
+
+/// A slice of a BCS encoded state value, used to keep responses bounded for large values
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StateValueChunk {
+    /// Position of the chunk in the encoded value
+    pub index: u64,
+    /// Number of chunks the encoded value was split into
+    pub num_chunks: u64,
+    /// Hash of the whole encoded value
+    pub content_hash: HashValue,
+    /// Bytes of the encoded value covered by this chunk
+    pub bytes: Vec<u8>,
+}
+
+impl StateValueChunk {
+    /// Splits the BCS encoding of `state_value` into chunks of at most `chunk_size` bytes.
+    pub fn split(state_value: &StateValue, chunk_size: usize) -> Vec<Self> {
+        let encoded = bcs::to_bytes(state_value).expect("StateValue serialization can't fail");
+        let content_hash = HashValue::sha3_256_of(&encoded);
+        let chunks: Vec<_> = encoded.chunks(chunk_size.max(1)).collect();
+        let num_chunks = chunks.len() as u64;
+        chunks
+            .into_iter()
+            .enumerate()
+            .map(|(index, bytes)| Self {
+                index: index as u64,
+                num_chunks,
+                content_hash,
+                bytes: bytes.to_vec(),
+            })
+            .collect()
+    }
+
+    /// Reassembles the state value from all of its chunks, in order, checking it against their
+    /// content hash.
+    pub fn reassemble(chunks: &[Self]) -> Result<StateValue, VerifyError> {
+        let first = chunks
+            .first()
+            .ok_or(VerifyError::MissingStateValueChunk { index: 0 })?;
+        let mut encoded = vec![];
+        for index in 0..first.num_chunks {
+            let chunk = chunks
+                .get(index as usize)
+                .filter(|chunk| {
+                    chunk.index == index
+                        && chunk.num_chunks == first.num_chunks
+                        && chunk.content_hash == first.content_hash
+                })
+                .ok_or(VerifyError::MissingStateValueChunk { index })?;
+            encoded.extend_from_slice(&chunk.bytes);
+        }
+
+        let actual = HashValue::sha3_256_of(&encoded);
+        if actual != first.content_hash {
+            return Err(VerifyError::ContentHashMismatch {
+                expected: first.content_hash,
+                actual,
+            });
+        }
+        bcs::from_bytes(&encoded).map_err(|error| VerifyError::InvalidStateValue {
+            error: error.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        check_encoded_size, verify_account_proof_for, verify_batch_account_proof,
+        verify_encoded_batch_account_proof, verify_resource_proof_for, AccountStateDeltaPayload,
+        CandidateEpoch, EpochChangeProofPayload, EventProofPayload, ProofEncodingError,
+        ProofTooLarge, ProvenResource, ResourceDelta, ResourceWithProof, StateAnchor,
+        StateProofPayload, StateProofView, StateValueChunk, TransactionProofPayload,
+    };
+    use crate::{
+        self_test::fixtures,
+        sparse_merkle_proof::CompressedSiblings,
+        test_utils::{
+            account_proof_payload, account_proof_payload_at_genesis,
+            account_proof_payload_at_reconfiguration, account_proof_payload_with_value,
+            batch_account_proof_payloads, event_proof_payload, ledger_info, sign, validator,
+        },
+        verify::{LedgerInfoVerifier, VerifyError},
+        LedgerInfo,
+    };
+    use aptos_crypto::{hash::CryptoHash, HashValue};
+    use aptos_types::{
+        account_address::AccountAddress,
+        account_config::{AccountResource, ChainIdResource},
+        aggregate_signature::AggregateSignature,
+        chain_id::ChainId,
+        contract_event::ContractEvent,
+        epoch_change::EpochChangeProof,
+        epoch_state::EpochState,
+        ledger_info::LedgerInfoWithSignatures,
+        proof::{SparseMerkleLeafNode, SparseMerkleProof, TransactionAccumulatorProof},
+        state_store::{state_key::StateKey, state_value::StateValue},
+        trusted_state::TrustedState,
+        validator_verifier::{self, ValidatorConsensusInfo, ValidatorVerifier},
+        waypoint::Waypoint,
+    };
+    use bytes::Bytes;
+    use move_core_types::{language_storage::TypeTag, move_resource::MoveStructType};
+    use std::cell::Cell;
+
+    #[test]
+    fn test_verify_returns_transaction() {
+        let (epoch_state, payload) = account_proof_payload();
+        let (version, transaction_hash) = payload.verify(&epoch_state).unwrap();
+        assert_eq!(version, payload.transaction_index);
+        assert_eq!(
+            transaction_hash,
+            payload.transaction.as_ref().unwrap().transaction_hash()
+        );
+    }
+
+    #[test]
+    fn test_verify_rejects_tampered_payload() {
+        let (epoch_state, payload) = account_proof_payload();
+
+        let mut wrong_index = payload.clone();
+        wrong_index.transaction_index -= 1;
+        assert!(matches!(
+            wrong_index.verify(&epoch_state),
+            Err(VerifyError::InvalidTransactionProof { version: 0, .. })
+        ));
+
+        // A leaf consistent with the claimed value, but not committed in the state tree
+        let mut wrong_value = payload;
+        wrong_value.element_hash = HashValue::zero();
+        wrong_value.state_proof = SparseMerkleProof::new(
+            Some(SparseMerkleLeafNode::new(
+                wrong_value.element_key,
+                wrong_value.element_hash,
+            )),
+            vec![],
+        );
+        assert!(matches!(
+            wrong_value.verify(&epoch_state),
+            Err(VerifyError::InvalidStateProof { .. })
+        ));
+    }
+
+    #[test]
+    fn test_verify_ledger_info_only() {
+        let fixtures = fixtures().unwrap();
+        let fixture = |name| {
+            fixtures
+                .iter()
+                .find(|fixture| fixture.name == name)
+                .unwrap()
+        };
+
+        let pass = fixture("pass");
+        assert_eq!(
+            pass.payload.verify_ledger_info_only(&pass.epoch_state),
+            Ok(pass.payload.ledger_info_v0.ledger_info())
+        );
+
+        // The state proof is never looked at
+        let state_mismatch = fixture("state_mismatch");
+        assert!(state_mismatch
+            .payload
+            .verify_ledger_info_only(&state_mismatch.epoch_state)
+            .is_ok());
+
+        let quorum_failure = fixture("quorum_failure");
+        assert!(matches!(
+            quorum_failure
+                .payload
+                .verify_ledger_info_only(&quorum_failure.epoch_state),
+            Err(VerifyError::InvalidSignatures {
+                error: validator_verifier::VerifyError::TooLittleVotingPower { .. },
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_verify_rejects_leaf_value_mismatch() {
+        let (epoch_state, mut payload) = account_proof_payload();
+        let leaf_value_hash = payload.element_hash;
+        payload.element_hash = HashValue::zero();
+        assert_eq!(
+            payload.verify(&epoch_state),
+            Err(VerifyError::LeafValueMismatch {
+                leaf_value_hash,
+                element_hash: HashValue::zero(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_verify_rejects_wrong_epoch_verifier() {
+        let (epoch_state, next_epoch_state, payload) = account_proof_payload_at_reconfiguration();
+
+        // Signatures still check out, but the attached set is the one of the next epoch
+        let mut wrong_verifier = payload;
+        wrong_verifier.validator_verifier = next_epoch_state.verifier;
+        assert_eq!(
+            wrong_verifier.verify(&epoch_state),
+            Err(VerifyError::ValidatorVerifierMismatch {
+                epoch: epoch_state.epoch
+            })
+        );
+    }
+
+    #[test]
+    fn test_verify_trimmed() {
+        let (epoch_state, payload) = account_proof_payload();
+        let trimmed = payload.trim().unwrap();
+        assert!(bcs::to_bytes(&trimmed).unwrap().len() < bcs::to_bytes(&payload).unwrap().len());
+        assert_eq!(trimmed.verify(&epoch_state), payload.verify(&epoch_state));
+        assert_eq!(
+            trimmed.clone().expand(payload.validator_verifier.clone()),
+            payload
+        );
+
+        let mut wrong_index = trimmed;
+        wrong_index.transaction_index -= 1;
+        assert!(matches!(
+            wrong_index.verify(&epoch_state),
+            Err(VerifyError::InvalidSignatures { .. })
+        ));
+    }
+
+    #[test]
+    fn test_verify_without_transaction_proof() {
+        let (epoch_state, payload) = account_proof_payload();
+        let state_root = payload
+            .transaction
+            .as_ref()
+            .unwrap()
+            .state_checkpoint_hash()
+            .unwrap();
+        let mut state_only = payload.clone();
+        state_only.transaction_proof = None;
+        state_only.transaction = None;
+
+        assert_eq!(
+            state_only.verify(&epoch_state),
+            Err(VerifyError::MissingTransactionProof {
+                version: payload.transaction_index
+            })
+        );
+        assert_eq!(state_only.trim(), None);
+        assert_eq!(state_only.verify_with_state_root(state_root), Ok(()));
+        assert!(matches!(
+            state_only.verify_with_state_root(HashValue::zero()),
+            Err(VerifyError::InvalidStateProof { .. })
+        ));
+    }
+
+    #[test]
+    fn test_verify_account_proof_for() {
+        // The payload only carries the hash of the state key
+        let (epoch_state, mut payload) = account_proof_payload();
+        payload.state_key = None;
+        assert_eq!(
+            verify_account_proof_for(AccountAddress::ONE, &payload, &epoch_state),
+            payload.verify(&epoch_state)
+        );
+        assert!(payload.verify(&epoch_state).is_ok());
+
+        assert!(matches!(
+            verify_account_proof_for(AccountAddress::TWO, &payload, &epoch_state),
+            Err(VerifyError::StateKeyMismatch { actual, .. }) if actual == payload.element_key
+        ));
+    }
+
+    #[test]
+    fn test_verify_resource_proof_for() {
+        // The fixture proves the account resource of `0x1`, derived from its short form
+        let (epoch_state, payload) = account_proof_payload();
+        let address = AccountAddress::from_hex_literal("0x1").unwrap();
+        assert_eq!(address, AccountAddress::ONE);
+        assert_eq!(
+            verify_resource_proof_for(
+                address,
+                &AccountResource::struct_tag(),
+                &payload,
+                &epoch_state
+            ),
+            payload.verify(&epoch_state)
+        );
+
+        let chain_id = ChainIdResource::struct_tag();
+        assert!(matches!(
+            verify_resource_proof_for(address, &chain_id, &payload, &epoch_state),
+            Err(VerifyError::StateKeyMismatch { expected, .. })
+                if expected == StateKey::resource(&address, &chain_id).unwrap().hash()
+        ));
+    }
+
+    #[test]
+    fn test_verify_rejects_accumulator_depth_mismatch() {
+        // Version 1 is in an accumulator of two leaves, so one sibling is expected
+        let (epoch_state, mut payload) = account_proof_payload();
+        let mut siblings = payload
+            .transaction_proof
+            .as_ref()
+            .unwrap()
+            .siblings()
+            .to_vec();
+        siblings.push(HashValue::zero());
+        payload.transaction_proof = Some(TransactionAccumulatorProof::new(siblings));
+        assert_eq!(
+            payload.verify(&epoch_state),
+            Err(VerifyError::AccumulatorDepthMismatch {
+                ledger_version: 1,
+                num_siblings: 2,
+                expected: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn test_verify_at_epoch_boundary() {
+        // The payload is signed by the validator of epoch 1
+        let (epoch_state, payload) = account_proof_payload();
+        let (version, transaction_hash) = payload.verify(&epoch_state).unwrap();
+
+        let previous = EpochState::new(0, validator(0).1);
+        assert_eq!(
+            payload.verify_at_epoch_boundary(&previous, &epoch_state),
+            Ok((CandidateEpoch::Next, version, transaction_hash))
+        );
+        let next = EpochState::new(2, validator(2).1);
+        assert_eq!(
+            payload.verify_at_epoch_boundary(&epoch_state, &next),
+            Ok((CandidateEpoch::Current, version, transaction_hash))
+        );
+
+        // Signed by neither
+        let after_next = EpochState::new(3, validator(3).1);
+        assert_eq!(
+            payload.verify_at_epoch_boundary(&next, &after_next),
+            Err(VerifyError::UnexpectedEpoch {
+                expected: 2,
+                actual: 1,
+            })
+        );
+        assert_eq!(
+            payload.verify_at_epoch_boundary(&previous, &next),
+            Err(VerifyError::NonIncrementingEpoch {
+                epoch: 0,
+                next_epoch: 2,
+            })
+        );
+    }
+
+    /// The anchor shared by the state proofs at the version of `payload`
+    fn state_anchor(payload: &StateProofPayload) -> StateAnchor {
+        StateAnchor {
+            transaction_proof: payload.transaction_proof.clone().unwrap(),
+            transaction: payload.transaction.clone().unwrap(),
+            transaction_index: payload.transaction_index,
+            ledger_info_v0: payload.ledger_info_v0.clone(),
+            validator_verifier: payload.validator_verifier.clone(),
+        }
+    }
+
+    #[test]
+    fn test_verify_account_state_delta() {
+        let (epoch_state, before) = account_proof_payload();
+        let (_, after) =
+            account_proof_payload_with_value(&StateValue::new_legacy(Bytes::from_static(b"other")));
+        let delta = |to: &StateProofPayload, changed| ResourceDelta {
+            state_key: before.state_key.clone().unwrap(),
+            from_value_hash: Some(before.element_hash),
+            from_proof: before.state_proof.clone(),
+            to_value_hash: Some(to.element_hash),
+            to_proof: to.state_proof.clone(),
+            changed,
+        };
+        // Both state trees only hold the account resource, so their leaf proves any other key is
+        // missing
+        let missing = ResourceDelta {
+            state_key: bcs::to_bytes(&StateKey::raw(b"missing")).unwrap(),
+            from_value_hash: None,
+            from_proof: before.state_proof.clone(),
+            to_value_hash: None,
+            to_proof: after.state_proof.clone(),
+            changed: false,
+        };
+        let payload = AccountStateDeltaPayload {
+            from: state_anchor(&before),
+            to: state_anchor(&after),
+            resources: vec![delta(&after, true), missing.clone()],
+            more: false,
+        };
+        assert_eq!(payload.verify(&epoch_state, &epoch_state), Ok(()));
+
+        let from_state_root = payload.from.verify(&epoch_state).unwrap();
+        let to_state_root = payload.to.verify(&epoch_state).unwrap();
+        assert_eq!(
+            delta(&before, false).verify(from_state_root, from_state_root),
+            Ok(before.state_key().unwrap().unwrap())
+        );
+        assert_eq!(
+            delta(&after, false).verify(from_state_root, to_state_root),
+            Err(VerifyError::ChangedFlagMismatch {
+                expected: true,
+                actual: false,
+            })
+        );
+        assert!(matches!(
+            delta(&after, true).verify(from_state_root, from_state_root),
+            Err(VerifyError::InvalidStateProof { .. })
+        ));
+
+        // The resource exists, it can't be proven missing
+        let mut deleted = delta(&after, true);
+        deleted.to_value_hash = None;
+        assert!(matches!(
+            deleted.verify(from_state_root, to_state_root),
+            Err(VerifyError::InvalidStateProof { .. })
+        ));
+        let mut changed = missing;
+        changed.changed = true;
+        assert_eq!(
+            changed.verify(from_state_root, to_state_root),
+            Err(VerifyError::ChangedFlagMismatch {
+                expected: false,
+                actual: true,
+            })
+        );
+    }
+
+    #[test]
+    fn test_verify_transaction_proof() {
+        let (epoch_state, payload) = account_proof_payload();
+        let proof = TransactionProofPayload {
+            transaction_proof: payload.transaction_proof.clone().unwrap(),
+            transaction: payload.transaction.clone().unwrap(),
+            transaction_index: payload.transaction_index,
+            ledger_info_v0: payload.ledger_info_v0.clone(),
+        };
+        assert_eq!(
+            proof.verify(&epoch_state),
+            Ok((
+                payload.transaction_index,
+                proof.transaction.transaction_hash()
+            ))
+        );
+
+        let mut other_version = proof.clone();
+        other_version.transaction_index -= 1;
+        assert!(matches!(
+            other_version.verify(&epoch_state),
+            Err(VerifyError::InvalidTransactionProof { .. })
+        ));
+
+        let (_, other_verifier) = validator(2);
+        assert!(matches!(
+            proof.verify(&EpochState::new(epoch_state.epoch, other_verifier)),
+            Err(VerifyError::InvalidSignatures { .. })
+        ));
+    }
+
+    #[test]
+    fn test_verify_event_proof() {
+        let (epoch_state, proof) = event_proof_payload();
+        assert_eq!(
+            proof.verify(&epoch_state),
+            Ok((0, proof.transaction.transaction_hash()))
+        );
+
+        let mut other_index = proof.clone();
+        other_index.event_index = 0;
+        assert!(matches!(
+            other_index.verify(&epoch_state),
+            Err(VerifyError::InvalidEventProof {
+                version: 0,
+                index: 0,
+                ..
+            })
+        ));
+
+        let mut other_event: EventProofPayload = proof;
+        other_event.event = ContractEvent::new_v2(TypeTag::Bool, vec![]);
+        assert!(matches!(
+            other_event.verify(&epoch_state),
+            Err(VerifyError::InvalidEventProof { .. })
+        ));
+    }
+
+    #[test]
+    fn test_verify_proven_resource() {
+        let state_value = StateValue::new_legacy(Bytes::from_static(b"resource"));
+        let (epoch_state, proof) = account_proof_payload_with_value(&state_value);
+        let resource = ProvenResource { state_value, proof };
+        assert_eq!(
+            resource.verify(&epoch_state),
+            Ok(StateKey::resource(&AccountAddress::ONE, &AccountResource::struct_tag()).unwrap())
+        );
+
+        let mut other_value = resource.clone();
+        other_value.state_value = StateValue::new_legacy(Bytes::from_static(b"other"));
+        assert!(matches!(
+            other_value.verify(&epoch_state),
+            Err(VerifyError::StateValueMismatch { .. })
+        ));
+
+        let mut without_key = resource;
+        without_key.proof.state_key = None;
+        assert!(matches!(
+            without_key.verify(&epoch_state),
+            Err(VerifyError::InvalidStateKey { .. })
+        ));
+    }
+
+    #[test]
+    fn test_verify_resource_with_proof() {
+        let state_value = StateValue::new_legacy(Bytes::from_static(b"resource"));
+        let (_, payload) = account_proof_payload_with_value(&state_value);
+        let state_key =
+            StateKey::resource(&AccountAddress::ONE, &AccountResource::struct_tag()).unwrap();
+        let state_root = payload
+            .transaction
+            .as_ref()
+            .unwrap()
+            .state_checkpoint_hash()
+            .unwrap();
+        let resource = ResourceWithProof {
+            version: payload.transaction_index,
+            state_value,
+            state_proof: payload.state_proof,
+        };
+        assert_eq!(resource.resource_bytes(), b"resource");
+        assert_eq!(resource.verify(&state_key, state_root), Ok(()));
+
+        let mut other_value = resource.clone();
+        other_value.state_value = StateValue::new_legacy(Bytes::from_static(b"other"));
+        assert!(matches!(
+            other_value.verify(&state_key, state_root),
+            Err(VerifyError::InvalidStateProof { .. })
+        ));
+
+        let other_key =
+            StateKey::resource(&AccountAddress::TWO, &AccountResource::struct_tag()).unwrap();
+        assert!(matches!(
+            resource.verify(&other_key, state_root),
+            Err(VerifyError::InvalidStateProof { .. })
+        ));
+    }
+
+    #[test]
+    fn test_is_equivalent() {
+        let (_, payload) = account_proof_payload();
+
+        // Served by a node with other options, and another signature aggregation
+        let mut other = payload.clone();
+        other.compressed_siblings = Some(CompressedSiblings::new(payload.state_proof.siblings()));
+        other.state_proof = SparseMerkleProof::new(payload.state_proof.leaf(), vec![]);
+        other.state_key = None;
+        other.validator_verifier = ValidatorVerifier::new(vec![]);
+        other.ledger_info_v0 = LedgerInfoWithSignatures::new(
+            payload.ledger_info_v0.ledger_info().clone(),
+            AggregateSignature::empty(),
+        );
+        assert!(payload.is_equivalent(&other));
+        assert_eq!(payload.canonicalize(), other.canonicalize());
+
+        // A lying node proving another value at the same version
+        let (_, divergent) =
+            account_proof_payload_with_value(&StateValue::new_legacy(Bytes::from_static(b"other")));
+        assert_eq!(divergent.transaction_index, payload.transaction_index);
+        assert!(!payload.is_equivalent(&divergent));
+
+        // Or the same value against another ledger info
+        let mut other_ledger_info = payload.clone();
+        let claimed = payload.ledger_info_v0.ledger_info();
+        other_ledger_info.ledger_info_v0 = LedgerInfoWithSignatures::new(
+            ledger_info(claimed.epoch(), claimed.version() + 1, None),
+            AggregateSignature::empty(),
+        );
+        assert!(!payload.is_equivalent(&other_ledger_info));
+    }
+
+    #[test]
+    fn test_verify_not_stale() {
+        // The payload is signed at version 1
+        let (epoch_state, payload) = account_proof_payload();
+        let verified = payload.verify(&epoch_state);
+        assert_eq!(
+            payload.verify_not_stale(&epoch_state, None, false),
+            verified
+        );
+        assert_eq!(
+            payload.verify_not_stale(&epoch_state, Some(1), false),
+            verified
+        );
+
+        assert_eq!(
+            payload.verify_not_stale(&epoch_state, Some(2), false),
+            Err(VerifyError::StaleLedgerInfo {
+                version: 1,
+                trusted_version: 2,
+            })
+        );
+        assert_eq!(
+            payload.verify_not_stale(&epoch_state, Some(2), true),
+            verified
+        );
+    }
+
+    #[test]
+    fn test_verify_compressed_siblings() {
+        let (epoch_state, payload) = account_proof_payload();
+        let mut compressed = payload.clone();
+        compressed.compressed_siblings =
+            Some(CompressedSiblings::new(payload.state_proof.siblings()));
+        assert_eq!(compressed.full_state_proof().unwrap(), payload.state_proof);
+        assert_eq!(
+            compressed.verify(&epoch_state),
+            payload.verify(&epoch_state)
+        );
+    }
+
+    #[test]
+    fn test_verify_at_reconfiguration() {
+        let (epoch_state, next_epoch_state, payload) = account_proof_payload_at_reconfiguration();
+        assert!(payload.ledger_info_v0.ledger_info().ends_epoch());
+        assert_eq!(
+            payload.verify(&epoch_state).unwrap().0,
+            payload.transaction_index
+        );
+
+        // The validators carried by the ledger info only take over in the next epoch
+        assert_eq!(
+            payload.verify(&next_epoch_state),
+            Err(VerifyError::UnexpectedEpoch {
+                expected: 2,
+                actual: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn test_verify_with_waypoint() {
+        let (_, _, payload) = account_proof_payload_at_reconfiguration();
+        let waypoint = Waypoint::new_epoch_boundary(payload.ledger_info_v0.ledger_info()).unwrap();
+        payload.verify_with_waypoint(&waypoint).unwrap();
+
+        let (_, other) = account_proof_payload();
+        assert!(matches!(
+            other.verify_with_waypoint(&waypoint),
+            Err(VerifyError::WaypointMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_verify_with_trusted_ledger_info() {
+        let (_, payload) = account_proof_payload();
+        assert_eq!(
+            payload.verify_with_trusted_ledger_info(&payload.ledger_info_v0),
+            Ok((
+                payload.transaction_index,
+                payload.transaction.as_ref().unwrap().transaction_hash()
+            ))
+        );
+
+        // A ledger info at another version than the one the proof claims
+        let claimed = payload.ledger_info_v0.ledger_info();
+        let (signer, verifier) = validator(claimed.epoch());
+        let external = sign(
+            &signer,
+            &verifier,
+            ledger_info(claimed.epoch(), claimed.version() + 1, None),
+        );
+        assert_eq!(
+            payload.verify_with_trusted_ledger_info(&external),
+            Err(VerifyError::LedgerInfoMismatch {
+                expected: external.ledger_info().hash(),
+                actual: claimed.hash(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_verify_genesis() {
+        let payload = account_proof_payload_at_genesis();
+        let genesis_waypoint =
+            Waypoint::new_epoch_boundary(payload.ledger_info_v0.ledger_info()).unwrap();
+        assert_eq!(
+            payload.verify_with_waypoint(&genesis_waypoint),
+            Ok((0, payload.transaction.as_ref().unwrap().transaction_hash()))
+        );
+
+        // Without signatures, genesis can't be verified against an epoch state
+        let epoch_state = EpochState::new(0, validator(1).1);
+        assert_eq!(
+            payload.verify(&epoch_state),
+            Err(VerifyError::UnsignedGenesis)
+        );
+
+        let (_, other) = account_proof_payload();
+        let other_waypoint = Waypoint::new_any(other.ledger_info_v0.ledger_info());
+        assert!(matches!(
+            payload.verify_with_waypoint(&other_waypoint),
+            Err(VerifyError::WaypointMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_hex_round_trip() {
+        let (epoch_state, payload) = account_proof_payload();
+        let encoded = payload.to_hex();
+        assert!(encoded.starts_with(&hex::encode(b"APRF")));
+
+        let decoded = StateProofPayload::from_hex(&encoded).unwrap();
+        assert_eq!(decoded, payload);
+        decoded.verify(&epoch_state).unwrap();
+        assert_eq!(
+            StateProofPayload::from_hex(&format!("0x{}", encoded)).unwrap(),
+            payload
+        );
+    }
+
+    #[test]
+    fn test_hex_rejects_unknown_format() {
+        let (_, payload) = account_proof_payload();
+        let encoded = payload.to_hex();
+
+        let wrong_magic = format!("{}{}", hex::encode(b"ABCD"), &encoded[8..]);
+        assert_eq!(
+            StateProofPayload::from_hex(&wrong_magic),
+            Err(ProofEncodingError::InvalidMagic)
+        );
+        let wrong_version = format!("{}01{}", &encoded[..8], &encoded[10..]);
+        assert_eq!(
+            StateProofPayload::from_hex(&wrong_version),
+            Err(ProofEncodingError::UnsupportedVersion(1))
+        );
+        assert!(matches!(
+            StateProofPayload::from_hex("not hex"),
+            Err(ProofEncodingError::InvalidHex(_))
+        ));
+        assert!(matches!(
+            StateProofPayload::from_hex(&encoded[..encoded.len() - 2]),
+            Err(ProofEncodingError::InvalidBcs(_))
+        ));
+    }
+
+    /// Counts the ledger infos it authenticates
+    struct CountingVerifier {
+        epoch_state: EpochState,
+        count: Cell<usize>,
+    }
+
+    impl LedgerInfoVerifier for CountingVerifier {
+        fn verify_ledger_info(
+            &self,
+            ledger_info_with_sigs: &LedgerInfoWithSignatures,
+        ) -> Result<(), VerifyError> {
+            self.count.set(self.count.get() + 1);
+            self.epoch_state.verify_ledger_info(ledger_info_with_sigs)
+        }
+    }
+
+    #[test]
+    fn test_verify_batch_account_proof() {
+        let (epoch_state, mut proofs) = batch_account_proof_payloads();
+        let verifier = CountingVerifier {
+            epoch_state,
+            count: Cell::new(0),
+        };
+
+        let results = verify_batch_account_proof(&verifier, &proofs).unwrap();
+        assert_eq!(results.len(), 3);
+        assert!(results
+            .iter()
+            .all(|result| result.as_ref().unwrap().0 == proofs[0].transaction_index));
+        assert_eq!(verifier.count.get(), 1);
+
+        // A bad sub-proof only fails its own result
+        proofs[1].element_hash = HashValue::zero();
+        let results = verify_batch_account_proof(&verifier, &proofs).unwrap();
+        assert!(results[0].is_ok());
+        assert!(matches!(
+            results[1],
+            Err(VerifyError::LeafValueMismatch { .. })
+        ));
+        assert!(results[2].is_ok());
+    }
+
+    #[test]
+    fn test_verify_batch_account_proof_rejects_inconsistent_batch() {
+        let (epoch_state, mut proofs) = batch_account_proof_payloads();
+        let (_, _, other) = account_proof_payload_at_reconfiguration();
+
+        // A proof of the same version, anchored to another ledger info
+        let mut mixed = proofs.clone();
+        mixed[1] = other;
+        assert_eq!(
+            verify_batch_account_proof(&epoch_state, &mixed),
+            Err(VerifyError::InconsistentBatch { index: 1 })
+        );
+
+        // A proof of another version, anchored to the same ledger info
+        proofs[2].transaction_index = 0;
+        assert_eq!(
+            verify_batch_account_proof(&epoch_state, &proofs),
+            Err(VerifyError::InconsistentBatch { index: 2 })
+        );
+        let mut results = vec![];
+        verify_encoded_batch_account_proof(
+            &epoch_state,
+            &bcs::to_bytes(&proofs).unwrap(),
+            |result| results.push(result),
+        )
+        .unwrap();
+        assert!(results[0].is_ok());
+        assert!(results[1].is_ok());
+        assert_eq!(results[2], Err(VerifyError::InconsistentBatch { index: 2 }));
+    }
+
+    #[test]
+    fn test_verify_encoded_batch_account_proof() {
+        let (epoch_state, proofs) = batch_account_proof_payloads();
+        let verifier = CountingVerifier {
+            epoch_state,
+            count: Cell::new(0),
+        };
+        let mut batch = proofs.repeat(1_000);
+        batch[1_500].element_hash = HashValue::zero();
+        let bytes = bcs::to_bytes(&batch).unwrap();
+
+        let mut results = vec![];
+        assert_eq!(
+            verify_encoded_batch_account_proof(&verifier, &bytes, |result| results.push(result)),
+            Ok(3_000)
+        );
+        assert_eq!(verifier.count.get(), 1);
+        assert_eq!(
+            results,
+            verify_batch_account_proof(&verifier, &batch).unwrap()
+        );
+        assert!(matches!(
+            results[1_500],
+            Err(VerifyError::LeafValueMismatch { .. })
+        ));
+
+        // Everything before a truncated proof is still reported
+        let mut num_results = 0;
+        assert!(matches!(
+            verify_encoded_batch_account_proof(&verifier, &bytes[..bytes.len() - 1], |_| {
+                num_results += 1
+            }),
+            Err(ProofEncodingError::InvalidBcs(_))
+        ));
+        assert_eq!(num_results, 2_999);
+    }
+
+    #[test]
+    fn test_sanity_check() {
+        let (_, payload) = account_proof_payload();
+        let ledger_info = |oldest_ledger_version| {
+            LedgerInfo::new(
+                &ChainId::test(),
+                &payload.ledger_info_v0,
+                oldest_ledger_version,
+                0,
+                1,
+            )
+        };
+        payload.sanity_check(&ledger_info(0)).unwrap();
+        payload.sanity_check(&ledger_info(1)).unwrap();
+
+        // The server claims to have pruned the version it proved
+        assert_eq!(
+            payload.sanity_check(&ledger_info(2)),
+            Err(VerifyError::VersionPruned {
+                version: 1,
+                oldest_version: 2,
+            })
+        );
+
+        let ledger_info = ledger_info(0);
+        let mut ahead = payload;
+        ahead.transaction_index = 2;
+        assert_eq!(
+            ahead.sanity_check(&ledger_info),
+            Err(VerifyError::VersionAheadOfLedgerInfo {
+                version: 2,
+                ledger_version: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn test_state_key_hashes_to_element_key() {
+        let (epoch_state, payload) = account_proof_payload();
+        let state_key = payload.state_key().unwrap().unwrap();
+        assert_eq!(state_key.hash(), payload.element_key);
+
+        let mut without_state_key = payload.clone();
+        without_state_key.state_key = None;
+        assert_eq!(without_state_key.state_key(), Ok(None));
+        without_state_key.verify(&epoch_state).unwrap();
+
+        let mut wrong_state_key = payload;
+        let other = StateKey::raw(b"other");
+        wrong_state_key.state_key = Some(bcs::to_bytes(&other).unwrap());
+        assert_eq!(
+            wrong_state_key.verify(&epoch_state),
+            Err(VerifyError::StateKeyMismatch {
+                expected: wrong_state_key.element_key,
+                actual: other.hash(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_verify_waypoint() {
+        let (signer, verifier) = validator(1);
+        let epoch_state = EpochState::new(1, verifier.clone());
+        let penultimate_li = ledger_info(0, 9, Some(epoch_state.clone()));
+        let payload = EpochChangeProofPayload {
+            trusted_state: TrustedState::EpochState {
+                waypoint: Waypoint::new_any(&penultimate_li),
+                epoch_state,
+            },
+            epoch_change_proof: EpochChangeProof::new(
+                vec![sign(
+                    &signer,
+                    &verifier,
+                    ledger_info(1, 19, Some(EpochState::new(2, verifier.clone()))),
+                )],
+                false,
+            ),
+            next_epoch_state_present: true,
+        };
+        assert_eq!(payload.verify_waypoint(&penultimate_li), Ok(()));
+
+        // Same ledger info, at another version
+        let wrong_version = ledger_info(0, 10, Some(EpochState::new(1, verifier.clone())));
+        assert_eq!(
+            payload.verify_waypoint(&wrong_version),
+            Err(VerifyError::WaypointVersionMismatch {
+                expected: 10,
+                actual: 9,
+            })
+        );
+
+        // Same version, with another validator set for the next epoch
+        let wrong_value = ledger_info(0, 9, Some(EpochState::new(1, validator(2).1)));
+        assert_eq!(
+            payload.verify_waypoint(&wrong_value),
+            Err(VerifyError::WaypointValueMismatch {
+                expected: Waypoint::new_any(&wrong_value).value(),
+                actual: Waypoint::new_any(&penultimate_li).value(),
+            })
+        );
+
+        // A malformed epoch boundary only comes with its waypoint, which can still be checked
+        let malformed_li = ledger_info(0, 9, None);
+        let malformed = EpochChangeProofPayload {
+            trusted_state: TrustedState::EpochWaypoint(Waypoint::new_any(&malformed_li)),
+            next_epoch_state_present: false,
+            ..payload
+        };
+        assert_eq!(malformed.verify_waypoint(&malformed_li), Ok(()));
+        assert_eq!(
+            bcs::from_bytes::<EpochChangeProofPayload>(&bcs::to_bytes(&malformed).unwrap())
+                .unwrap(),
+            malformed
+        );
+    }
+
+    #[test]
+    fn test_size_breakdown() {
+        let (_, mut payload) = account_proof_payload();
+        payload.compressed_siblings = Some(CompressedSiblings::new(payload.state_proof.siblings()));
+        let breakdown = payload.size_breakdown();
+        assert_eq!(
+            breakdown.state_proof.0
+                + breakdown.accumulator_proof.0
+                + breakdown.ledger_info.0
+                + breakdown.validator_set.0
+                + breakdown.other.0,
+            breakdown.total.0
+        );
+        assert_eq!(
+            breakdown.total.0,
+            bcs::to_bytes(&payload).unwrap().len() as u64
+        );
+        assert_eq!(
+            breakdown.validator_set.0,
+            bcs::to_bytes(&payload.validator_verifier).unwrap().len() as u64
+        );
+    }
+
+    #[test]
+    fn test_check() {
+        let fixtures = fixtures().unwrap();
+        let check = |name: &str| {
+            let fixture = fixtures
+                .iter()
+                .find(|fixture| fixture.name == name)
+                .unwrap();
+            fixture.payload.check(&fixture.epoch_state)
+        };
+
+        assert!(check("pass").passed());
+
+        let report = check("quorum_failure");
+        assert!(report.state_proof.passed);
+        assert!(report.transaction_proof.passed);
+        assert!(!report.signatures.passed);
+        assert!(report.signatures.error.is_some());
+
+        let report = check("state_mismatch");
+        assert!(!report.state_proof.passed);
+        assert!(report.transaction_proof.passed);
+        assert!(report.signatures.passed);
+    }
+
+    #[test]
+    fn test_state_proof_view() {
+        let (_, payload) = account_proof_payload();
+        let view = StateProofView::try_from(payload.clone()).unwrap();
+        assert_eq!(
+            aptos_crypto::HashValue::from(view.element_key.clone()),
+            payload.element_key
+        );
+        assert_eq!(view.transaction_index.0, payload.transaction_index);
+        assert_eq!(
+            view.state_proof.siblings.len(),
+            payload.state_proof.siblings().len()
+        );
+        assert_eq!(
+            aptos_crypto::HashValue::from(view.transaction.clone().unwrap().transaction_hash),
+            payload.transaction.as_ref().unwrap().transaction_hash()
+        );
+
+        let json = serde_json::to_string(&view).unwrap();
+        assert_eq!(serde_json::from_str::<StateProofView>(&json).unwrap(), view);
+    }
+
+    #[test]
+    fn test_check_encoded_size() {
+        // Validators can share a key, only the size of the set matters
+        let (signer, verifier) = validator(1);
+        let large_verifier = ValidatorVerifier::new(
+            (0..1_000)
+                .map(|index| {
+                    ValidatorConsensusInfo::new(
+                        AccountAddress::from_hex_literal(&format!("0x{:x}", index)).unwrap(),
+                        signer.public_key(),
+                        1,
+                    )
+                })
+                .collect(),
+        );
+        let ending_li = ledger_info(1, 99, Some(EpochState::new(2, large_verifier)));
+        let payload = EpochChangeProofPayload {
+            trusted_state: TrustedState::EpochState {
+                waypoint: Waypoint::new_any(&ending_li),
+                epoch_state: EpochState::new(1, verifier.clone()),
+            },
+            epoch_change_proof: EpochChangeProof::new(
+                vec![sign(&signer, &verifier, ending_li)],
+                false,
+            ),
+            next_epoch_state_present: true,
+        };
+
+        let size = bcs::to_bytes(&payload).unwrap().len();
+        assert!(size > 64 * 1024);
+        assert_eq!(check_encoded_size(&payload, size), Ok(size));
+        assert_eq!(
+            check_encoded_size(&payload, 64 * 1024),
+            Err(ProofTooLarge {
+                size,
+                max_size: 64 * 1024
+            })
+        );
+    }
+
+    #[test]
+    fn test_reassemble_large_state_value() {
+        let state_value = StateValue::new_legacy(
+            (0..5 * 1024 * 1024)
+                .map(|i| (i % 251) as u8)
+                .collect::<Vec<_>>()
+                .into(),
+        );
+        let (epoch_state, payload) = account_proof_payload_with_value(&state_value);
+        payload.verify(&epoch_state).unwrap();
+
+        let chunks = StateValueChunk::split(&state_value, 1024 * 1024);
+        assert_eq!(chunks.len(), 6);
+        assert!(chunks.iter().all(|chunk| chunk.bytes.len() <= 1024 * 1024));
+
+        let reassembled = StateValueChunk::reassemble(&chunks).unwrap();
+        assert_eq!(reassembled, state_value);
+        payload.verify_state_value(&reassembled).unwrap();
+
+        // Chunks must all be present and in order
+        assert_eq!(
+            StateValueChunk::reassemble(&chunks[..5]),
+            Err(VerifyError::MissingStateValueChunk { index: 5 })
+        );
+        let mut reordered = chunks.clone();
+        reordered.swap(1, 2);
+        assert_eq!(
+            StateValueChunk::reassemble(&reordered),
+            Err(VerifyError::MissingStateValueChunk { index: 1 })
+        );
+
+        let mut tampered = chunks;
+        tampered[3].bytes[0] ^= 1;
+        assert!(matches!(
+            StateValueChunk::reassemble(&tampered),
+            Err(VerifyError::ContentHashMismatch { .. })
+        ));
+    }
+}
@@ -0,0 +1,213 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Description of the BCS layout of the proofs served by the API, for clients decoding them
+//! without the Rust types.
+//!
+//! BCS encodes struct fields in declaration order without their names, and enum variants as their
+//! index followed by their fields, so the order of the fields and variants below is the layout.
+
+use poem_openapi::Object as PoemObject;
+use serde::{Deserialize, Serialize};
+
+/// Version of the proof schema, bumped whenever the layout of a described type changes
+pub const PROOF_SCHEMA_VERSION: u16 = 2;
+
+/// Fields of `AccountProofPayload`, also named `StateProofPayload`
+const ACCOUNT_PROOF_PAYLOAD_FIELDS: &[(&str, &str)] = &[
+    ("state_proof", "SparseMerkleProof"),
+    ("element_key", "HashValue"),
+    ("element_hash", "HashValue"),
+    ("transaction_proof", "Option<TransactionAccumulatorProof>"),
+    ("transaction", "Option<TransactionInfo>"),
+    ("transaction_index", "u64"),
+    ("ledger_info_v0", "LedgerInfoWithSignatures"),
+    ("validator_verifier", "ValidatorVerifier"),
+    ("state_key", "Option<Vec<u8>>"),
+    ("value_chunk", "Option<StateValueChunk>"),
+    ("compressed_siblings", "Option<CompressedSiblings>"),
+];
+
+/// Fields of `EpochChangeProofPayload`
+const EPOCH_CHANGE_PROOF_PAYLOAD_FIELDS: &[(&str, &str)] = &[
+    ("epoch_change_proof", "EpochChangeProof"),
+    ("trusted_state", "TrustedState"),
+    ("next_epoch_state_present", "bool"),
+];
+
+/// Variants of `TrustedState`, in index order, with their fields
+const TRUSTED_STATE_VARIANTS: &[(&str, &[(&str, &str)])] = &[
+    ("EpochWaypoint", &[("0", "Waypoint")]),
+    ("EpochState", &[
+        ("waypoint", "Waypoint"),
+        ("epoch_state", "EpochState"),
+    ]),
+];
+
+/// Versioned description of the BCS layout of the proof payloads
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, PoemObject)]
+pub struct ProofSchema {
+    /// Version of the schema, see [`PROOF_SCHEMA_VERSION`]
+    pub version: u16,
+    pub types: Vec<TypeSchema>,
+}
+
+impl ProofSchema {
+    pub fn new() -> Self {
+        Self {
+            version: PROOF_SCHEMA_VERSION,
+            types: vec![
+                TypeSchema::new_struct("AccountProofPayload", ACCOUNT_PROOF_PAYLOAD_FIELDS),
+                TypeSchema::new_struct(
+                    "EpochChangeProofPayload",
+                    EPOCH_CHANGE_PROOF_PAYLOAD_FIELDS,
+                ),
+                TypeSchema::new_enum("TrustedState", TRUSTED_STATE_VARIANTS),
+            ],
+        }
+    }
+}
+
+impl Default for ProofSchema {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Layout of a struct, or of an enum if it has variants
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, PoemObject)]
+pub struct TypeSchema {
+    pub name: String,
+    /// Fields of a struct, in encoding order. Empty for an enum
+    pub fields: Vec<FieldSchema>,
+    /// Variants of an enum, in index order. Empty for a struct
+    pub variants: Vec<VariantSchema>,
+}
+
+impl TypeSchema {
+    fn new_struct(name: &str, fields: &[(&str, &str)]) -> Self {
+        Self {
+            name: name.to_string(),
+            fields: FieldSchema::list(fields),
+            variants: vec![],
+        }
+    }
+
+    fn new_enum(name: &str, variants: &[(&str, &[(&str, &str)])]) -> Self {
+        Self {
+            name: name.to_string(),
+            fields: vec![],
+            variants: variants
+                .iter()
+                .enumerate()
+                .map(|(index, (name, fields))| VariantSchema {
+                    index: index as u32,
+                    name: name.to_string(),
+                    fields: FieldSchema::list(fields),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A variant of an enum, encoded as its index followed by its fields
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, PoemObject)]
+pub struct VariantSchema {
+    pub index: u32,
+    pub name: String,
+    /// Fields of the variant, in encoding order. Fields of a tuple variant are named after their
+    /// position
+    pub fields: Vec<FieldSchema>,
+}
+
+/// A field and the name of its Rust type in `aptos-types`
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, PoemObject)]
+pub struct FieldSchema {
+    pub name: String,
+    #[serde(rename = "type")]
+    #[oai(rename = "type")]
+    pub typ: String,
+}
+
+impl FieldSchema {
+    fn list(fields: &[(&str, &str)]) -> Vec<Self> {
+        fields
+            .iter()
+            .map(|(name, typ)| Self {
+                name: name.to_string(),
+                typ: typ.to_string(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ProofSchema, ACCOUNT_PROOF_PAYLOAD_FIELDS, EPOCH_CHANGE_PROOF_PAYLOAD_FIELDS,
+        TRUSTED_STATE_VARIANTS,
+    };
+    use crate::{
+        proof_payload::EpochChangeProofPayload,
+        test_utils::{account_proof_payload, ledger_info, validator},
+    };
+    use aptos_types::{
+        epoch_change::EpochChangeProof, epoch_state::EpochState, trusted_state::TrustedState,
+        waypoint::Waypoint,
+    };
+    use serde::Serialize;
+    use serde_json::Value;
+
+    /// Names of the fields of `value`, in declaration order
+    fn field_names(value: &impl Serialize) -> Vec<String> {
+        match serde_json::to_value(value).unwrap() {
+            Value::Object(fields) => fields.keys().cloned().collect(),
+            value => panic!("Not a struct: {}", value),
+        }
+    }
+
+    fn schema_names(fields: &[(&str, &str)]) -> Vec<String> {
+        fields.iter().map(|(name, _)| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_proof_schema_in_sync() {
+        let (_, payload) = account_proof_payload();
+        assert_eq!(
+            field_names(&payload),
+            schema_names(ACCOUNT_PROOF_PAYLOAD_FIELDS)
+        );
+
+        let (_, verifier) = validator(1);
+        let waypoint = Waypoint::new_any(&ledger_info(1, 10, None));
+        let trusted_state = TrustedState::EpochState {
+            waypoint,
+            epoch_state: EpochState::new(1, verifier),
+        };
+        let payload = EpochChangeProofPayload {
+            epoch_change_proof: EpochChangeProof::new(vec![], false),
+            trusted_state: trusted_state.clone(),
+            next_epoch_state_present: true,
+        };
+        assert_eq!(
+            field_names(&payload),
+            schema_names(EPOCH_CHANGE_PROOF_PAYLOAD_FIELDS)
+        );
+
+        // Only the struct variant has named fields to compare
+        match &trusted_state {
+            TrustedState::EpochWaypoint(_) | TrustedState::EpochState { .. } => {
+                assert_eq!(TRUSTED_STATE_VARIANTS.len(), 2)
+            },
+        }
+        let variant = serde_json::to_value(&trusted_state).unwrap();
+        assert_eq!(
+            field_names(&variant["EpochState"]),
+            schema_names(TRUSTED_STATE_VARIANTS[1].1)
+        );
+
+        let schema = ProofSchema::new();
+        assert_eq!(schema.types.len(), 3);
+        assert_eq!(schema.types[2].variants[1].index, 1);
+    }
+}
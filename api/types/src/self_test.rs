@@ -0,0 +1,284 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Self-test of the proof verifier, for integrators to run at startup and confirm verification
+//! behaves as expected in their environment before trusting live proofs.
+//!
+//! The fixtures are shipped as constant BCS bytes, built once from fixed keys, blocks and values,
+//! so they are identical on every run and each comes with its expected outcome. Nothing is signed
+//! or hashed to produce them at runtime, only to verify them.
+
+use crate::{proof_payload::StateProofPayload, verify::VerifyError};
+use aptos_crypto::HashValue;
+use aptos_types::{epoch_state::EpochState, transaction::Version, validator_verifier};
+use thiserror::Error;
+
+/// Version the fixture account state is proven at
+const FIXTURE_VERSION: Version = 1;
+
+/// BCS encoded epoch state of the fixtures: epoch 1, with two validators of voting power 1
+const FIXTURE_EPOCH_STATE: &[u8] = include_bytes!("data/self_test/epoch_state.bcs");
+
+/// Outcome of verifying a fixture
+type Outcome = Result<(Version, HashValue), VerifyError>;
+
+/// The BCS encoded proofs of the self-test, with their expected outcomes, see [`fixtures`]
+const FIXTURES: [(&str, &[u8], fn(&Outcome) -> bool); 3] = [
+    (
+        "pass",
+        include_bytes!("data/self_test/pass.bcs"),
+        expect_pass,
+    ),
+    (
+        "quorum_failure",
+        include_bytes!("data/self_test/quorum_failure.bcs"),
+        expect_quorum_failure,
+    ),
+    (
+        "state_mismatch",
+        include_bytes!("data/self_test/state_mismatch.bcs"),
+        expect_state_mismatch,
+    ),
+];
+
+fn expect_pass(outcome: &Outcome) -> bool {
+    matches!(outcome, Ok((FIXTURE_VERSION, _)))
+}
+
+fn expect_quorum_failure(outcome: &Outcome) -> bool {
+    matches!(
+        outcome,
+        Err(VerifyError::InvalidSignatures {
+            error: validator_verifier::VerifyError::TooLittleVotingPower { .. },
+            ..
+        })
+    )
+}
+
+fn expect_state_mismatch(outcome: &Outcome) -> bool {
+    matches!(outcome, Err(VerifyError::InvalidStateProof { .. }))
+}
+
+/// Errors possible while running the self-test
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SelfTestError {
+    #[error("Self-test fixture {fixture} is not valid BCS: {error}")]
+    InvalidFixture {
+        fixture: &'static str,
+        error: String,
+    },
+    #[error("Self-test fixture {fixture} verified unexpectedly: {outcome:?}")]
+    UnexpectedOutcome {
+        fixture: &'static str,
+        outcome: Outcome,
+    },
+}
+
+/// A proof and the trusted epoch state to verify it against, with its expected outcome
+pub struct SelfTestFixture {
+    pub name: &'static str,
+    pub epoch_state: EpochState,
+    pub payload: StateProofPayload,
+    expected: fn(&Outcome) -> bool,
+}
+
+impl SelfTestFixture {
+    /// Verifies the fixture, failing if the outcome isn't the expected one.
+    pub fn run(&self) -> Result<(), SelfTestError> {
+        let outcome = self.payload.verify(&self.epoch_state);
+        if (self.expected)(&outcome) {
+            Ok(())
+        } else {
+            Err(SelfTestError::UnexpectedOutcome {
+                fixture: self.name,
+                outcome,
+            })
+        }
+    }
+}
+
+/// Decodes and runs every fixture of [`fixtures`], stopping at the first unexpected outcome.
+pub fn run_self_test() -> Result<(), SelfTestError> {
+    fixtures()?.iter().try_for_each(SelfTestFixture::run)
+}
+
+/// Decodes the constant fixtures of the self-test:
+/// - `pass`: a valid proof, signed by both validators of the epoch;
+/// - `quorum_failure`: the same proof, signed by only one of the two validators;
+/// - `state_mismatch`: the valid proof, for an account state value other than the committed one.
+pub fn fixtures() -> Result<Vec<SelfTestFixture>, SelfTestError> {
+    let epoch_state: EpochState =
+        bcs::from_bytes(FIXTURE_EPOCH_STATE).map_err(|err| SelfTestError::InvalidFixture {
+            fixture: "epoch_state",
+            error: err.to_string(),
+        })?;
+    FIXTURES
+        .iter()
+        .map(|&(name, bytes, expected)| {
+            bcs::from_bytes(bytes)
+                .map(|payload| SelfTestFixture {
+                    name,
+                    epoch_state: epoch_state.clone(),
+                    payload,
+                    expected,
+                })
+                .map_err(|err| SelfTestError::InvalidFixture {
+                    fixture: name,
+                    error: err.to_string(),
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fixtures, run_self_test, FIXTURES, FIXTURE_EPOCH_STATE, FIXTURE_VERSION};
+    use crate::proof_payload::StateProofPayload;
+    use aptos_crypto::{bls12381, hash::CryptoHash, HashValue};
+    use aptos_types::{
+        account_address::AccountAddress,
+        account_config::AccountResource,
+        aggregate_signature::PartialSignatures,
+        block_info::BlockInfo,
+        epoch_state::EpochState,
+        ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+        proof::{
+            SparseMerkleLeafNode, SparseMerkleProof, TransactionAccumulatorInternalNode,
+            TransactionAccumulatorProof,
+        },
+        state_store::{state_key::StateKey, state_value::StateValue},
+        transaction::{ExecutionStatus, TransactionInfo},
+        validator_signer::ValidatorSigner,
+        validator_verifier::{ValidatorConsensusInfo, ValidatorVerifier},
+    };
+    use bytes::Bytes;
+    use move_core_types::move_resource::MoveStructType;
+    use std::collections::BTreeMap;
+
+    /// A validator whose address and private key are both derived from `seed`
+    fn fixture_signer(seed: u8) -> ValidatorSigner {
+        ValidatorSigner::new(
+            AccountAddress::new([seed; AccountAddress::LENGTH]),
+            bls12381::PrivateKey::try_from([seed; 32].as_slice()).unwrap(),
+        )
+    }
+
+    /// A proof of the account resource of `0x1`, with state value `value`, at version 1, signed
+    /// by `signers`
+    fn fixture_payload(
+        signers: &[ValidatorSigner],
+        verifier: &ValidatorVerifier,
+        value: &'static [u8],
+    ) -> StateProofPayload {
+        // The account is the only leaf of the state tree, so the leaf is also the root
+        let state_key =
+            StateKey::resource(&AccountAddress::ONE, &AccountResource::struct_tag()).unwrap();
+        let committed = SparseMerkleLeafNode::new(
+            state_key.hash(),
+            StateValue::new_legacy(Bytes::from_static(b"self-test account")).hash(),
+        );
+        let leaf = SparseMerkleLeafNode::new(
+            state_key.hash(),
+            StateValue::new_legacy(Bytes::from_static(value)).hash(),
+        );
+
+        // Version 1 is the right child of the two leaf transaction accumulator
+        let genesis = TransactionInfo::new(
+            HashValue::sha3_256_of(b"self-test genesis"),
+            HashValue::zero(),
+            HashValue::zero(),
+            None,
+            0,
+            ExecutionStatus::Success,
+        );
+        let transaction = TransactionInfo::new(
+            HashValue::sha3_256_of(b"self-test transaction"),
+            HashValue::zero(),
+            HashValue::zero(),
+            Some(committed.hash()),
+            0,
+            ExecutionStatus::Success,
+        );
+        let accumulator_root =
+            TransactionAccumulatorInternalNode::new(genesis.hash(), transaction.hash()).hash();
+
+        let ledger_info = LedgerInfo::new(
+            BlockInfo::new(
+                1,
+                1,
+                HashValue::sha3_256_of(b"self-test block"),
+                accumulator_root,
+                FIXTURE_VERSION,
+                1_000,
+                None,
+            ),
+            HashValue::zero(),
+        );
+        let partial_signatures = PartialSignatures::new(
+            signers
+                .iter()
+                .map(|signer| (signer.author(), signer.sign(&ledger_info).unwrap()))
+                .collect::<BTreeMap<_, _>>(),
+        );
+        let signatures = verifier.aggregate_signatures(&partial_signatures).unwrap();
+
+        StateProofPayload {
+            state_proof: SparseMerkleProof::new(Some(leaf), vec![]),
+            element_key: leaf.key(),
+            element_hash: leaf.value_hash(),
+            transaction_proof: Some(TransactionAccumulatorProof::new(vec![genesis.hash()])),
+            transaction: Some(transaction),
+            transaction_index: FIXTURE_VERSION,
+            ledger_info_v0: LedgerInfoWithSignatures::new(ledger_info, signatures),
+            validator_verifier: verifier.clone(),
+            state_key: Some(bcs::to_bytes(&state_key).unwrap()),
+            value_chunk: None,
+            compressed_siblings: None,
+        }
+    }
+
+    #[test]
+    fn test_run_self_test() {
+        assert_eq!(run_self_test(), Ok(()));
+    }
+
+    #[test]
+    fn test_fixtures() {
+        let fixtures = fixtures().unwrap();
+        assert_eq!(
+            fixtures
+                .iter()
+                .map(|fixture| fixture.name)
+                .collect::<Vec<_>>(),
+            ["pass", "quorum_failure", "state_mismatch"]
+        );
+        for fixture in fixtures {
+            assert_eq!(fixture.run(), Ok(()), "{}", fixture.name);
+        }
+    }
+
+    /// The constant fixtures are the ones built from their fixed keys, blocks and values.
+    #[test]
+    fn test_fixture_bytes() {
+        let signers = [fixture_signer(1), fixture_signer(2)];
+        let verifier = ValidatorVerifier::new(
+            signers
+                .iter()
+                .map(|signer| ValidatorConsensusInfo::new(signer.author(), signer.public_key(), 1))
+                .collect(),
+        );
+        assert_eq!(
+            bcs::to_bytes(&EpochState::new(1, verifier.clone())).unwrap(),
+            FIXTURE_EPOCH_STATE
+        );
+
+        let built = [
+            fixture_payload(&signers, &verifier, b"self-test account"),
+            fixture_payload(&signers[..1], &verifier, b"self-test account"),
+            fixture_payload(&signers, &verifier, b"another account"),
+        ];
+        for ((name, bytes, _), payload) in FIXTURES.iter().zip(built) {
+            assert_eq!(bcs::to_bytes(&payload).unwrap(), *bytes, "{}", name);
+        }
+    }
+}
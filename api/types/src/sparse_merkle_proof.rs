@@ -2,10 +2,14 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{HashValue};
+use crate::{verify::VerifyError, HashValue};
+use aptos_crypto::hash::{CryptoHash, SPARSE_MERKLE_PLACEHOLDER_HASH};
+use aptos_types::proof::{
+    SparseMerkleInternalNode, SparseMerkleLeafNode as InternLeafNode,
+    SparseMerkleProof as InternProof,
+};
 use poem_openapi::Object as PoemObject;
 use serde::{Deserialize, Serialize};
-use aptos_types::proof::{SparseMerkleLeafNode as InternLeafNode, SparseMerkleProof as InternProof};
 
 /// A SparseMerkleProof
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, PoemObject)]
@@ -14,12 +18,59 @@ pub struct SparseMerkleProof {
     pub siblings: Vec<HashValue>,
 }
 
-impl From<InternProof> for SparseMerkleProof {
-    fn from(proof: InternProof) -> Self {
-        Self {
-            leaf: proof.leaf().map(|leaf| leaf.into()),
-            siblings: proof.siblings().iter().map(|sibling| HashValue::from(*sibling)).collect::<Vec<HashValue>>(),
+impl SparseMerkleProof {
+    /// Root hash of the tree in which `element_key` maps to `element_hash`, following the same
+    /// path as [`InternProof::verify_by_hash`]: the leaf is hashed up through the siblings, from
+    /// the deepest one, on the side given by the matching bit of the key.
+    ///
+    /// Siblings beyond [`MAX_SPARSE_MERKLE_PROOF_SIBLINGS`] have no bit of the key left and are
+    /// ignored, such proofs are rejected when converted from an [`InternProof`].
+    pub fn root_hash(&self, element_key: HashValue, element_hash: HashValue) -> HashValue {
+        let element_key = aptos_crypto::HashValue::from(element_key);
+        let leaf_hash = InternLeafNode::new(element_key, element_hash.into()).hash();
+        let root_hash = self
+            .siblings
+            .iter()
+            .rev()
+            .zip(
+                element_key
+                    .iter_bits()
+                    .rev()
+                    .skip(MAX_SPARSE_MERKLE_PROOF_SIBLINGS.saturating_sub(self.siblings.len())),
+            )
+            .fold(leaf_hash, |hash, (sibling_hash, bit)| {
+                if bit {
+                    SparseMerkleInternalNode::new((*sibling_hash).into(), hash).hash()
+                } else {
+                    SparseMerkleInternalNode::new(hash, (*sibling_hash).into()).hash()
+                }
+            });
+        root_hash.into()
+    }
+}
+
+/// Maximum number of siblings of a proof, one per bit of the hashed key
+pub const MAX_SPARSE_MERKLE_PROOF_SIBLINGS: usize = aptos_crypto::HashValue::LENGTH * 8;
+
+impl TryFrom<InternProof> for SparseMerkleProof {
+    type Error = VerifyError;
+
+    fn try_from(proof: InternProof) -> Result<Self, Self::Error> {
+        let num_siblings = proof.siblings().len();
+        if num_siblings > MAX_SPARSE_MERKLE_PROOF_SIBLINGS {
+            return Err(VerifyError::TooManySiblings {
+                num_siblings,
+                max: MAX_SPARSE_MERKLE_PROOF_SIBLINGS,
+            });
         }
+        Ok(Self {
+            leaf: proof.leaf().map(|leaf| leaf.into()),
+            siblings: proof
+                .siblings()
+                .iter()
+                .map(|sibling| HashValue::from(*sibling))
+                .collect::<Vec<HashValue>>(),
+        })
     }
 }
 
@@ -30,11 +81,180 @@ pub struct SparseMerkleLeafNode {
     pub value: HashValue,
 }
 
-impl From<InternLeafNode> for  SparseMerkleLeafNode {
+impl From<InternLeafNode> for SparseMerkleLeafNode {
     fn from(value: InternLeafNode) -> Self {
         Self {
             key: value.key().into(),
             value: value.value_hash().into(),
         }
     }
-}
\ No newline at end of file
+}
+
+/// The siblings of a sparse merkle proof, with the placeholder hashes of empty subtrees elided
+///
+/// Deep proofs are mostly made of placeholders, so this is usually a lot smaller than the full
+/// list of siblings.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct CompressedSiblings {
+    /// Number of siblings in the full proof
+    pub num_siblings: u16,
+    /// Bit `i` (least significant bit first within each byte) is set if sibling `i` is the
+    /// placeholder hash
+    pub placeholder_bitmap: Vec<u8>,
+    /// The siblings that aren't placeholders, in order
+    pub siblings: Vec<aptos_crypto::HashValue>,
+}
+
+impl CompressedSiblings {
+    pub fn new(siblings: &[aptos_crypto::HashValue]) -> Self {
+        let mut placeholder_bitmap = vec![0u8; (siblings.len() + 7) / 8];
+        let mut non_placeholders = vec![];
+        for (i, sibling) in siblings.iter().enumerate() {
+            if *sibling == *SPARSE_MERKLE_PLACEHOLDER_HASH {
+                placeholder_bitmap[i / 8] |= 1 << (i % 8);
+            } else {
+                non_placeholders.push(*sibling);
+            }
+        }
+        Self {
+            num_siblings: siblings.len() as u16,
+            placeholder_bitmap,
+            siblings: non_placeholders,
+        }
+    }
+
+    /// Reconstructs the full list of siblings, to be verified as part of a [`InternProof`].
+    pub fn decompress(&self) -> Result<Vec<aptos_crypto::HashValue>, VerifyError> {
+        let num_siblings = self.num_siblings as usize;
+        if self.placeholder_bitmap.len() != (num_siblings + 7) / 8 {
+            return Err(VerifyError::MalformedCompressedSiblings {
+                error: format!(
+                    "Bitmap of {} bytes for {} siblings",
+                    self.placeholder_bitmap.len(),
+                    num_siblings
+                ),
+            });
+        }
+        if num_siblings % 8 != 0
+            && self.placeholder_bitmap[num_siblings / 8] >> (num_siblings % 8) != 0
+        {
+            return Err(VerifyError::MalformedCompressedSiblings {
+                error: "Bitmap has bits set past the last sibling".to_string(),
+            });
+        }
+
+        let mut non_placeholders = self.siblings.iter();
+        let siblings = (0..num_siblings)
+            .map(|i| {
+                if self.placeholder_bitmap[i / 8] & (1 << (i % 8)) != 0 {
+                    Ok(*SPARSE_MERKLE_PLACEHOLDER_HASH)
+                } else {
+                    non_placeholders.next().copied().ok_or_else(|| {
+                        VerifyError::MalformedCompressedSiblings {
+                            error: format!("Missing sibling {}", i),
+                        }
+                    })
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if non_placeholders.next().is_some() {
+            return Err(VerifyError::MalformedCompressedSiblings {
+                error: "More siblings than the bitmap accounts for".to_string(),
+            });
+        }
+        Ok(siblings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CompressedSiblings, MAX_SPARSE_MERKLE_PROOF_SIBLINGS};
+    use crate::verify::VerifyError;
+    use aptos_crypto::{hash::SPARSE_MERKLE_PLACEHOLDER_HASH, HashValue};
+    use aptos_types::proof::{SparseMerkleLeafNode, SparseMerkleProof};
+
+    fn siblings(num_siblings: usize, every: usize) -> Vec<HashValue> {
+        (0..num_siblings)
+            .map(|i| {
+                if i % every == 0 {
+                    HashValue::sha3_256_of(&i.to_le_bytes())
+                } else {
+                    *SPARSE_MERKLE_PLACEHOLDER_HASH
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for (num_siblings, every) in [(0, 1), (1, 1), (7, 2), (8, 3), (9, 1), (64, 100)] {
+            let siblings = siblings(num_siblings, every);
+            let compressed = CompressedSiblings::new(&siblings);
+            assert_eq!(compressed.decompress().unwrap(), siblings);
+        }
+    }
+
+    #[test]
+    fn test_deep_proof_size() {
+        let siblings = siblings(256, 16);
+        let full = bcs::to_bytes(&SparseMerkleProof::new(None, siblings.clone())).unwrap();
+        let compressed = bcs::to_bytes(&CompressedSiblings::new(&siblings)).unwrap();
+        // 16 of the 256 siblings are kept, along with a 32 byte bitmap
+        assert!(compressed.len() * 10 < full.len());
+    }
+
+    #[test]
+    fn test_malformed() {
+        let mut compressed = CompressedSiblings::new(&siblings(9, 2));
+        compressed.siblings.pop();
+        assert!(matches!(
+            compressed.decompress(),
+            Err(VerifyError::MalformedCompressedSiblings { .. })
+        ));
+
+        let mut compressed = CompressedSiblings::new(&siblings(9, 2));
+        compressed.placeholder_bitmap[1] |= 1 << 1;
+        assert!(matches!(
+            compressed.decompress(),
+            Err(VerifyError::MalformedCompressedSiblings { .. })
+        ));
+    }
+
+    #[test]
+    fn test_siblings_bound() {
+        let proof = SparseMerkleProof::new(None, siblings(MAX_SPARSE_MERKLE_PROOF_SIBLINGS, 3));
+        let view = super::SparseMerkleProof::try_from(proof).unwrap();
+        assert_eq!(view.siblings.len(), MAX_SPARSE_MERKLE_PROOF_SIBLINGS);
+
+        let proof = SparseMerkleProof::new(None, siblings(MAX_SPARSE_MERKLE_PROOF_SIBLINGS + 1, 3));
+        assert_eq!(
+            super::SparseMerkleProof::try_from(proof),
+            Err(VerifyError::TooManySiblings {
+                num_siblings: MAX_SPARSE_MERKLE_PROOF_SIBLINGS + 1,
+                max: MAX_SPARSE_MERKLE_PROOF_SIBLINGS,
+            })
+        );
+    }
+
+    #[test]
+    fn test_root_hash() {
+        let element_key = HashValue::sha3_256_of(b"key");
+        let element_hash = HashValue::sha3_256_of(b"value");
+        let leaf = SparseMerkleLeafNode::new(element_key, element_hash);
+        for num_siblings in [0, 1, 9, MAX_SPARSE_MERKLE_PROOF_SIBLINGS] {
+            let proof = SparseMerkleProof::new(Some(leaf), siblings(num_siblings, 3));
+            let view = super::SparseMerkleProof::try_from(proof.clone()).unwrap();
+            let root_hash = view.root_hash(element_key.into(), element_hash.into());
+            proof
+                .verify_by_hash(root_hash.into(), element_key, Some(element_hash))
+                .unwrap();
+
+            let other_hash = HashValue::sha3_256_of(b"other value");
+            let other_root_hash = view.root_hash(element_key.into(), other_hash.into());
+            assert_ne!(other_root_hash, root_hash);
+            assert!(proof
+                .verify_by_hash(other_root_hash.into(), element_key, Some(element_hash))
+                .is_err());
+        }
+    }
+}
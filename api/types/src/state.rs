@@ -1,7 +1,9 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::HexEncodedBytes;
+use crate::{Address, HexEncodedBytes, MoveStructTag, VerifyInput, VerifyInputWithRecursion};
+use aptos_crypto::HashValue;
+use aptos_types::state_store::state_value::StateValue;
 use poem_openapi::Object;
 use serde::{Deserialize, Serialize};
 
@@ -10,3 +12,29 @@ use serde::{Deserialize, Serialize};
 pub struct RawStateValueRequest {
     pub key: HexEncodedBytes,
 }
+
+/// A state value returned by the GetRawStateValue and GetRawTableItem APIs, along with the hash of
+/// its state key, the key of its leaf in the sparse merkle tree
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateValueWithKeyHash {
+    pub value: StateValue,
+    pub key_hash: HashValue,
+}
+
+/// Entry of the batch account proof API
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct AccountProofRequest {
+    pub address: Address,
+    /// Struct to prove instead of the account resource, e.g.
+    /// `0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>`
+    pub resource_type: Option<MoveStructTag>,
+}
+
+impl VerifyInput for AccountProofRequest {
+    fn verify(&self) -> anyhow::Result<()> {
+        match &self.resource_type {
+            Some(resource_type) => resource_type.verify(0),
+            None => Ok(()),
+        }
+    }
+}
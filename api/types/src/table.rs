@@ -1,7 +1,9 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{HexEncodedBytes, MoveType, VerifyInput, VerifyInputWithRecursion};
+use crate::{
+    AptosError, HexEncodedBytes, MoveType, MoveValue, VerifyInput, VerifyInputWithRecursion,
+};
 use poem_openapi::Object;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -27,3 +29,18 @@ impl VerifyInput for TableItemRequest {
 pub struct RawTableItemRequest {
     pub key: HexEncodedBytes,
 }
+
+/// Outcome of one item of a batch table item request, with either the value of the item or the
+/// error that prevented reading it
+#[derive(Clone, Debug, Serialize, Object)]
+pub struct TableItemResult {
+    pub value: Option<MoveValue>,
+    pub error: Option<AptosError>,
+}
+
+/// BCS form of [`TableItemResult`], with the BCS encoded value of the item
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RawTableItemResult {
+    pub value: Option<Vec<u8>>,
+    pub error: Option<AptosError>,
+}
@@ -0,0 +1,346 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Helpers for building signed ledger infos and epoch change proofs in tests
+
+use crate::proof_payload::{EventProofPayload, StateProofPayload};
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_types::{
+    account_address::AccountAddress,
+    account_config::AccountResource,
+    aggregate_signature::{AggregateSignature, PartialSignatures},
+    block_info::BlockInfo,
+    contract_event::ContractEvent,
+    epoch_change::EpochChangeProof,
+    epoch_state::EpochState,
+    event::EventKey,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    proof::{
+        EventAccumulatorInternalNode, EventAccumulatorProof, SparseMerkleInternalNode,
+        SparseMerkleLeafNode, SparseMerkleProof, TransactionAccumulatorInternalNode,
+        TransactionAccumulatorProof,
+    },
+    state_store::{state_key::StateKey, state_value::StateValue},
+    transaction::{ExecutionStatus, TransactionInfo},
+    validator_signer::ValidatorSigner,
+    validator_verifier::{ValidatorConsensusInfo, ValidatorVerifier},
+};
+use bytes::Bytes;
+use move_core_types::{language_storage::TypeTag, move_resource::MoveStructType};
+use std::collections::BTreeMap;
+
+/// Number of versions committed in every test epoch
+pub(crate) const VERSIONS_PER_EPOCH: u64 = 100;
+
+/// A single validator with all of the voting power for an epoch
+pub(crate) fn validator(seed: u64) -> (ValidatorSigner, ValidatorVerifier) {
+    let signer = ValidatorSigner::random([seed as u8; 32]);
+    let verifier = ValidatorVerifier::new(vec![ValidatorConsensusInfo::new(
+        signer.author(),
+        signer.public_key(),
+        1,
+    )]);
+    (signer, verifier)
+}
+
+pub(crate) fn sign(
+    signer: &ValidatorSigner,
+    verifier: &ValidatorVerifier,
+    ledger_info: LedgerInfo,
+) -> LedgerInfoWithSignatures {
+    let signature = signer.sign(&ledger_info).unwrap();
+    let partial_signatures = PartialSignatures::new(BTreeMap::from([(signer.author(), signature)]));
+    LedgerInfoWithSignatures::new(
+        ledger_info,
+        verifier.aggregate_signatures(&partial_signatures).unwrap(),
+    )
+}
+
+/// A ledger info at `version` in `epoch`, optionally ending the epoch with `next_epoch_state`
+pub(crate) fn ledger_info(
+    epoch: u64,
+    version: u64,
+    next_epoch_state: Option<EpochState>,
+) -> LedgerInfo {
+    LedgerInfo::new(
+        BlockInfo::new(
+            epoch,
+            0,
+            HashValue::zero(),
+            HashValue::zero(),
+            version,
+            version * 1_000,
+            next_epoch_state,
+        ),
+        HashValue::zero(),
+    )
+}
+
+/// A chain of signed epoch-ending ledger infos and the keys needed to extend it
+pub(crate) struct TestEpochChain {
+    /// The epoch state trusted before the proof
+    pub trusted: EpochState,
+    pub proof: EpochChangeProof,
+    /// The epoch state the proof ratchets to
+    pub latest: EpochState,
+    /// The validator of the `latest` epoch
+    pub latest_signer: ValidatorSigner,
+}
+
+impl TestEpochChain {
+    /// Builds a proof of `num_epochs` epoch changes, starting from `start_epoch`
+    pub(crate) fn new(start_epoch: u64, num_epochs: u64) -> Self {
+        let (mut signer, mut verifier) = validator(start_epoch);
+        let trusted = EpochState::new(start_epoch, verifier.clone());
+
+        let mut ledger_infos = vec![];
+        for epoch in start_epoch..start_epoch + num_epochs {
+            let (next_signer, next_verifier) = validator(epoch + 1);
+            let next_epoch_state = EpochState::new(epoch + 1, next_verifier.clone());
+            let ledger_info = ledger_info(
+                epoch,
+                (epoch + 1) * VERSIONS_PER_EPOCH - 1,
+                Some(next_epoch_state),
+            );
+            ledger_infos.push(sign(&signer, &verifier, ledger_info));
+            signer = next_signer;
+            verifier = next_verifier;
+        }
+
+        Self {
+            trusted,
+            proof: EpochChangeProof::new(ledger_infos, false),
+            latest: EpochState::new(start_epoch + num_epochs, verifier),
+            latest_signer: signer,
+        }
+    }
+}
+
+/// A verifiable proof of the account resource of `0x1` at version 1, along with the epoch state
+/// whose validator signed it
+pub(crate) fn account_proof_payload() -> (EpochState, StateProofPayload) {
+    account_proof_payload_with_value(&StateValue::new_legacy(Bytes::from_static(b"account")))
+}
+
+/// Same as [`account_proof_payload`], proving `state_value` as the account resource
+pub(crate) fn account_proof_payload_with_value(
+    state_value: &StateValue,
+) -> (EpochState, StateProofPayload) {
+    build_account_proof_payload(state_value, None)
+}
+
+/// Same as [`account_proof_payload`], at the last version of epoch 1. Also returns the state of
+/// epoch 2, carried by the ledger info.
+pub(crate) fn account_proof_payload_at_reconfiguration(
+) -> (EpochState, EpochState, StateProofPayload) {
+    let next_epoch_state = EpochState::new(2, validator(2).1);
+    let (epoch_state, payload) = build_account_proof_payload(
+        &StateValue::new_legacy(Bytes::from_static(b"account")),
+        Some(next_epoch_state.clone()),
+    );
+    (epoch_state, next_epoch_state, payload)
+}
+
+/// A proof of the account resource of `0x1` at genesis, whose ledger info carries no signatures
+pub(crate) fn account_proof_payload_at_genesis() -> StateProofPayload {
+    let state_key =
+        StateKey::resource(&AccountAddress::ONE, &AccountResource::struct_tag()).unwrap();
+    let leaf = SparseMerkleLeafNode::new(
+        state_key.hash(),
+        StateValue::new_legacy(Bytes::from_static(b"account")).hash(),
+    );
+
+    // Genesis is the only leaf of the transaction accumulator, so the leaf is also the root
+    let genesis = TransactionInfo::new(
+        HashValue::sha3_256_of(b"genesis"),
+        HashValue::zero(),
+        HashValue::zero(),
+        Some(leaf.hash()),
+        0,
+        ExecutionStatus::Success,
+    );
+    let ledger_info = LedgerInfo::new(
+        BlockInfo::new(
+            0,
+            0,
+            HashValue::zero(),
+            genesis.hash(),
+            0,
+            0,
+            Some(EpochState::new(1, validator(1).1)),
+        ),
+        HashValue::zero(),
+    );
+
+    StateProofPayload {
+        state_proof: SparseMerkleProof::new(Some(leaf), vec![]),
+        element_key: leaf.key(),
+        element_hash: leaf.value_hash(),
+        transaction_proof: Some(TransactionAccumulatorProof::new(vec![])),
+        transaction: Some(genesis),
+        transaction_index: 0,
+        ledger_info_v0: LedgerInfoWithSignatures::new(ledger_info, AggregateSignature::empty()),
+        validator_verifier: validator(1).1,
+        state_key: Some(bcs::to_bytes(&state_key).unwrap()),
+        value_chunk: None,
+        compressed_siblings: None,
+    }
+}
+
+/// Proofs of three accounts in the same state tree, all anchored to the same ledger info
+pub(crate) fn batch_account_proof_payloads() -> (EpochState, Vec<StateProofPayload>) {
+    // The keys differ in their first two bits, so the tree is
+    //         root
+    //        /    \
+    //       .      C
+    //      / \
+    //     A   B
+    let leaves: Vec<_> = [0x00, 0x40, 0x80]
+        .into_iter()
+        .map(|first_byte| {
+            let mut key = [0u8; HashValue::LENGTH];
+            key[0] = first_byte;
+            SparseMerkleLeafNode::new(HashValue::new(key), HashValue::sha3_256_of(&[first_byte]))
+        })
+        .collect();
+    let (a, b, c) = (leaves[0].hash(), leaves[1].hash(), leaves[2].hash());
+    let ab = SparseMerkleInternalNode::new(a, b).hash();
+    let state_root = SparseMerkleInternalNode::new(ab, c).hash();
+
+    let mut epoch_state = None;
+    let payloads = leaves
+        .into_iter()
+        .zip([vec![c, b], vec![c, a], vec![ab]])
+        .map(|(leaf, siblings)| {
+            let (trusted, payload) = anchor_state_proof(
+                SparseMerkleProof::new(Some(leaf), siblings),
+                state_root,
+                None,
+            );
+            epoch_state = Some(trusted);
+            payload
+        })
+        .collect();
+    (epoch_state.unwrap(), payloads)
+}
+
+fn build_account_proof_payload(
+    state_value: &StateValue,
+    next_epoch_state: Option<EpochState>,
+) -> (EpochState, StateProofPayload) {
+    // The account is the only leaf of the state tree, so the leaf is also the root
+    let state_key =
+        StateKey::resource(&AccountAddress::ONE, &AccountResource::struct_tag()).unwrap();
+    let leaf = SparseMerkleLeafNode::new(state_key.hash(), state_value.hash());
+    let (epoch_state, mut payload) = anchor_state_proof(
+        SparseMerkleProof::new(Some(leaf), vec![]),
+        leaf.hash(),
+        next_epoch_state,
+    );
+    payload.state_key = Some(bcs::to_bytes(&state_key).unwrap());
+    (epoch_state, payload)
+}
+
+/// A proof of the second of the two events emitted by the transaction at version 0, signed by the
+/// validator of epoch 1
+pub(crate) fn event_proof_payload() -> (EpochState, EventProofPayload) {
+    let (signer, verifier) = validator(1);
+    let events: Vec<_> = (0..2)
+        .map(|sequence_number| {
+            ContractEvent::new_v1(
+                EventKey::new(0, AccountAddress::ONE),
+                sequence_number,
+                TypeTag::U64,
+                bcs::to_bytes(&sequence_number).unwrap(),
+            )
+        })
+        .collect();
+    let event_root = EventAccumulatorInternalNode::new(events[0].hash(), events[1].hash()).hash();
+
+    // The transaction is the only leaf of the transaction accumulator, so the leaf is also the root
+    let transaction = TransactionInfo::new(
+        HashValue::sha3_256_of(b"transaction"),
+        HashValue::zero(),
+        event_root,
+        None,
+        0,
+        ExecutionStatus::Success,
+    );
+    let ledger_info = LedgerInfo::new(
+        BlockInfo::new(1, 0, HashValue::zero(), transaction.hash(), 0, 1_000, None),
+        HashValue::zero(),
+    );
+
+    let payload = EventProofPayload {
+        event: events[1].clone(),
+        event_index: 1,
+        event_proof: EventAccumulatorProof::new(vec![events[0].hash()]),
+        transaction_proof: TransactionAccumulatorProof::new(vec![]),
+        transaction,
+        transaction_index: 0,
+        ledger_info_v0: sign(&signer, &verifier, ledger_info),
+    };
+    (EpochState::new(1, verifier), payload)
+}
+
+/// Anchors the inclusion `state_proof` to a ledger info at version 1, signed by the validator of
+/// epoch 1. Signing is deterministic, so the same `state_root` always gets the same ledger info.
+fn anchor_state_proof(
+    state_proof: SparseMerkleProof,
+    state_root: HashValue,
+    next_epoch_state: Option<EpochState>,
+) -> (EpochState, StateProofPayload) {
+    let (signer, verifier) = validator(1);
+    let epoch_state = EpochState::new(1, verifier.clone());
+    let leaf = state_proof
+        .leaf()
+        .expect("Only inclusion proofs are anchored");
+
+    // Version 1 is the right child of the two leaf transaction accumulator
+    let genesis = TransactionInfo::new(
+        HashValue::sha3_256_of(b"genesis"),
+        HashValue::zero(),
+        HashValue::zero(),
+        None,
+        0,
+        ExecutionStatus::Success,
+    );
+    let transaction = TransactionInfo::new(
+        HashValue::sha3_256_of(b"transaction"),
+        HashValue::zero(),
+        HashValue::zero(),
+        Some(state_root),
+        0,
+        ExecutionStatus::Success,
+    );
+    let accumulator_root =
+        TransactionAccumulatorInternalNode::new(genesis.hash(), transaction.hash()).hash();
+
+    let ledger_info = LedgerInfo::new(
+        BlockInfo::new(
+            1,
+            0,
+            HashValue::zero(),
+            accumulator_root,
+            1,
+            1_000,
+            next_epoch_state,
+        ),
+        HashValue::zero(),
+    );
+
+    let payload = StateProofPayload {
+        state_proof,
+        element_key: leaf.key(),
+        element_hash: leaf.value_hash(),
+        transaction_proof: Some(TransactionAccumulatorProof::new(vec![genesis.hash()])),
+        transaction: Some(transaction),
+        transaction_index: 1,
+        ledger_info_v0: sign(&signer, &verifier, ledger_info),
+        validator_verifier: verifier,
+        state_key: None,
+        value_chunk: None,
+        compressed_siblings: None,
+    };
+    (epoch_state, payload)
+}
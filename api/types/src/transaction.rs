@@ -371,6 +371,31 @@ impl From<aptos_types::proof::TransactionAccumulatorProof> for TransactionAccumu
     }
 }
 
+/// The info of a transaction hashed into the transaction accumulator, as carried by proofs
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct TransactionInfoV0 {
+    pub gas_used: U64,
+    /// The execution status of the transaction
+    pub status: String,
+    pub transaction_hash: HashValue,
+    pub event_root_hash: HashValue,
+    pub state_change_hash: HashValue,
+    pub state_checkpoint_hash: Option<HashValue>,
+}
+
+impl From<aptos_types::transaction::TransactionInfo> for TransactionInfoV0 {
+    fn from(value: aptos_types::transaction::TransactionInfo) -> Self {
+        Self {
+            gas_used: value.gas_used().into(),
+            status: format!("{:?}", value.status()),
+            transaction_hash: value.transaction_hash().into(),
+            event_root_hash: value.event_root_hash().into(),
+            state_change_hash: value.state_change_hash().into(),
+            state_checkpoint_hash: value.state_checkpoint_hash().map(Into::into),
+        }
+    }
+}
+
 /// Information related to how a transaction affected the state of the blockchain
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
 pub struct TransactionInfo {
@@ -1,48 +1,450 @@
-use crate::ledger_info::EpochState;
-use crate::waypoint::Waypoint;
-use poem_openapi::Object as PoemObject;
-use serde::{Deserialize, Serialize};
+use crate::{
+    epoch_state_store::InMemoryEpochStateStore,
+    ledger_info::EpochState,
+    verify::{ratchet_epoch_state, VerifyError},
+    waypoint::Waypoint,
+};
+use aptos_types::epoch_change::EpochChangeProof;
+use poem_openapi::{Object as PoemObject, Union};
+use serde::{
+    de::{self, MapAccess, SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize,
+};
+use std::{convert::TryFrom, fmt};
 
-#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, PoemObject)]
+/// Index of the `EpochWaypoint` variant of [`TrustedState`]
+const EPOCH_WAYPOINT_VARIANT: u8 = 0;
+/// Index of the `EpochState` variant of [`TrustedState`]
+const EPOCH_STATE_VARIANT: u8 = 1;
+
+/// Encoded as its variant index followed by the fields of that variant, the way
+/// [`aptos_types::trusted_state::TrustedState`] is.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, PoemObject)]
 pub struct TrustedState {
     pub variant: u8,
     pub data: TrustedStateData,
 }
 
 impl TrustedState {
+    pub fn new_epoch_waypoint(waypoint: Waypoint) -> Self {
+        Self {
+            variant: EPOCH_WAYPOINT_VARIANT,
+            data: TrustedStateData::EpochWaypoint(EpochWaypointData { waypoint }),
+        }
+    }
+
     pub fn new_epoch_state(waypoint: Waypoint, epoch_state: EpochState) -> Self {
         Self {
-            variant: 1,
-            data: TrustedStateData {
+            variant: EPOCH_STATE_VARIANT,
+            data: TrustedStateData::EpochState(EpochStateData {
                 waypoint,
                 epoch_state,
+            }),
+        }
+    }
+
+    /// The waypoint, if this is the `EpochWaypoint` variant
+    pub fn as_waypoint(&self) -> Option<&Waypoint> {
+        match &self.data {
+            TrustedStateData::EpochWaypoint(data) => Some(&data.waypoint),
+            TrustedStateData::EpochState(_) => None,
+        }
+    }
+
+    /// The epoch state, if this is the `EpochState` variant
+    pub fn as_epoch_state(&self) -> Option<&EpochState> {
+        self.data.epoch_state()
+    }
+
+    /// Verifies `proof` from this trusted state, returning the state trusted after the last epoch
+    /// change it proves, as [`aptos_types::trusted_state::TrustedState::verify_and_ratchet_inner`]
+    /// does.
+    ///
+    /// A trusted waypoint must be the one of the first ledger info of `proof`, the rest of which is
+    /// then verified from the epoch state that ledger info carries. Ledger infos of epochs older
+    /// than the trusted one are skipped, and a proof of no newer epoch change leaves the state as
+    /// is.
+    pub fn verify_and_ratchet(&self, proof: &EpochChangeProof) -> Result<Self, VerifyError> {
+        let first = proof
+            .ledger_info_with_sigs
+            .first()
+            .ok_or(VerifyError::EmptyEpochChangeProof)?;
+        let (trusted, rest) = match self.as_epoch_state() {
+            Some(epoch_state) => (
+                aptos_types::epoch_state::EpochState::try_from(epoch_state.clone()).map_err(
+                    |error| VerifyError::InvalidTrustedState {
+                        error: error.to_string(),
+                    },
+                )?,
+                &proof.ledger_info_with_sigs[..],
+            ),
+            None => {
+                let ledger_info = first.ledger_info();
+                let expected = aptos_types::waypoint::Waypoint::new_any(ledger_info);
+                let waypoint = self.data.waypoint();
+                if waypoint.version() != expected.version()
+                    || waypoint.value() != expected.value().into()
+                {
+                    return Err(VerifyError::WaypointMismatch {
+                        error: format!("expected {}, got {}", waypoint, expected),
+                    });
+                }
+                let next_epoch_state =
+                    ledger_info
+                        .next_epoch_state()
+                        .ok_or(VerifyError::MissingNextEpochState {
+                            epoch: ledger_info.epoch(),
+                        })?;
+                (next_epoch_state.clone(), &proof.ledger_info_with_sigs[1..])
             },
+        };
+
+        let latest = if rest.is_empty() {
+            trusted.clone()
+        } else {
+            ratchet_epoch_state(
+                &trusted,
+                &EpochChangeProof::new(rest.to_vec(), proof.more),
+                &mut InMemoryEpochStateStore::new(0),
+            )?
+        };
+
+        // The new waypoint is the one of the verified ledger info ending the epoch before the
+        // latest one. Skipped ledger infos are all of epochs older than the trusted one.
+        let ending = if latest.epoch > trusted.epoch {
+            rest.iter()
+                .find(|li| li.ledger_info().epoch() + 1 == latest.epoch)
+                .expect("Ratcheting goes through the ledger info ending each epoch")
+        } else if self.as_epoch_state().is_none() {
+            first
+        } else {
+            return Ok(self.clone());
+        };
+        let waypoint = aptos_types::waypoint::Waypoint::new_epoch_boundary(ending.ledger_info())
+            .expect("Ledger infos ending an epoch convert to waypoints");
+        Ok(Self::new_epoch_state(waypoint.into(), latest.into()))
+    }
+}
+
+impl<'de> Deserialize<'de> for TrustedState {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_struct("TrustedState", &["variant", "data"], TrustedStateVisitor)
+    }
+}
+
+/// Decodes the data of a [`TrustedState`] as the variant read before it. Self-describing formats
+/// may list the data first, it is then told apart by its fields and checked against the variant.
+struct TrustedStateVisitor;
+
+impl<'de> Visitor<'de> for TrustedStateVisitor {
+    type Value = TrustedState;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a trusted state")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<TrustedState, A::Error> {
+        let variant: u8 = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let data = match variant {
+            EPOCH_WAYPOINT_VARIANT => seq.next_element()?.map(TrustedStateData::EpochWaypoint),
+            EPOCH_STATE_VARIANT => seq.next_element()?.map(TrustedStateData::EpochState),
+            _ => return Err(invalid_variant(variant)),
+        }
+        .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        Ok(TrustedState { variant, data })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<TrustedState, A::Error> {
+        let mut variant: Option<u8> = None;
+        let mut data: Option<TrustedStateData> = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "variant" => variant = Some(map.next_value()?),
+                "data" => data = Some(map.next_value()?),
+                _ => {
+                    map.next_value::<de::IgnoredAny>()?;
+                },
+            }
+        }
+        let variant = variant.ok_or_else(|| de::Error::missing_field("variant"))?;
+        let data = data.ok_or_else(|| de::Error::missing_field("data"))?;
+        match (variant, &data) {
+            (EPOCH_WAYPOINT_VARIANT, TrustedStateData::EpochWaypoint(_))
+            | (EPOCH_STATE_VARIANT, TrustedStateData::EpochState(_)) => {
+                Ok(TrustedState { variant, data })
+            },
+            (EPOCH_WAYPOINT_VARIANT | EPOCH_STATE_VARIANT, _) => Err(de::Error::custom(format!(
+                "data of trusted state variant {} has the fields of another variant",
+                variant
+            ))),
+            _ => Err(invalid_variant(variant)),
+        }
+    }
+}
+
+fn invalid_variant<E: de::Error>(variant: u8) -> E {
+    E::invalid_value(
+        de::Unexpected::Unsigned(variant.into()),
+        &"trusted state variant 0 or 1",
+    )
+}
+
+/// Fields of the variant of a [`TrustedState`]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, Union)]
+#[serde(untagged)]
+pub enum TrustedStateData {
+    // Listed first so that data with an epoch state isn't taken for a waypoint alone
+    EpochState(EpochStateData),
+    EpochWaypoint(EpochWaypointData),
+}
+
+impl TrustedStateData {
+    pub fn waypoint(&self) -> &Waypoint {
+        match self {
+            TrustedStateData::EpochWaypoint(data) => &data.waypoint,
+            TrustedStateData::EpochState(data) => &data.waypoint,
         }
     }
+
+    /// Only set for the `EpochState` variant, a waypoint alone doesn't carry the validator set
+    pub fn epoch_state(&self) -> Option<&EpochState> {
+        match self {
+            TrustedStateData::EpochWaypoint(_) => None,
+            TrustedStateData::EpochState(data) => Some(&data.epoch_state),
+        }
+    }
+}
+
+/// Fields of the `EpochWaypoint` variant of a [`TrustedState`]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, PoemObject)]
+pub struct EpochWaypointData {
+    pub waypoint: Waypoint,
 }
 
+/// Fields of the `EpochState` variant of a [`TrustedState`]
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, PoemObject)]
-pub struct TrustedStateData {
-    waypoint: Waypoint,
-    epoch_state: EpochState,
+pub struct EpochStateData {
+    pub waypoint: Waypoint,
+    pub epoch_state: EpochState,
 }
 
 impl From<aptos_types::trusted_state::TrustedState> for TrustedState {
     fn from(value: aptos_types::trusted_state::TrustedState) -> Self {
         match value {
-            aptos_types::trusted_state::TrustedState::EpochWaypoint(_) => {
-                unimplemented!("Cannot handle TrustedState::EpochWaypoint")
+            aptos_types::trusted_state::TrustedState::EpochWaypoint(waypoint) => {
+                TrustedState::new_epoch_waypoint(waypoint.into())
             },
             aptos_types::trusted_state::TrustedState::EpochState {
                 epoch_state,
                 waypoint,
-            } => TrustedState {
-                variant: 1,
-                data: TrustedStateData {
-                    epoch_state: epoch_state.into(),
-                    waypoint: waypoint.into(),
-                },
+            } => TrustedState::new_epoch_state(waypoint.into(), epoch_state.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TrustedState;
+    use crate::{
+        ledger_info::EpochState as EpochStateView,
+        test_utils::{ledger_info, validator, TestEpochChain},
+        verify::VerifyError,
+        waypoint::Waypoint as WaypointView,
+    };
+    use aptos_types::{
+        epoch_change::EpochChangeProof, epoch_state::EpochState,
+        validator_verifier::ValidatorVerifier, waypoint::Waypoint,
+    };
+    use serde::Serialize;
+
+    /// [`TrustedState`] as encoded before the `EpochWaypoint` variant was supported, when the
+    /// data only had the fields of the `EpochState` variant
+    #[derive(Serialize)]
+    struct EpochStateOnlyTrustedState {
+        variant: u8,
+        data: EpochStateOnlyData,
+    }
+
+    #[derive(Serialize)]
+    struct EpochStateOnlyData {
+        waypoint: WaypointView,
+        epoch_state: EpochStateView,
+    }
+
+    #[test]
+    fn test_trusted_state_bcs_round_trip() {
+        let epoch_state = EpochState::new(1, ValidatorVerifier::new(vec![]));
+        let waypoint =
+            Waypoint::new_epoch_boundary(&ledger_info(0, 0, Some(epoch_state.clone()))).unwrap();
+
+        let epoch_waypoint = TrustedState::from(
+            aptos_types::trusted_state::TrustedState::EpochWaypoint(waypoint),
+        );
+        assert_eq!(
+            epoch_waypoint,
+            TrustedState::new_epoch_waypoint(waypoint.into())
+        );
+
+        let in_epoch = TrustedState::from(aptos_types::trusted_state::TrustedState::EpochState {
+            waypoint,
+            epoch_state: epoch_state.clone(),
+        });
+        assert_eq!(
+            in_epoch,
+            TrustedState::new_epoch_state(waypoint.into(), epoch_state.into())
+        );
+
+        for trusted_state in [epoch_waypoint, in_epoch] {
+            let bytes = bcs::to_bytes(&trusted_state).unwrap();
+            assert_eq!(
+                bcs::from_bytes::<TrustedState>(&bytes).unwrap(),
+                trusted_state
+            );
+        }
+    }
+
+    #[test]
+    fn test_trusted_state_epoch_state_layout() {
+        let epoch_state = EpochState::new(1, ValidatorVerifier::new(vec![]));
+        let waypoint =
+            Waypoint::new_epoch_boundary(&ledger_info(0, 0, Some(epoch_state.clone()))).unwrap();
+        let old = EpochStateOnlyTrustedState {
+            variant: 1,
+            data: EpochStateOnlyData {
+                waypoint: waypoint.into(),
+                epoch_state: epoch_state.clone().into(),
             },
+        };
+        let expected = TrustedState::new_epoch_state(waypoint.into(), epoch_state.into());
+
+        let bytes = bcs::to_bytes(&old).unwrap();
+        assert_eq!(bcs::from_bytes::<TrustedState>(&bytes).unwrap(), expected);
+        assert_eq!(bcs::to_bytes(&expected).unwrap(), bytes);
+
+        let json = serde_json::to_value(&old).unwrap();
+        assert_eq!(
+            serde_json::from_value::<TrustedState>(json.clone()).unwrap(),
+            expected
+        );
+        assert_eq!(serde_json::to_value(&expected).unwrap(), json);
+
+        // The data must be the one of the variant
+        let mut mismatch = json;
+        mismatch["variant"] = 0.into();
+        assert!(serde_json::from_value::<TrustedState>(mismatch).is_err());
+        let mut bytes = bcs::to_bytes(&old).unwrap();
+        bytes[0] = 2;
+        assert!(bcs::from_bytes::<TrustedState>(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_trusted_state_accessors() {
+        let epoch_state = EpochState::new(1, ValidatorVerifier::new(vec![]));
+        let waypoint =
+            Waypoint::new_epoch_boundary(&ledger_info(0, 0, Some(epoch_state.clone()))).unwrap();
+
+        let epoch_waypoint = TrustedState::new_epoch_waypoint(waypoint.into());
+        let in_epoch = TrustedState::new_epoch_state(waypoint.into(), epoch_state.clone().into());
+        for trusted_state in [epoch_waypoint, in_epoch] {
+            let trusted_state: TrustedState =
+                bcs::from_bytes(&bcs::to_bytes(&trusted_state).unwrap()).unwrap();
+            assert_eq!(trusted_state.data.waypoint().version(), waypoint.version());
+            assert_eq!(
+                trusted_state.data.waypoint().value(),
+                waypoint.value().into()
+            );
+            match trusted_state.variant {
+                0 => {
+                    assert_eq!(
+                        trusted_state.as_waypoint(),
+                        Some(trusted_state.data.waypoint())
+                    );
+                    assert_eq!(trusted_state.as_epoch_state(), None);
+                    assert_eq!(trusted_state.data.epoch_state(), None);
+                },
+                _ => {
+                    assert_eq!(trusted_state.as_waypoint(), None);
+                    let expected = EpochStateView::from(epoch_state.clone());
+                    assert_eq!(trusted_state.as_epoch_state(), Some(&expected));
+                    assert_eq!(trusted_state.data.epoch_state(), Some(&expected));
+                },
+            }
         }
     }
+
+    #[test]
+    fn test_verify_and_ratchet() {
+        let chain = TestEpochChain::new(1, 3);
+        let ledger_infos = &chain.proof.ledger_info_with_sigs;
+        let latest_waypoint = Waypoint::new_epoch_boundary(ledger_infos[2].ledger_info()).unwrap();
+        let expected =
+            TrustedState::new_epoch_state(latest_waypoint.into(), chain.latest.clone().into());
+
+        let waypoint = Waypoint::new_epoch_boundary(ledger_infos[0].ledger_info()).unwrap();
+        let in_epoch = TrustedState::new_epoch_state(
+            Waypoint::new_any(&ledger_info(0, 0, Some(chain.trusted.clone()))).into(),
+            chain.trusted.clone().into(),
+        );
+        let epoch_waypoint = TrustedState::new_epoch_waypoint(waypoint.into());
+        for trusted_state in [&in_epoch, &epoch_waypoint] {
+            assert_eq!(
+                trusted_state.verify_and_ratchet(&chain.proof),
+                Ok(expected.clone())
+            );
+        }
+
+        // Trusting the latest epoch already, the proof changes nothing
+        assert_eq!(
+            expected.verify_and_ratchet(&chain.proof),
+            Ok(expected.clone())
+        );
+
+        // A waypoint proves its own ledger info, and nothing past it
+        let first_only = EpochChangeProof::new(vec![ledger_infos[0].clone()], false);
+        let next_epoch_state = ledger_infos[0]
+            .ledger_info()
+            .next_epoch_state()
+            .unwrap()
+            .clone();
+        assert_eq!(
+            epoch_waypoint.verify_and_ratchet(&first_only),
+            Ok(TrustedState::new_epoch_state(
+                waypoint.into(),
+                next_epoch_state.into()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_verify_and_ratchet_failures() {
+        let chain = TestEpochChain::new(1, 3);
+        let ledger_infos = &chain.proof.ledger_info_with_sigs;
+
+        let epoch_waypoint = TrustedState::new_epoch_waypoint(
+            Waypoint::new_epoch_boundary(ledger_infos[1].ledger_info())
+                .unwrap()
+                .into(),
+        );
+        assert!(matches!(
+            epoch_waypoint.verify_and_ratchet(&chain.proof),
+            Err(VerifyError::WaypointMismatch { .. })
+        ));
+        assert_eq!(
+            epoch_waypoint.verify_and_ratchet(&EpochChangeProof::new(vec![], false)),
+            Err(VerifyError::EmptyEpochChangeProof)
+        );
+
+        // Validators other than the ones of epoch 1 didn't sign its ledger info
+        let (_, other_verifier) = validator(9);
+        let other_validators = TrustedState::new_epoch_state(
+            Waypoint::new_any(&ledger_info(0, 0, None)).into(),
+            EpochState::new(1, other_verifier).into(),
+        );
+        assert!(matches!(
+            other_validators.verify_and_ratchet(&chain.proof),
+            Err(VerifyError::InvalidSignatures { epoch: 1, .. })
+        ));
+    }
 }
@@ -0,0 +1,622 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Client-side verification of the proofs served by the API
+
+use crate::epoch_state_store::{EpochStateStore, InMemoryEpochStateStore};
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_types::{
+    aggregate_signature::AggregateSignature,
+    epoch_change::EpochChangeProof,
+    epoch_state::EpochState,
+    ledger_info::LedgerInfoWithSignatures,
+    validator_verifier::{self, ValidatorVerifier},
+    waypoint::Waypoint,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Errors possible while verifying a proof
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum VerifyError {
+    #[error("The EpochChangeProof is empty")]
+    EmptyEpochChangeProof,
+    #[error(
+        "EpochChangeProof has {actual} LedgerInfos for {expected} requested epochs (more: {more})"
+    )]
+    EpochSpanMismatch {
+        expected: u64,
+        actual: u64,
+        more: bool,
+    },
+    #[error("LedgerInfo has unexpected epoch {actual}, expected {expected}")]
+    UnexpectedEpoch { expected: u64, actual: u64 },
+    #[error("LedgerInfo ending epoch {epoch} doesn't carry a next EpochState")]
+    MissingNextEpochState { epoch: u64 },
+    #[error("LedgerInfo ending epoch {epoch} is followed by epoch {next_epoch}")]
+    NonIncrementingEpoch { epoch: u64, next_epoch: u64 },
+    #[error(
+        "LedgerInfo ending epoch {epoch} has timestamp {timestamp_usecs}, before the previous \
+         {previous_timestamp_usecs}"
+    )]
+    TimestampDecreased {
+        epoch: u64,
+        timestamp_usecs: u64,
+        previous_timestamp_usecs: u64,
+    },
+    #[error("Invalid signatures on the LedgerInfo for epoch {epoch}: {error}")]
+    InvalidSignatures {
+        epoch: u64,
+        error: validator_verifier::VerifyError,
+    },
+    #[error("The genesis LedgerInfo carries no signatures, verify it against a genesis Waypoint")]
+    UnsignedGenesis,
+    #[error(
+        "Signers bitmask of the LedgerInfo for epoch {epoch} has {num_bits} bits, too few for \
+         {num_validators} validators"
+    )]
+    BitmaskTooShort {
+        epoch: u64,
+        num_bits: usize,
+        num_validators: usize,
+    },
+    #[error(
+        "Signers bitmask of the LedgerInfo for epoch {epoch} sets bit {bit}, beyond its \
+         {num_validators} validators"
+    )]
+    BitmaskOutOfRange {
+        epoch: u64,
+        bit: usize,
+        num_validators: usize,
+    },
+    #[error("The attached ValidatorVerifier is not the trusted one for epoch {epoch}")]
+    ValidatorVerifierMismatch { epoch: u64 },
+    #[error("Proved version {version} is ahead of the signed LedgerInfo version {ledger_version}")]
+    VersionAheadOfLedgerInfo { version: u64, ledger_version: u64 },
+    #[error("Proved version {version} is older than the oldest version {oldest_version}")]
+    VersionPruned { version: u64, oldest_version: u64 },
+    #[error("LedgerInfo version {version} is older than the trusted version {trusted_version}")]
+    StaleLedgerInfo { version: u64, trusted_version: u64 },
+    #[error("LedgerInfo hashes to {actual}, but the externally trusted one hashes to {expected}")]
+    LedgerInfoMismatch {
+        expected: HashValue,
+        actual: HashValue,
+    },
+    #[error("LedgerInfo doesn't match the trusted Waypoint: {error}")]
+    WaypointMismatch { error: String },
+    #[error("Invalid trusted EpochState: {error}")]
+    InvalidTrustedState { error: String },
+    #[error("Embedded Waypoint has version {actual}, but its LedgerInfo is at version {expected}")]
+    WaypointVersionMismatch { expected: u64, actual: u64 },
+    #[error("Embedded Waypoint has value {actual}, but its LedgerInfo converts to {expected}")]
+    WaypointValueMismatch {
+        expected: HashValue,
+        actual: HashValue,
+    },
+    #[error(
+        "Transaction accumulator proof has {num_siblings} siblings, but {expected} are expected in \
+        the accumulator of ledger version {ledger_version}"
+    )]
+    AccumulatorDepthMismatch {
+        ledger_version: u64,
+        num_siblings: usize,
+        expected: usize,
+    },
+    #[error(
+        "Sub-proof {index} of the batch isn't anchored to the same LedgerInfo and version as the \
+         first one"
+    )]
+    InconsistentBatch { index: usize },
+    #[error("Invalid transaction accumulator proof for version {version}: {error}")]
+    InvalidTransactionProof { version: u64, error: String },
+    #[error("Invalid event accumulator proof for event {index} of version {version}: {error}")]
+    InvalidEventProof {
+        version: u64,
+        index: u64,
+        error: String,
+    },
+    #[error("TransactionInfo at version {version} has no state checkpoint hash")]
+    MissingStateCheckpoint { version: u64 },
+    #[error("The proof of version {version} was served without its transaction proof")]
+    MissingTransactionProof { version: u64 },
+    #[error("Sparse merkle proof has {num_siblings} siblings, more than the maximum {max}")]
+    TooManySiblings { num_siblings: usize, max: usize },
+    #[error("Malformed compressed sparse merkle proof siblings: {error}")]
+    MalformedCompressedSiblings { error: String },
+    #[error(
+        "The state proof leaf has value hash {leaf_value_hash}, but {element_hash} is claimed"
+    )]
+    LeafValueMismatch {
+        leaf_value_hash: HashValue,
+        element_hash: HashValue,
+    },
+    #[error("Invalid sparse merkle proof: {error}")]
+    InvalidStateProof { error: String },
+    #[error("The resource is reported as changed: {actual}, but its proofs show {expected}")]
+    ChangedFlagMismatch { expected: bool, actual: bool },
+    #[error("Unable to decode the StateKey: {error}")]
+    InvalidStateKey { error: String },
+    #[error("StateKey hashes to {actual}, expected {expected}")]
+    StateKeyMismatch {
+        expected: HashValue,
+        actual: HashValue,
+    },
+    #[error("Missing StateValue chunk {index}")]
+    MissingStateValueChunk { index: u64 },
+    #[error("Reassembled StateValue hashes to {actual}, expected content hash {expected}")]
+    ContentHashMismatch {
+        expected: HashValue,
+        actual: HashValue,
+    },
+    #[error("Unable to decode the StateValue: {error}")]
+    InvalidStateValue { error: String },
+    #[error("StateValue hashes to {actual}, expected {expected}")]
+    StateValueMismatch {
+        expected: HashValue,
+        actual: HashValue,
+    },
+}
+
+/// Authenticates signed ledger infos, usually with the validators of a trusted epoch
+pub trait LedgerInfoVerifier {
+    fn verify_ledger_info(
+        &self,
+        ledger_info_with_sigs: &LedgerInfoWithSignatures,
+    ) -> Result<(), VerifyError>;
+}
+
+impl LedgerInfoVerifier for EpochState {
+    fn verify_ledger_info(
+        &self,
+        ledger_info_with_sigs: &LedgerInfoWithSignatures,
+    ) -> Result<(), VerifyError> {
+        verify_ledger_info(self, ledger_info_with_sigs)
+    }
+}
+
+/// Verifies that `ledger_info_with_sigs` belongs to the epoch of `epoch_state` and carries a
+/// quorum of its validators' signatures.
+pub fn verify_ledger_info(
+    epoch_state: &EpochState,
+    ledger_info_with_sigs: &LedgerInfoWithSignatures,
+) -> Result<(), VerifyError> {
+    let epoch = ledger_info_with_sigs.ledger_info().epoch();
+    // Genesis is authenticated by a waypoint, checking its missing signatures would only fail on
+    // the voting power
+    if epoch == 0 && ledger_info_with_sigs.signatures().get_num_voters() == 0 {
+        return Err(VerifyError::UnsignedGenesis);
+    }
+    if epoch != epoch_state.epoch {
+        return Err(VerifyError::UnexpectedEpoch {
+            expected: epoch_state.epoch,
+            actual: epoch,
+        });
+    }
+    check_signers_bitmask(
+        epoch,
+        &epoch_state.verifier,
+        ledger_info_with_sigs.signatures(),
+    )?;
+    ledger_info_with_sigs
+        .verify_signatures(&epoch_state.verifier)
+        .map_err(|error| VerifyError::InvalidSignatures { epoch, error })
+}
+
+/// Checks that the signers bitmask of `signatures` has a bit for every validator of `verifier`,
+/// and none set for a validator beyond it.
+fn check_signers_bitmask(
+    epoch: u64,
+    verifier: &ValidatorVerifier,
+    signatures: &AggregateSignature,
+) -> Result<(), VerifyError> {
+    let bitmask = signatures.get_signers_bitvec();
+    let num_bits = bitmask.num_buckets() * 8;
+    let num_validators = verifier.len();
+    if num_bits < num_validators {
+        return Err(VerifyError::BitmaskTooShort {
+            epoch,
+            num_bits,
+            num_validators,
+        });
+    }
+    match bitmask.last_set_bit() {
+        Some(bit) if bit as usize >= num_validators => Err(VerifyError::BitmaskOutOfRange {
+            epoch,
+            bit: bit as usize,
+            num_validators,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Checks that `proof` has one ledger info for every epoch in `[start_epoch, end_epoch)`, the span
+/// it was requested for. A server may return fewer when it caps the response, but must then set
+/// `more`.
+pub fn verify_epoch_span(
+    proof: &EpochChangeProof,
+    start_epoch: u64,
+    end_epoch: u64,
+) -> Result<(), VerifyError> {
+    let expected = end_epoch.saturating_sub(start_epoch);
+    let actual = proof.ledger_info_with_sigs.len() as u64;
+    if actual > expected || (actual < expected && !proof.more) {
+        return Err(VerifyError::EpochSpanMismatch {
+            expected,
+            actual,
+            more: proof.more,
+        });
+    }
+    Ok(())
+}
+
+/// Ratchets the trusted `epoch_state` through `proof`, returning the epoch state of the last epoch
+/// the proof reaches.
+///
+/// Ledger infos from epochs older than `epoch_state` are skipped. Epoch states already present in
+/// `store` are trusted without re-checking signatures, and every newly verified one is added to it.
+pub fn ratchet_epoch_state(
+    epoch_state: &EpochState,
+    proof: &EpochChangeProof,
+    store: &mut dyn EpochStateStore,
+) -> Result<EpochState, VerifyError> {
+    ratchet(epoch_state, proof, store, |_, _| {})
+}
+
+/// One epoch change of a [`TrustLineage`]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TrustLink {
+    /// The epoch ended by the ledger info
+    pub epoch: u64,
+    /// SHA3-256 hash of the BCS encoded validator set of `epoch`, which signed the ledger info
+    pub validator_set_hash: HashValue,
+    /// Hash of the signed ledger info
+    pub ledger_info_hash: HashValue,
+    /// Waypoint of the ledger info, from which the next epoch is trusted
+    pub waypoint: Waypoint,
+}
+
+/// Audit trail of a ratchet through an epoch change proof: the unbroken chain of trust from the
+/// initially trusted epoch state to the latest one, one link per epoch crossed.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TrustLineage {
+    pub links: Vec<TrustLink>,
+    /// The epoch state the lineage leads to
+    pub latest: EpochState,
+}
+
+/// Same as [`ratchet_epoch_state`], also recording the [`TrustLineage`] followed.
+///
+/// No epoch state store is consulted, so the signatures of every ledger info in the lineage are
+/// checked.
+pub fn ratchet_epoch_state_with_lineage(
+    epoch_state: &EpochState,
+    proof: &EpochChangeProof,
+) -> Result<TrustLineage, VerifyError> {
+    let mut links = vec![];
+    let latest = ratchet(
+        epoch_state,
+        proof,
+        &mut InMemoryEpochStateStore::new(0),
+        |signing_epoch_state, ledger_info_with_sigs| {
+            let ledger_info = ledger_info_with_sigs.ledger_info();
+            links.push(TrustLink {
+                epoch: ledger_info.epoch(),
+                validator_set_hash: HashValue::sha3_256_of(
+                    &bcs::to_bytes(&signing_epoch_state.verifier)
+                        .expect("Validator sets are serializable"),
+                ),
+                ledger_info_hash: ledger_info.hash(),
+                waypoint: Waypoint::new_epoch_boundary(ledger_info)
+                    .expect("Ledger infos ending an epoch convert to waypoints"),
+            });
+        },
+    )?;
+    Ok(TrustLineage { links, latest })
+}
+
+/// Ratchets like [`ratchet_epoch_state`], calling `on_epoch_change` with the epoch state each
+/// ledger info was authenticated with, once it is.
+fn ratchet(
+    epoch_state: &EpochState,
+    proof: &EpochChangeProof,
+    store: &mut dyn EpochStateStore,
+    mut on_epoch_change: impl FnMut(&EpochState, &LedgerInfoWithSignatures),
+) -> Result<EpochState, VerifyError> {
+    if proof.ledger_info_with_sigs.is_empty() {
+        return Err(VerifyError::EmptyEpochChangeProof);
+    }
+
+    let mut current = epoch_state.clone();
+    let mut previous_timestamp_usecs = 0;
+    for ledger_info_with_sigs in proof
+        .ledger_info_with_sigs
+        .iter()
+        .skip_while(|li| li.ledger_info().epoch() < epoch_state.epoch)
+    {
+        let ledger_info = ledger_info_with_sigs.ledger_info();
+        if ledger_info.epoch() != current.epoch {
+            return Err(VerifyError::UnexpectedEpoch {
+                expected: current.epoch,
+                actual: ledger_info.epoch(),
+            });
+        }
+        let next_epoch_state =
+            ledger_info
+                .next_epoch_state()
+                .ok_or(VerifyError::MissingNextEpochState {
+                    epoch: ledger_info.epoch(),
+                })?;
+        if next_epoch_state.epoch != ledger_info.epoch() + 1 {
+            return Err(VerifyError::NonIncrementingEpoch {
+                epoch: ledger_info.epoch(),
+                next_epoch: next_epoch_state.epoch,
+            });
+        }
+        if ledger_info.timestamp_usecs() < previous_timestamp_usecs {
+            return Err(VerifyError::TimestampDecreased {
+                epoch: ledger_info.epoch(),
+                timestamp_usecs: ledger_info.timestamp_usecs(),
+                previous_timestamp_usecs,
+            });
+        }
+        previous_timestamp_usecs = ledger_info.timestamp_usecs();
+
+        if !store.contains(next_epoch_state) {
+            verify_ledger_info(&current, ledger_info_with_sigs)?;
+            store.insert(next_epoch_state.clone());
+        }
+        on_epoch_change(&current, ledger_info_with_sigs);
+        current = next_epoch_state.clone();
+    }
+    Ok(current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ratchet_epoch_state, ratchet_epoch_state_with_lineage, verify_epoch_span,
+        verify_ledger_info, TrustLineage, VerifyError,
+    };
+    use crate::{
+        epoch_state_store::{EpochStateStore, InMemoryEpochStateStore},
+        test_utils::{ledger_info, sign, validator, TestEpochChain},
+    };
+    use aptos_bitvec::BitVec;
+    use aptos_crypto::{hash::CryptoHash, HashValue};
+    use aptos_types::{
+        aggregate_signature::AggregateSignature, epoch_change::EpochChangeProof,
+        epoch_state::EpochState, ledger_info::LedgerInfoWithSignatures,
+    };
+
+    fn strip_signatures(proof: &EpochChangeProof) -> EpochChangeProof {
+        EpochChangeProof::new(
+            proof
+                .ledger_info_with_sigs
+                .iter()
+                .map(|li| {
+                    LedgerInfoWithSignatures::new(
+                        li.ledger_info().clone(),
+                        AggregateSignature::empty(),
+                    )
+                })
+                .collect(),
+            proof.more,
+        )
+    }
+
+    #[test]
+    fn test_ratchet_epoch_state() {
+        let chain = TestEpochChain::new(1, 3);
+        let mut store = InMemoryEpochStateStore::default();
+
+        let latest = ratchet_epoch_state(&chain.trusted, &chain.proof, &mut store).unwrap();
+        assert_eq!(latest, chain.latest);
+        assert_eq!(store.len(), 3);
+        assert_eq!(store.get(4), Some(chain.latest));
+    }
+
+    #[test]
+    fn test_cache_hit_skips_reverification() {
+        let chain = TestEpochChain::new(1, 3);
+        let mut store = InMemoryEpochStateStore::default();
+        ratchet_epoch_state(&chain.trusted, &chain.proof, &mut store).unwrap();
+
+        // Every epoch change is cached, so the signatures are never looked at again
+        let unsigned = strip_signatures(&chain.proof);
+        assert_eq!(
+            ratchet_epoch_state(&chain.trusted, &unsigned, &mut store).unwrap(),
+            chain.latest
+        );
+
+        // Without the cache the same proof is rejected
+        let mut empty_store = InMemoryEpochStateStore::default();
+        assert!(matches!(
+            ratchet_epoch_state(&chain.trusted, &unsigned, &mut empty_store),
+            Err(VerifyError::BitmaskTooShort { epoch: 1, .. })
+        ));
+        assert!(empty_store.is_empty());
+    }
+
+    #[test]
+    fn test_verify_ledger_info_rejects_malformed_bitmask() {
+        let (signer, verifier) = validator(1);
+        let epoch_state = EpochState::new(1, verifier.clone());
+        let signed = sign(&signer, &verifier, ledger_info(1, 10, None));
+        verify_ledger_info(&epoch_state, &signed).unwrap();
+        let with_bitmask = |bitmask: BitVec| {
+            LedgerInfoWithSignatures::new(
+                signed.ledger_info().clone(),
+                AggregateSignature::new(bitmask, signed.signatures().sig().clone()),
+            )
+        };
+
+        // No bit for the only validator
+        assert_eq!(
+            verify_ledger_info(&epoch_state, &with_bitmask(BitVec::with_num_bits(0))),
+            Err(VerifyError::BitmaskTooShort {
+                epoch: 1,
+                num_bits: 0,
+                num_validators: 1,
+            })
+        );
+
+        // A signature from a validator that doesn't exist
+        let mut out_of_range = BitVec::with_num_bits(1);
+        out_of_range.set(0);
+        out_of_range.set(3);
+        assert_eq!(
+            verify_ledger_info(&epoch_state, &with_bitmask(out_of_range)),
+            Err(VerifyError::BitmaskOutOfRange {
+                epoch: 1,
+                bit: 3,
+                num_validators: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn test_ratchet_skips_stale_epochs() {
+        let chain = TestEpochChain::new(1, 3);
+        let mut store = InMemoryEpochStateStore::default();
+        let trusted = ratchet_epoch_state(
+            &chain.trusted,
+            &EpochChangeProof::new(chain.proof.ledger_info_with_sigs[..1].to_vec(), true),
+            &mut store,
+        )
+        .unwrap();
+        assert_eq!(trusted.epoch, 2);
+
+        let latest = ratchet_epoch_state(&trusted, &chain.proof, &mut store).unwrap();
+        assert_eq!(latest, chain.latest);
+    }
+
+    #[test]
+    fn test_ratchet_epoch_state_with_lineage() {
+        let chain = TestEpochChain::new(1, 3);
+        let lineage = ratchet_epoch_state_with_lineage(&chain.trusted, &chain.proof).unwrap();
+        assert_eq!(lineage.links.len(), 3);
+        assert_eq!(lineage.latest, chain.latest);
+
+        let mut verifier = chain.trusted.verifier.clone();
+        for (link, ledger_info) in lineage.links.iter().zip(&chain.proof.ledger_info_with_sigs) {
+            let ledger_info = ledger_info.ledger_info();
+            assert_eq!(link.epoch, ledger_info.epoch());
+            assert_eq!(
+                link.validator_set_hash,
+                HashValue::sha3_256_of(&bcs::to_bytes(&verifier).unwrap())
+            );
+            assert_eq!(link.ledger_info_hash, ledger_info.hash());
+            link.waypoint.verify(ledger_info).unwrap();
+            verifier = ledger_info.next_epoch_state().unwrap().verifier.clone();
+        }
+
+        // Round-trips through serde for the auditors
+        let json = serde_json::to_string(&lineage).unwrap();
+        assert_eq!(
+            serde_json::from_str::<TrustLineage>(&json).unwrap(),
+            lineage
+        );
+    }
+
+    #[test]
+    fn test_empty_proof() {
+        let chain = TestEpochChain::new(1, 1);
+        assert_eq!(
+            ratchet_epoch_state(
+                &chain.trusted,
+                &EpochChangeProof::new(vec![], false),
+                &mut InMemoryEpochStateStore::default()
+            ),
+            Err(VerifyError::EmptyEpochChangeProof)
+        );
+    }
+
+    #[test]
+    fn test_non_incrementing_epoch() {
+        let (signer, verifier) = validator(1);
+        let trusted = EpochState::new(1, verifier.clone());
+        let mut store = InMemoryEpochStateStore::default();
+
+        for next_epoch in [1, 3] {
+            let next_epoch_state = EpochState::new(next_epoch, validator(2).1);
+            let proof = EpochChangeProof::new(
+                vec![sign(
+                    &signer,
+                    &verifier,
+                    ledger_info(1, 99, Some(next_epoch_state)),
+                )],
+                false,
+            );
+            assert_eq!(
+                ratchet_epoch_state(&trusted, &proof, &mut store),
+                Err(VerifyError::NonIncrementingEpoch {
+                    epoch: 1,
+                    next_epoch
+                })
+            );
+        }
+        assert!(store.is_empty());
+    }
+
+    #[test]
+    fn test_decreasing_timestamp() {
+        let mut ledger_infos = vec![];
+        let (mut signer, mut verifier) = validator(1);
+        let trusted = EpochState::new(1, verifier.clone());
+        // The ledger info ending epoch 2 is dated before the one ending epoch 1
+        for (epoch, version) in [(1, 199), (2, 150), (3, 399)] {
+            let (next_signer, next_verifier) = validator(epoch + 1);
+            let next_epoch_state = EpochState::new(epoch + 1, next_verifier.clone());
+            ledger_infos.push(sign(
+                &signer,
+                &verifier,
+                ledger_info(epoch, version, Some(next_epoch_state)),
+            ));
+            signer = next_signer;
+            verifier = next_verifier;
+        }
+
+        let mut store = InMemoryEpochStateStore::default();
+        assert_eq!(
+            ratchet_epoch_state(
+                &trusted,
+                &EpochChangeProof::new(ledger_infos, false),
+                &mut store
+            ),
+            Err(VerifyError::TimestampDecreased {
+                epoch: 2,
+                timestamp_usecs: 150_000,
+                previous_timestamp_usecs: 199_000,
+            })
+        );
+        assert_eq!(store.len(), 1);
+    }
+
+    #[test]
+    fn test_verify_epoch_span() {
+        let chain = TestEpochChain::new(1, 3);
+        verify_epoch_span(&chain.proof, 1, 4).unwrap();
+
+        let truncated =
+            |more| EpochChangeProof::new(chain.proof.ledger_info_with_sigs[..2].to_vec(), more);
+        // A capped response is fine as long as the server says there is more
+        verify_epoch_span(&truncated(true), 1, 4).unwrap();
+        assert_eq!(
+            verify_epoch_span(&truncated(false), 1, 4),
+            Err(VerifyError::EpochSpanMismatch {
+                expected: 3,
+                actual: 2,
+                more: false,
+            })
+        );
+
+        assert_eq!(
+            verify_epoch_span(&chain.proof, 1, 3),
+            Err(VerifyError::EpochSpanMismatch {
+                expected: 2,
+                actual: 3,
+                more: false,
+            })
+        );
+    }
+}
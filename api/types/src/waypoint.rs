@@ -1,6 +1,8 @@
 use crate::{HashValue, U64};
+use anyhow::{format_err, Context};
 use poem_openapi::Object as PoemObject;
 use serde::{Deserialize, Serialize};
+use std::{convert::TryFrom, fmt, str::FromStr};
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, PoemObject)]
 pub struct Waypoint {
@@ -8,6 +10,23 @@ pub struct Waypoint {
     value: HashValue,
 }
 
+impl Waypoint {
+    pub fn new(version: u64, value: HashValue) -> Self {
+        Self {
+            version: version.into(),
+            value,
+        }
+    }
+
+    pub fn version(&self) -> u64 {
+        self.version.0
+    }
+
+    pub fn value(&self) -> HashValue {
+        self.value
+    }
+}
+
 impl From<aptos_types::waypoint::Waypoint> for Waypoint {
     fn from(value: aptos_types::waypoint::Waypoint) -> Self {
         Self {
@@ -16,3 +35,84 @@ impl From<aptos_types::waypoint::Waypoint> for Waypoint {
         }
     }
 }
+
+/// Parses the canonical `version:hash` form, the hash with or without a `0x` prefix
+impl FromStr for Waypoint {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let (version, value) = s
+            .split_once(':')
+            .ok_or_else(|| format_err!("Waypoint {} isn't of the form version:hash", s))?;
+        Ok(Self {
+            version: version
+                .parse::<u64>()
+                .with_context(|| format!("Invalid waypoint version {}", version))?
+                .into(),
+            value: value
+                .parse()
+                .with_context(|| format!("Invalid waypoint hash {}", value))?,
+        })
+    }
+}
+
+impl TryFrom<&str> for Waypoint {
+    type Error = anyhow::Error;
+
+    fn try_from(s: &str) -> anyhow::Result<Self> {
+        s.parse()
+    }
+}
+
+/// Emits the canonical `version:hash` form, as [`aptos_types::waypoint::Waypoint`] does
+impl fmt::Display for Waypoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.version.0, self.value.0.to_hex())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Waypoint;
+    use aptos_crypto::HashValue;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_round_trip() {
+        let waypoint =
+            aptos_types::waypoint::Waypoint::new_any(&aptos_types::ledger_info::LedgerInfo::new(
+                aptos_types::block_info::BlockInfo::empty(),
+                HashValue::zero(),
+            ));
+        let view = Waypoint::from(waypoint);
+        assert_eq!(view.to_string(), waypoint.to_string());
+        assert_eq!(view.to_string().parse::<Waypoint>().unwrap(), view);
+        assert_eq!(
+            Waypoint::try_from(format!("0:0x{}", waypoint.value().to_hex()).as_str()).unwrap(),
+            view
+        );
+    }
+
+    #[test]
+    fn test_accessors() {
+        let value = HashValue::sha3_256_of(b"waypoint");
+        let waypoint = Waypoint::new(42, value.into());
+        assert_eq!(waypoint.version(), 42);
+        assert_eq!(waypoint.value(), value.into());
+        assert_eq!(waypoint.to_string(), format!("42:{}", value.to_hex()));
+    }
+
+    #[test]
+    fn test_invalid() {
+        let hash = HashValue::zero().to_hex();
+        for invalid in [
+            hash.clone(),
+            format!("-1:{}", hash),
+            format!("a:{}", hash),
+            "0:1234".to_string(),
+            format!("0:{}:0", hash),
+        ] {
+            assert!(invalid.parse::<Waypoint>().is_err(), "{}", invalid);
+        }
+    }
+}
@@ -0,0 +1,82 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Checks that a batch of account proofs is verified in memory bounded by a single proof. Kept in
+//! its own test binary, as it replaces the global allocator to measure the memory allocated.
+
+use aptos_api_types::{
+    proof_payload::{verify_encoded_batch_account_proof, StateProofPayload},
+    self_test::fixtures,
+};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+/// Allocator tracking the memory allocated by each thread
+struct PeakTrackingAllocator;
+
+thread_local! {
+    static ALLOCATED: Cell<usize> = const { Cell::new(0) };
+    static PEAK_ALLOCATED: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for PeakTrackingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        // Thread locals are unavailable while the thread is torn down
+        let _ = ALLOCATED.try_with(|allocated| {
+            allocated.set(allocated.get() + layout.size());
+            let _ = PEAK_ALLOCATED.try_with(|peak| peak.set(peak.get().max(allocated.get())));
+        });
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        // Memory may be freed by another thread than the one that allocated it
+        let _ = ALLOCATED
+            .try_with(|allocated| allocated.set(allocated.get().saturating_sub(layout.size())));
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: PeakTrackingAllocator = PeakTrackingAllocator;
+
+/// Runs `f`, also returning the peak memory it allocated on the current thread
+fn peak_allocated<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let baseline = ALLOCATED.with(Cell::get);
+    PEAK_ALLOCATED.with(|peak| peak.set(baseline));
+    let output = f();
+    (output, PEAK_ALLOCATED.with(Cell::get) - baseline)
+}
+
+#[test]
+fn test_verify_encoded_batch_account_proof_memory() {
+    // The valid self-test proof, repeated, is a consistent batch
+    let fixture = fixtures()
+        .unwrap()
+        .into_iter()
+        .find(|fixture| fixture.name == "pass")
+        .unwrap();
+    let bytes = bcs::to_bytes(&vec![fixture.payload.clone(); 3_000]).unwrap();
+
+    // Proofs are verified as they are decoded, so memory is bounded by a single proof rather than
+    // by the whole batch
+    let mut num_results = 0;
+    let (_, streaming_peak) = peak_allocated(|| {
+        verify_encoded_batch_account_proof(&fixture.epoch_state, &bytes, |result| {
+            assert!(result.is_ok());
+            num_results += 1
+        })
+        .unwrap()
+    });
+    assert_eq!(num_results, 3_000);
+    let (_, decoding_peak) =
+        peak_allocated(|| bcs::from_bytes::<Vec<StateProofPayload>>(&bytes).unwrap());
+    assert!(
+        streaming_peak * 100 < decoding_peak,
+        "{} bytes allocated while streaming, {} while decoding",
+        streaming_peak,
+        decoding_peak
+    );
+}
@@ -49,6 +49,9 @@ pub struct ApiConfig {
     /// Enables transaction simulation
     #[serde(default = "default_enabled")]
     pub transaction_simulation_enabled: bool,
+    /// Enables the developer tools APIs, helpers for debugging client implementations
+    #[serde(default = "default_disabled")]
+    pub developer_tools_enabled: bool,
     /// Maximum number of transactions that can be sent with the Batch submit API
     pub max_submit_transaction_batch_size: usize,
     /// Maximum page size for transaction paginated APIs
@@ -87,6 +90,37 @@ pub struct ApiConfig {
     pub wait_by_hash_poll_interval_ms: u64,
     /// The number of active wait_by_hash requests that can be active at any given time.
     pub wait_by_hash_max_active_connections: usize,
+    /// Maximum size in bytes of a state value chunk returned alongside a proof
+    pub max_state_value_chunk_size: usize,
+    /// Maximum size in bytes of an epoch change proof response
+    pub max_epoch_change_proof_size: usize,
+    /// Maximum number of epochs proven in one epoch change proof. Ranges spanning more epochs are
+    /// truncated, and proofs between waypoints further apart are rejected, bounding the epoch
+    /// ending ledger infos read per request
+    pub max_epoch_change_proof_span: usize,
+    /// Maximum number of versions a resource can be proven at in one request
+    pub max_resource_proof_versions: usize,
+    /// Resources proven along with the account resource by the account proof bundle endpoint, e.g.
+    /// `0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>`
+    pub account_proof_resources: Vec<String>,
+    /// Maximum number of resources proven in an account state delta
+    pub max_state_delta_resources: usize,
+    /// Maximum number of accounts proven in one batch account proof request
+    pub max_account_proof_batch_size: usize,
+    /// Maximum number of table items read in one batch table item request
+    pub max_table_item_batch_size: usize,
+    /// Bearer token required to access the proof APIs. If not set, they are open to everyone.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proof_auth_token: Option<String>,
+    /// Address of a second listener serving the API over TLS to clients presenting a certificate
+    /// signed by `proof_mtls_client_ca_path`. Proofs requested through it are authorized by the
+    /// client certificate, without the bearer token. Requires `tls_cert_path` and `tls_key_path`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proof_mtls_address: Option<SocketAddr>,
+    /// Path to the PEM encoded CA certificates that sign the client certificates accepted on
+    /// `proof_mtls_address`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proof_mtls_client_ca_path: Option<String>,
 }
 
 const DEFAULT_ADDRESS: &str = "127.0.0.1";
@@ -96,6 +130,17 @@ pub const DEFAULT_MAX_SUBMIT_TRANSACTION_BATCH_SIZE: usize = 10;
 pub const DEFAULT_MAX_PAGE_SIZE: u16 = 100;
 const DEFAULT_MAX_ACCOUNT_RESOURCES_PAGE_SIZE: u16 = 9999;
 const DEFAULT_MAX_ACCOUNT_MODULES_PAGE_SIZE: u16 = 9999;
+const DEFAULT_MAX_STATE_VALUE_CHUNK_SIZE: usize = 1024 * 1024; // 1 MB
+const DEFAULT_MAX_EPOCH_CHANGE_PROOF_SIZE: usize = 8 * 1024 * 1024; // 8 MB
+const DEFAULT_MAX_EPOCH_CHANGE_PROOF_SPAN: usize = 100;
+/// Most epoch ending ledger infos the DB reads at once, proofs can't span more epochs
+const MAX_EPOCH_CHANGE_PROOF_SPAN: usize = 100;
+const DEFAULT_MAX_RESOURCE_PROOF_VERSIONS: usize = 10;
+const DEFAULT_MAX_STATE_DELTA_RESOURCES: usize = 100;
+const DEFAULT_MAX_ACCOUNT_PROOF_BATCH_SIZE: usize = 100;
+const DEFAULT_MAX_TABLE_ITEM_BATCH_SIZE: usize = 100;
+const DEFAULT_ACCOUNT_PROOF_RESOURCES: &[&str] =
+    &["0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>"];
 const DEFAULT_MAX_VIEW_GAS: u64 = 2_000_000; // We keep this value the same as the max number of gas allowed for one single transaction defined in aptos-gas.
 
 fn default_enabled() -> bool {
@@ -122,6 +167,7 @@ impl Default for ApiConfig {
             encode_submission_enabled: default_enabled(),
             transaction_submission_enabled: default_enabled(),
             transaction_simulation_enabled: default_enabled(),
+            developer_tools_enabled: default_disabled(),
             max_submit_transaction_batch_size: DEFAULT_MAX_SUBMIT_TRANSACTION_BATCH_SIZE,
             max_transactions_page_size: DEFAULT_MAX_PAGE_SIZE,
             max_events_page_size: DEFAULT_MAX_PAGE_SIZE,
@@ -138,6 +184,20 @@ impl Default for ApiConfig {
             wait_by_hash_timeout_ms: 1_000,
             wait_by_hash_poll_interval_ms: 20,
             wait_by_hash_max_active_connections: 100,
+            max_state_value_chunk_size: DEFAULT_MAX_STATE_VALUE_CHUNK_SIZE,
+            max_epoch_change_proof_size: DEFAULT_MAX_EPOCH_CHANGE_PROOF_SIZE,
+            max_epoch_change_proof_span: DEFAULT_MAX_EPOCH_CHANGE_PROOF_SPAN,
+            max_resource_proof_versions: DEFAULT_MAX_RESOURCE_PROOF_VERSIONS,
+            account_proof_resources: DEFAULT_ACCOUNT_PROOF_RESOURCES
+                .iter()
+                .map(|resource| resource.to_string())
+                .collect(),
+            max_state_delta_resources: DEFAULT_MAX_STATE_DELTA_RESOURCES,
+            max_account_proof_batch_size: DEFAULT_MAX_ACCOUNT_PROOF_BATCH_SIZE,
+            max_table_item_batch_size: DEFAULT_MAX_TABLE_ITEM_BATCH_SIZE,
+            proof_auth_token: None,
+            proof_mtls_address: None,
+            proof_mtls_client_ca_path: None,
         }
     }
 }
@@ -187,6 +247,30 @@ impl ConfigSanitizer for ApiConfig {
             ));
         }
 
+        // Epoch change proofs are read from the DB in one go
+        if api_config.max_epoch_change_proof_span > MAX_EPOCH_CHANGE_PROOF_SPAN {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                format!(
+                    "max_epoch_change_proof_span must be at most {}!",
+                    MAX_EPOCH_CHANGE_PROOF_SPAN
+                ),
+            ));
+        }
+
+        // Client certificates are only verified over TLS, against the configured CA
+        if api_config.proof_mtls_address.is_some()
+            && (api_config.proof_mtls_client_ca_path.is_none()
+                || api_config.tls_cert_path.is_none()
+                || api_config.tls_key_path.is_none())
+        {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                "proof_mtls_address requires proof_mtls_client_ca_path, tls_cert_path and tls_key_path!"
+                    .into(),
+            ));
+        }
+
         // We don't support Block ID based simulation filters.
         for rule in api_config.simulation_filter.rules() {
             if let Matcher::BlockId(_) = rule.matcher() {
@@ -312,4 +396,50 @@ mod tests {
                 .unwrap_err();
         assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
     }
+
+    #[test]
+    fn test_sanitize_epoch_change_proof_span() {
+        // Create a node config with a span beyond what the DB reads at once
+        let node_config = NodeConfig {
+            api: ApiConfig {
+                enabled: true,
+                max_epoch_change_proof_span: MAX_EPOCH_CHANGE_PROOF_SPAN + 1,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // Sanitize the config and verify that it fails because
+        // the span is too large.
+        let error =
+            ApiConfig::sanitize(&node_config, NodeType::Validator, Some(ChainId::mainnet()))
+                .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+    }
+
+    #[test]
+    fn test_sanitize_proof_mtls_without_tls() {
+        // Create a node config with an mTLS listener but no server certificate
+        let mut node_config = NodeConfig {
+            api: ApiConfig {
+                enabled: true,
+                proof_mtls_address: Some("127.0.0.1:8443".parse().unwrap()),
+                proof_mtls_client_ca_path: Some("client_ca.pem".into()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // Sanitize the config and verify that it fails because
+        // client certificates can't be verified without TLS.
+        let error =
+            ApiConfig::sanitize(&node_config, NodeType::Validator, Some(ChainId::mainnet()))
+                .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+
+        // Sanitize the config with TLS enabled and verify that it succeeds
+        node_config.api.tls_cert_path = Some("cert.pem".into());
+        node_config.api.tls_key_path = Some("key.pem".into());
+        ApiConfig::sanitize(&node_config, NodeType::Validator, Some(ChainId::mainnet())).unwrap();
+    }
 }
@@ -280,6 +280,7 @@ impl From<RestError> for ApiError {
                 AptosErrorCode::StateValueNotFound => {
                     ApiError::StateValueNotFound(Some(err.error.message))
                 },
+                AptosErrorCode::EventNotFound => ApiError::InternalError(Some(err.error.message)),
                 AptosErrorCode::VersionPruned => ApiError::VersionPruned(Some(err.error.message)),
                 AptosErrorCode::BlockPruned => ApiError::BlockPruned(Some(err.error.message)),
                 AptosErrorCode::InvalidInput => ApiError::InvalidInput(Some(err.error.message)),
@@ -300,6 +301,10 @@ impl From<RestError> for ApiError {
                 AptosErrorCode::BcsNotSupported => ApiError::InvalidInput(Some(err.error.message)),
                 AptosErrorCode::InternalError => ApiError::InternalError(Some(err.error.message)),
                 AptosErrorCode::ApiDisabled => ApiError::InternalError(Some(err.error.message)),
+                AptosErrorCode::Unauthorized => ApiError::InternalError(Some(err.error.message)),
+                AptosErrorCode::ResourceDeserializationError => {
+                    ApiError::DeserializationFailed(Some(err.error.message))
+                },
             },
             RestError::Bcs(_) => ApiError::DeserializationFailed(None),
             RestError::Json(_) => ApiError::DeserializationFailed(None),
@@ -3,7 +3,7 @@
 
 use crate::{
     db::{
-        get_first_seq_num_and_limit, test_helper,
+        get_epoch_ending_page, get_first_seq_num_and_limit, test_helper,
         test_helper::{
             arb_blocks_to_commit, put_as_state_root, put_transaction_auxiliary_data,
             put_transaction_infos,
@@ -84,6 +84,20 @@ fn test_get_first_seq_num_and_limit() {
     );
 }
 
+#[test]
+fn test_get_epoch_ending_page() {
+    const LIMIT: usize = 100;
+
+    // One short of the limit: everything fits, nothing more to fetch
+    assert_eq!(get_epoch_ending_page(5, 5 + 99, LIMIT), (5 + 99, false));
+    // Exactly the limit: still a single page, so no needless final request
+    assert_eq!(get_epoch_ending_page(5, 5 + 100, LIMIT), (5 + 100, false));
+    // One past the limit: the page is truncated and the client has to come back
+    assert_eq!(get_epoch_ending_page(5, 5 + 101, LIMIT), (5 + 100, true));
+    // Empty range
+    assert_eq!(get_epoch_ending_page(5, 5, LIMIT), (5, false));
+}
+
 #[test]
 fn test_too_many_requested() {
     let tmp_dir = TempPath::new();
@@ -286,3 +286,14 @@ fn get_first_seq_num_and_limit(order: Order, cursor: u64, limit: u64) -> Result<
         (0, cursor + 1)
     })
 }
+
+// Returns the exclusive upper bound of the page of epoch ending ledger infos starting at
+// `start_epoch`, and whether there are more epochs in `[start_epoch, end_epoch)` after it. A range
+// of exactly `limit` epochs fits in a single page, so `more` is only set when it exceeds `limit`.
+fn get_epoch_ending_page(start_epoch: u64, end_epoch: u64, limit: usize) -> (u64, bool) {
+    if end_epoch - start_epoch > limit as u64 {
+        (start_epoch + limit as u64, true)
+    } else {
+        (end_epoch, false)
+    }
+}
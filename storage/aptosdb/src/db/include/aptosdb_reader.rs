@@ -298,6 +298,35 @@ impl DbReader for AptosDB {
         })
     }
 
+    fn get_event_with_proof_by_key(
+        &self,
+        event_key: &EventKey,
+        seq_num: u64,
+        ledger_version: Version,
+    ) -> Result<Option<(EventWithVersion, u64, EventAccumulatorProof)>> {
+        gauged_api("get_event_with_proof_by_key", || {
+            let Some((_seq, version, index)) = self
+                .event_store
+                .lookup_events_by_key(event_key, seq_num, 1, ledger_version)?
+                .pop()
+            else {
+                return Ok(None);
+            };
+            self.error_if_ledger_pruned("Event", version)?;
+
+            let events = self.ledger_db.event_db().get_events_by_version(version)?;
+            let event = events.get(index as usize).cloned().ok_or_else(|| {
+                AptosDbError::NotFound(format!("Event {} of Txn {}", index, version))
+            })?;
+            let proof = self.event_store.get_event_proof_by_version_and_index(
+                version,
+                events.len() as u64,
+                index,
+            )?;
+            Ok(Some((EventWithVersion::new(version, event), index, proof)))
+        })
+    }
+
     fn get_transaction_iterator(
         &self,
         start_version: Version,
@@ -860,11 +889,7 @@ impl AptosDB {
             latest_epoch - 1,  // okay to -1 because genesis LedgerInfo has .next_block_epoch() == 1
         );
 
-        let (paging_epoch, more) = if end_epoch - start_epoch > limit as u64 {
-            (start_epoch + limit as u64, true)
-        } else {
-            (end_epoch, false)
-        };
+        let (paging_epoch, more) = get_epoch_ending_page(start_epoch, end_epoch, limit);
 
         let lis = self
             .ledger_db
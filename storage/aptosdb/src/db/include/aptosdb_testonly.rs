@@ -91,6 +91,14 @@ impl AptosDB {
     pub(crate) fn state_merkle_db(&self) -> Arc<StateMerkleDb> {
         self.state_store.state_db.state_merkle_db.clone()
     }
+
+    /// Reports the ledger versions before `min_readable_version` as pruned, without deleting them.
+    pub fn set_ledger_min_readable_version_for_test(
+        &self,
+        min_readable_version: Version,
+    ) -> Result<()> {
+        self.ledger_pruner.save_min_readable_version(min_readable_version)
+    }
 }
 
 pub fn gather_state_updates_until_last_checkpoint(
@@ -52,8 +52,8 @@ use aptos_types::{
     event::EventKey,
     ledger_info::LedgerInfoWithSignatures,
     proof::{
-        accumulator::InMemoryAccumulator, AccumulatorConsistencyProof, SparseMerkleProofExt,
-        TransactionAccumulatorRangeProof, TransactionAccumulatorSummary,
+        accumulator::InMemoryAccumulator, AccumulatorConsistencyProof, EventAccumulatorProof,
+        SparseMerkleProofExt, TransactionAccumulatorRangeProof, TransactionAccumulatorSummary,
         TransactionInfoListWithProof,
     },
     state_proof::StateProof,
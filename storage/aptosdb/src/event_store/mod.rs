@@ -27,7 +27,7 @@ use aptos_types::{
     account_config::{new_block_event_key, NewBlockEvent},
     contract_event::ContractEvent,
     event::EventKey,
-    proof::position::Position,
+    proof::{position::Position, EventAccumulatorProof},
     transaction::Version,
 };
 use std::{
@@ -57,6 +57,23 @@ impl EventStore {
             .ok_or_else(|| AptosDbError::NotFound(format!("Event {} of Txn {}", index, version)))
     }
 
+    /// Proof of the event at `index` in the event accumulator of the transaction at `version`,
+    /// which emitted `num_events` events.
+    pub fn get_event_proof_by_version_and_index(
+        &self,
+        version: Version,
+        num_events: u64,
+        index: u64,
+    ) -> Result<EventAccumulatorProof> {
+        Ok(
+            MerkleAccumulator::<EventHashReader, EventAccumulatorHasher>::get_proof(
+                &EventHashReader::new(self, version),
+                num_events,
+                index,
+            )?,
+        )
+    }
+
     pub fn get_txn_ver_by_seq_num(&self, event_key: &EventKey, seq_num: u64) -> Result<u64> {
         let (ver, _) = self
             .event_db
@@ -13,8 +13,9 @@ use aptos_types::{
     event::EventKey,
     ledger_info::LedgerInfoWithSignatures,
     proof::{
-        AccumulatorConsistencyProof, SparseMerkleProof, SparseMerkleProofExt,
-        SparseMerkleRangeProof, TransactionAccumulatorRangeProof, TransactionAccumulatorSummary,
+        AccumulatorConsistencyProof, EventAccumulatorProof, SparseMerkleProof,
+        SparseMerkleProofExt, SparseMerkleRangeProof, TransactionAccumulatorRangeProof,
+        TransactionAccumulatorSummary,
     },
     state_proof::StateProof,
     state_store::{
@@ -207,6 +208,16 @@ pub trait DbReader: Send + Sync {
             ledger_version: Version,
         ) -> Result<Vec<EventWithVersion>>;
 
+        /// Returns the event with sequence number `seq_num` of `event_key`, if it was emitted at
+        /// or before `ledger_version`, along with its index among the events of the transaction
+        /// that emitted it and its proof in the event accumulator of that transaction.
+        fn get_event_with_proof_by_key(
+            &self,
+            event_key: &EventKey,
+            seq_num: u64,
+            ledger_version: Version,
+        ) -> Result<Option<(EventWithVersion, u64, EventAccumulatorProof)>>;
+
         fn get_transaction_iterator(
             &self,
             start_version: Version,